@@ -1,6 +1,7 @@
 // See https://kit.svelte.dev/docs/types#app
 
 import type { User__Output } from "$lib/proto/proto/User";
+import type { Permission__Output } from "$lib/proto/proto/Permission";
 import type { Profile__Output } from "$lib/proto/proto/Profile";
 import type { Note__Output } from "$lib/proto/proto/Note";
 
@@ -10,7 +11,12 @@ declare global {
         // interface Error {}
         interface Locals {
             token: string;
+            accessToken: string;
             user: User__Output;
+            // The admin impersonating the user, empty otherwise
+            impersonator: string;
+            // Granted by the role, see `Permission`
+            permissions: Permission__Output[];
         }
         interface PageState {
             open: boolean;
@@ -1,5 +1,5 @@
-import { COOKIE_DOMAIN } from "$env/static/private";
-import { grpcSafe } from "$lib/safe";
+import { AUTH_URI, COOKIE_DOMAIN } from "$env/static/private";
+import { grpcSafe, safe } from "$lib/safe";
 import { usersService } from "$lib/server/grpc";
 import { logger, perf } from "$lib/server/logger";
 import { createMetadata } from "$lib/server/metadata";
@@ -21,63 +21,152 @@ export async function handle({ event, resolve }) {
         subscription_end: "-infinity",
         subscription_check: "-infinity",
         subscription_active: false,
+        two_factor_enabled: false,
+        two_factor_required: false,
     };
+    event.locals.impersonator = "";
+    event.locals.permissions = [];
 
     if (event.url.pathname === "/auth") {
-        event.cookies.set("token", "", {
-            domain: COOKIE_DOMAIN,
-            path: "/",
-            maxAge: 0,
-        });
+        /**
+         * Revoke the session on the server, so the cookies can't be reused
+         * We don't check for errors, the token might be already expired
+         */
+        const token = event.cookies.get("token");
+        if (token) {
+            await new Promise((res) => {
+                usersService.Logout({}, createMetadata(token), grpcSafe(res));
+            });
+        }
+        for (const name of ["token", "access_token"]) {
+            event.cookies.set(name, "", {
+                domain: COOKIE_DOMAIN,
+                path: "/",
+                maxAge: 0,
+            });
+        }
+        return await resolve(event);
+    }
+
+    // The two-factor challenge happens before there is a session
+    if (event.url.pathname === "/auth/2fa") {
         return await resolve(event);
     }
 
     /**
      * Check if the user is coming from the oauth flow
-     * If so, set a temporary cookie with the token
+     * If so, exchange the one-time code for the token and set a temporary cookie with it
      * On the next request, the new token will be used
      */
-    let token = event.url.searchParams.get("token");
-    if (token) {
-        event.cookies.set("token", token, {
+    const code = event.url.searchParams.get("code");
+    if (code) {
+        /** @type {import("$lib/safe").Safe<{ token: string, return_to: string }>} */
+        const exchange = await safe(
+            fetch(`${AUTH_URI}/oauth-exchange`, {
+                method: "POST",
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ code }),
+            }).then((res) => {
+                if (!res.ok) {
+                    throw new Error(`Failed to exchange code: ${res.status}`);
+                }
+                return res.json();
+            }),
+        );
+        if (exchange.error || !exchange.data.token) {
+            logger.error("Error during code exchange");
+            throw redirect(302, "/auth?error=invalid_code");
+        }
+        event.cookies.set("token", exchange.data.token, {
             domain: COOKIE_DOMAIN,
             path: "/",
             // 10 seconds, it should be enough to be read by the backend on the next request
             maxAge: 10,
         });
-        throw redirect(302, "/dashboard");
+        // Validated by the auth service against its allowlist
+        throw redirect(302, exchange.data.return_to || "/dashboard");
     }
 
-    token = event.cookies.get("token") ?? "";
-    if (!token) {
+    const token = event.cookies.get("token") ?? "";
+    const accessToken = event.cookies.get("access_token") ?? "";
+    if (!token && !accessToken) {
         logger.info("No token");
-        throw redirect(302, "/auth");
+        // Come back to this page after login
+        if (event.url.pathname === "/") {
+            throw redirect(302, "/auth");
+        }
+        const returnTo = event.url.pathname + event.url.search;
+        throw redirect(302, `/auth?return_to=${encodeURIComponent(returnTo)}`);
     }
 
     if (event.url.pathname === "/") {
         throw redirect(302, "/dashboard");
     }
 
-    const metadata = createMetadata(token);
     /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/AuthResponse").AuthResponse__Output>} */
-    const auth = await new Promise((res) => {
-        usersService.Auth({}, metadata, grpcSafe(res));
-    });
-    if (auth.error || !auth.data.token || !auth.data.user) {
+    let auth = { error: true, msg: "No access token" };
+    if (accessToken) {
+        const metadata = createMetadata(accessToken);
+        auth = await new Promise((res) => {
+            usersService.Auth({}, metadata, grpcSafe(res));
+        });
+    }
+
+    /**
+     * The access token is missing or expired, so use the refresh token
+     * It is rotated on every use, the old one can't be used again
+     */
+    let refreshed = false;
+    if (auth.error) {
+        const metadata = createMetadata(token);
+        // Shown to the user on the list of sessions
+        metadata.set("x-user-agent", event.request.headers.get("user-agent") ?? "");
+        metadata.set("x-client-ip", event.getClientAddress());
+        auth = await new Promise((res) => {
+            usersService.RefreshToken({}, metadata, grpcSafe(res));
+        });
+        refreshed = true;
+    }
+    if (auth.error || !auth.data.user) {
         logger.error("Error during auth");
-        throw redirect(302, "/auth?error=1");
+        throw redirect(302, "/auth?error=session_expired");
     }
     logger.debug(auth.data.user);
 
     event.locals.user = auth.data.user;
-    event.locals.token = auth.data.token;
+    event.locals.token = refreshed ? auth.data.token : token;
+    event.locals.accessToken = refreshed ? auth.data.access_token : accessToken;
+    event.locals.impersonator = auth.data.impersonator_id;
+    event.locals.permissions = auth.data.permissions;
+
+    /**
+     * The role requires two-factor, so the user has to set it up before using the app
+     * Not thrown, the rotated tokens still have to be set below
+     */
+    const user = auth.data.user;
+    const setupTwoFactor =
+        user.two_factor_required &&
+        !user.two_factor_enabled &&
+        event.url.pathname !== "/settings";
 
     end();
-    const response = await resolve(event);
-    // max age is 7 days
-    response.headers.append(
-        "set-cookie",
-        `token=${auth.data.token}; HttpOnly; SameSite=Lax; Secure; Max-Age=604800; Domain=${COOKIE_DOMAIN}; Path=/`,
-    );
+    const response = setupTwoFactor
+        ? new Response(null, {
+              status: 302,
+              headers: { location: "/settings?two_factor=required" },
+          })
+        : await resolve(event);
+    if (refreshed) {
+        // max age is 7 days
+        response.headers.append(
+            "set-cookie",
+            `token=${auth.data.token}; HttpOnly; SameSite=Lax; Secure; Max-Age=604800; Domain=${COOKIE_DOMAIN}; Path=/`,
+        );
+        // max age is 15 minutes, same as the access token itself
+        response.headers.append(
+            "set-cookie",
+            `access_token=${auth.data.access_token}; HttpOnly; SameSite=Lax; Secure; Max-Age=900; Domain=${COOKIE_DOMAIN}; Path=/`,
+        );
+    }
     return response;
 }
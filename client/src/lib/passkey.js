@@ -0,0 +1,110 @@
+import { PUBLIC_AUTH_URL } from "$env/static/public";
+
+/**
+ * @param {string} value - base64url
+ * @returns {ArrayBuffer}
+ */
+function decode(value) {
+    const base64 = value.replace(/-/g, "+").replace(/_/g, "/");
+    const binary = atob(base64.padEnd(Math.ceil(base64.length / 4) * 4, "="));
+    return Uint8Array.from(binary, (c) => c.charCodeAt(0)).buffer;
+}
+
+/**
+ * @param {ArrayBuffer} value
+ * @returns {string} - base64url
+ */
+function encode(value) {
+    const binary = String.fromCharCode(...new Uint8Array(value));
+    return btoa(binary).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
+}
+
+/**
+ * @param {string} path
+ * @param {unknown} body
+ * @returns {Promise<Response>}
+ */
+async function post(path, body) {
+    const res = await fetch(`${PUBLIC_AUTH_URL}${path}`, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify(body),
+    });
+    if (!res.ok) {
+        throw new Error(`Passkey request failed: ${res.status}`);
+    }
+    return res;
+}
+
+/**
+ * Register a new passkey for the signed in user
+ * @param {string} code - One-time link code from `CreateLinkCode`
+ * @param {string} name - Shown by the authenticator, usually the email
+ * @returns {Promise<void>}
+ */
+export async function registerPasskey(code, name) {
+    /** @type {{ id: string, publicKey: any }} */
+    const start = await (await post("/passkey/register/start", { code, name })).json();
+    const options = start.publicKey;
+    const credential = /** @type {PublicKeyCredential | null} */ (
+        await navigator.credentials.create({
+            publicKey: {
+                ...options,
+                challenge: decode(options.challenge),
+                user: { ...options.user, id: decode(options.user.id) },
+            },
+        })
+    );
+    if (!credential) {
+        throw new Error("Passkey registration was cancelled");
+    }
+    const response = /** @type {AuthenticatorAttestationResponse} */ (
+        credential.response
+    );
+    await post("/passkey/register/finish", {
+        id: start.id,
+        credential: {
+            id: credential.id,
+            response: {
+                clientDataJSON: encode(response.clientDataJSON),
+                attestationObject: encode(response.attestationObject),
+            },
+        },
+    });
+}
+
+/**
+ * Sign in with any passkey of the domain
+ * @returns {Promise<string>} - Where to go next, the same as after the OAuth flow
+ */
+export async function loginWithPasskey() {
+    /** @type {{ id: string, publicKey: any }} */
+    const start = await (await post("/passkey/login/start", {})).json();
+    const options = start.publicKey;
+    const credential = /** @type {PublicKeyCredential | null} */ (
+        await navigator.credentials.get({
+            publicKey: { ...options, challenge: decode(options.challenge) },
+        })
+    );
+    if (!credential) {
+        throw new Error("Passkey login was cancelled");
+    }
+    const response = /** @type {AuthenticatorAssertionResponse} */ (
+        credential.response
+    );
+    /** @type {{ redirect: string }} */
+    const finish = await (
+        await post("/passkey/login/finish", {
+            id: start.id,
+            credential: {
+                id: credential.id,
+                response: {
+                    clientDataJSON: encode(response.clientDataJSON),
+                    authenticatorData: encode(response.authenticatorData),
+                    signature: encode(response.signature),
+                },
+            },
+        })
+    ).json();
+    return finish.redirect;
+}
@@ -11,21 +11,42 @@ type SubtypeConstructor<Constructor extends new (...args: any) => any, Subtype>
 
 export interface ProtoGrpcType {
   proto: {
+    AuditEvent: MessageTypeDefinition
+    AuditEventFilter: MessageTypeDefinition
+    AuditEventType: EnumTypeDefinition
     AuthResponse: MessageTypeDefinition
     Count: MessageTypeDefinition
+    DataExportPart: MessageTypeDefinition
+    DataExportRequest: MessageTypeDefinition
     Email: MessageTypeDefinition
     Empty: MessageTypeDefinition
     File: MessageTypeDefinition
     FileTarget: EnumTypeDefinition
     Id: MessageTypeDefinition
+    Identity: MessageTypeDefinition
+    Ids: MessageTypeDefinition
+    LoginError: EnumTypeDefinition
+    LoginFailure: MessageTypeDefinition
+    LoginResponse: MessageTypeDefinition
     Note: MessageTypeDefinition
     NoteResponse: MessageTypeDefinition
     NotesService: SubtypeConstructor<typeof grpc.Client, _proto_NotesServiceClient> & { service: _proto_NotesServiceDefinition }
     Page: MessageTypeDefinition
+    Passkey: MessageTypeDefinition
+    Permission: EnumTypeDefinition
     Profile: MessageTypeDefinition
+    RecoveryCodes: MessageTypeDefinition
+    RoleTwoFactor: MessageTypeDefinition
+    Session: MessageTypeDefinition
     StripeUrlResponse: MessageTypeDefinition
+    TwoFactorCode: MessageTypeDefinition
+    TwoFactorSetup: MessageTypeDefinition
     User: MessageTypeDefinition
+    UserDataDeletion: MessageTypeDefinition
+    UserDetails: MessageTypeDefinition
+    UserFilter: MessageTypeDefinition
     UserRole: EnumTypeDefinition
+    UserRoleChange: MessageTypeDefinition
     UsersService: SubtypeConstructor<typeof grpc.Client, _proto_UsersServiceClient> & { service: _proto_UsersServiceDefinition }
     UtilsService: SubtypeConstructor<typeof grpc.Client, _proto_UtilsServiceClient> & { service: _proto_UtilsServiceDefinition }
   }
@@ -0,0 +1,25 @@
+// Original file: users.proto
+
+import type { AuditEventType as _proto_AuditEventType, AuditEventType__Output as _proto_AuditEventType__Output } from '../proto/AuditEventType';
+
+export interface AuditEvent {
+  'id'?: (string);
+  'created'?: (string);
+  'user_id'?: (string);
+  'type'?: (_proto_AuditEventType);
+  'provider'?: (string);
+  'ip'?: (string);
+  'user_agent'?: (string);
+  'detail'?: (string);
+}
+
+export interface AuditEvent__Output {
+  'id': (string);
+  'created': (string);
+  'user_id': (string);
+  'type': (_proto_AuditEventType__Output);
+  'provider': (string);
+  'ip': (string);
+  'user_agent': (string);
+  'detail': (string);
+}
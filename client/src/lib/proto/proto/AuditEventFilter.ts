@@ -0,0 +1,18 @@
+// Original file: users.proto
+
+import type { AuditEventType as _proto_AuditEventType, AuditEventType__Output as _proto_AuditEventType__Output } from '../proto/AuditEventType';
+import type { Long } from '@grpc/proto-loader';
+
+export interface AuditEventFilter {
+  'user_id'?: (string);
+  'type'?: (_proto_AuditEventType);
+  'offset'?: (number | string | Long);
+  'limit'?: (number | string | Long);
+}
+
+export interface AuditEventFilter__Output {
+  'user_id': (string);
+  'type': (_proto_AuditEventType__Output);
+  'offset': (string);
+  'limit': (string);
+}
@@ -0,0 +1,68 @@
+// Original file: users.proto
+
+export const AuditEventType = {
+  AUDIT_UNSET: 0,
+  LOGIN_SUCCESS: 1,
+  LOGIN_FAILURE: 2,
+  TOKEN_ISSUED: 3,
+  TOKEN_REUSED: 4,
+  SESSION_REJECTED: 5,
+  TWO_FACTOR_FAILURE: 6,
+  SUBSCRIPTION_CHECKOUT: 7,
+  SUBSCRIPTION_PORTAL: 8,
+  SUBSCRIPTION_UPDATED: 9,
+  PROFILE_UPDATED: 10,
+  IMPERSONATION_STARTED: 11,
+  USER_ROLE_CHANGED: 12,
+  USER_DELETED: 13,
+  USER_RESTORED: 14,
+  SESSIONS_REVOKED: 15,
+  ACCOUNT_DELETED: 16,
+  ACCOUNT_PURGED: 17,
+  DATA_EXPORT_REQUESTED: 18,
+  IDENTITY_LINKED: 19,
+} as const;
+
+export type AuditEventType =
+  | 'AUDIT_UNSET'
+  | 0
+  | 'LOGIN_SUCCESS'
+  | 1
+  | 'LOGIN_FAILURE'
+  | 2
+  | 'TOKEN_ISSUED'
+  | 3
+  | 'TOKEN_REUSED'
+  | 4
+  | 'SESSION_REJECTED'
+  | 5
+  | 'TWO_FACTOR_FAILURE'
+  | 6
+  | 'SUBSCRIPTION_CHECKOUT'
+  | 7
+  | 'SUBSCRIPTION_PORTAL'
+  | 8
+  | 'SUBSCRIPTION_UPDATED'
+  | 9
+  | 'PROFILE_UPDATED'
+  | 10
+  | 'IMPERSONATION_STARTED'
+  | 11
+  | 'USER_ROLE_CHANGED'
+  | 12
+  | 'USER_DELETED'
+  | 13
+  | 'USER_RESTORED'
+  | 14
+  | 'SESSIONS_REVOKED'
+  | 15
+  | 'ACCOUNT_DELETED'
+  | 16
+  | 'ACCOUNT_PURGED'
+  | 17
+  | 'DATA_EXPORT_REQUESTED'
+  | 18
+  | 'IDENTITY_LINKED'
+  | 19
+
+export type AuditEventType__Output = typeof AuditEventType[keyof typeof AuditEventType]
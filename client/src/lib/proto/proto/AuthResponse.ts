@@ -1,13 +1,20 @@
 // Original file: main.proto
 
+import type { Permission as _proto_Permission, Permission__Output as _proto_Permission__Output } from '../proto/Permission';
 import type { User as _proto_User, User__Output as _proto_User__Output } from '../proto/User';
 
 export interface AuthResponse {
   'token'?: (string);
   'user'?: (_proto_User | null);
+  'access_token'?: (string);
+  'impersonator_id'?: (string);
+  'permissions'?: (_proto_Permission)[];
 }
 
 export interface AuthResponse__Output {
   'token': (string);
   'user': (_proto_User__Output | null);
+  'access_token': (string);
+  'impersonator_id': (string);
+  'permissions': (_proto_Permission__Output)[];
 }
@@ -0,0 +1,12 @@
+// Original file: main.proto
+
+
+export interface DataExportPart {
+  'name'?: (string);
+  'content'?: (Buffer | Uint8Array | string);
+}
+
+export interface DataExportPart__Output {
+  'name': (string);
+  'content': (Buffer);
+}
@@ -0,0 +1,13 @@
+// Original file: main.proto
+
+import type { DataExportPart as _proto_DataExportPart, DataExportPart__Output as _proto_DataExportPart__Output } from '../proto/DataExportPart';
+
+export interface DataExportRequest {
+  'user_id'?: (string);
+  'parts'?: (_proto_DataExportPart)[];
+}
+
+export interface DataExportRequest__Output {
+  'user_id': (string);
+  'parts': (_proto_DataExportPart__Output)[];
+}
@@ -4,6 +4,7 @@ export const FileTarget = {
   FILE_UNSET: 0,
   DOCUMENT: 1,
   AVATAR: 2,
+  DATA_EXPORT: 3,
 } as const;
 
 export type FileTarget =
@@ -13,5 +14,7 @@ export type FileTarget =
   | 1
   | 'AVATAR'
   | 2
+  | 'DATA_EXPORT'
+  | 3
 
 export type FileTarget__Output = typeof FileTarget[keyof typeof FileTarget]
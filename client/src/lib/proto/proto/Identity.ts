@@ -0,0 +1,16 @@
+// Original file: users.proto
+
+
+export interface Identity {
+  'id'?: (string);
+  'created'?: (string);
+  'provider'?: (string);
+  'email'?: (string);
+}
+
+export interface Identity__Output {
+  'id': (string);
+  'created': (string);
+  'provider': (string);
+  'email': (string);
+}
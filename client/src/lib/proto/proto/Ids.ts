@@ -0,0 +1,10 @@
+// Original file: main.proto
+
+
+export interface Ids {
+  'ids'?: (string)[];
+}
+
+export interface Ids__Output {
+  'ids': (string)[];
+}
@@ -0,0 +1,20 @@
+// Original file: main.proto
+
+export const LoginError = {
+  LOGIN_ERROR_UNSET: 0,
+  ACCOUNT_EXISTS: 1,
+  ACCOUNT_DISABLED: 2,
+  INVALID_LINK_CODE: 3,
+} as const;
+
+export type LoginError =
+  | 'LOGIN_ERROR_UNSET'
+  | 0
+  | 'ACCOUNT_EXISTS'
+  | 1
+  | 'ACCOUNT_DISABLED'
+  | 2
+  | 'INVALID_LINK_CODE'
+  | 3
+
+export type LoginError__Output = typeof LoginError[keyof typeof LoginError]
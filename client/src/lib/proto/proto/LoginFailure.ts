@@ -0,0 +1,11 @@
+// Original file: main.proto
+
+import type { LoginError as _proto_LoginError, LoginError__Output as _proto_LoginError__Output } from '../proto/LoginError';
+
+export interface LoginFailure {
+  'reason'?: (_proto_LoginError);
+}
+
+export interface LoginFailure__Output {
+  'reason': (_proto_LoginError__Output);
+}
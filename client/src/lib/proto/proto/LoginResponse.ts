@@ -0,0 +1,12 @@
+// Original file: main.proto
+
+
+export interface LoginResponse {
+  'token'?: (string);
+  'challenge_id'?: (string);
+}
+
+export interface LoginResponse__Output {
+  'token': (string);
+  'challenge_id': (string);
+}
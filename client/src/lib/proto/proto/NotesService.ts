@@ -3,11 +3,13 @@
 import type * as grpc from '@grpc/grpc-js'
 import type { MethodDefinition } from '@grpc/proto-loader'
 import type { Count as _proto_Count, Count__Output as _proto_Count__Output } from '../proto/Count';
+import type { DataExportPart as _proto_DataExportPart, DataExportPart__Output as _proto_DataExportPart__Output } from '../proto/DataExportPart';
 import type { Empty as _proto_Empty, Empty__Output as _proto_Empty__Output } from '../proto/Empty';
 import type { Id as _proto_Id, Id__Output as _proto_Id__Output } from '../proto/Id';
 import type { Note as _proto_Note, Note__Output as _proto_Note__Output } from '../proto/Note';
 import type { NoteResponse as _proto_NoteResponse, NoteResponse__Output as _proto_NoteResponse__Output } from '../proto/NoteResponse';
 import type { Page as _proto_Page, Page__Output as _proto_Page__Output } from '../proto/Page';
+import type { UserDataDeletion as _proto_UserDataDeletion, UserDataDeletion__Output as _proto_UserDataDeletion__Output } from '../proto/UserDataDeletion';
 
 export interface NotesServiceClient extends grpc.Client {
   CountNotesByUserId(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
@@ -37,6 +39,24 @@ export interface NotesServiceClient extends grpc.Client {
   deleteNoteById(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
   deleteNoteById(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
   
+  DeleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUserData(argument: _proto_UserDataDeletion, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUserData(argument: _proto_UserDataDeletion, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  ExportUserData(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  ExportUserData(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  ExportUserData(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  ExportUserData(argument: _proto_Id, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  exportUserData(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  exportUserData(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  exportUserData(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  exportUserData(argument: _proto_Id, callback: grpc.requestCallback<_proto_DataExportPart__Output>): grpc.ClientUnaryCall;
+  
   GetNoteById(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Note__Output>): grpc.ClientUnaryCall;
   GetNoteById(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Note__Output>): grpc.ClientUnaryCall;
   GetNoteById(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Note__Output>): grpc.ClientUnaryCall;
@@ -60,6 +80,10 @@ export interface NotesServiceHandlers extends grpc.UntypedServiceImplementation
   
   DeleteNoteById: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
   
+  DeleteUserData: grpc.handleUnaryCall<_proto_UserDataDeletion__Output, _proto_Empty>;
+  
+  ExportUserData: grpc.handleUnaryCall<_proto_Id__Output, _proto_DataExportPart>;
+  
   GetNoteById: grpc.handleUnaryCall<_proto_Id__Output, _proto_Note>;
   
   GetNotesByUserId: grpc.handleServerStreamingCall<_proto_Page__Output, _proto_NoteResponse>;
@@ -70,6 +94,8 @@ export interface NotesServiceDefinition extends grpc.ServiceDefinition {
   CountNotesByUserId: MethodDefinition<_proto_Empty, _proto_Count, _proto_Empty__Output, _proto_Count__Output>
   CreateNote: MethodDefinition<_proto_Note, _proto_Note, _proto_Note__Output, _proto_Note__Output>
   DeleteNoteById: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  DeleteUserData: MethodDefinition<_proto_UserDataDeletion, _proto_Empty, _proto_UserDataDeletion__Output, _proto_Empty__Output>
+  ExportUserData: MethodDefinition<_proto_Id, _proto_DataExportPart, _proto_Id__Output, _proto_DataExportPart__Output>
   GetNoteById: MethodDefinition<_proto_Id, _proto_Note, _proto_Id__Output, _proto_Note__Output>
   GetNotesByUserId: MethodDefinition<_proto_Page, _proto_NoteResponse, _proto_Page__Output, _proto_NoteResponse__Output>
 }
@@ -0,0 +1,25 @@
+// Original file: users.proto
+
+import type { Long } from '@grpc/proto-loader';
+
+export interface Passkey {
+  'id'?: (string);
+  'created'?: (string);
+  'last_used'?: (string);
+  'user_id'?: (string);
+  'credential_id'?: (string);
+  'public_key'?: (Buffer | Uint8Array | string);
+  'sign_count'?: (number | string | Long);
+  'name'?: (string);
+}
+
+export interface Passkey__Output {
+  'id': (string);
+  'created': (string);
+  'last_used': (string);
+  'user_id': (string);
+  'credential_id': (string);
+  'public_key': (Buffer);
+  'sign_count': (string);
+  'name': (string);
+}
@@ -0,0 +1,23 @@
+// Original file: users.proto
+
+export const Permission = {
+  PERMISSION_UNSET: 0,
+  READ_AUDIT_LOG: 1,
+  IMPERSONATE_USERS: 2,
+  MANAGE_ROLES: 3,
+  MANAGE_USERS: 4,
+} as const;
+
+export type Permission =
+  | 'PERMISSION_UNSET'
+  | 0
+  | 'READ_AUDIT_LOG'
+  | 1
+  | 'IMPERSONATE_USERS'
+  | 2
+  | 'MANAGE_ROLES'
+  | 3
+  | 'MANAGE_USERS'
+  | 4
+
+export type Permission__Output = typeof Permission[keyof typeof Permission]
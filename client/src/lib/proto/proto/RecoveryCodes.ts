@@ -0,0 +1,10 @@
+// Original file: users.proto
+
+
+export interface RecoveryCodes {
+  'codes'?: (string)[];
+}
+
+export interface RecoveryCodes__Output {
+  'codes': (string)[];
+}
@@ -0,0 +1,13 @@
+// Original file: users.proto
+
+import type { UserRole as _proto_UserRole, UserRole__Output as _proto_UserRole__Output } from '../proto/UserRole';
+
+export interface RoleTwoFactor {
+  'role'?: (_proto_UserRole);
+  'required'?: (boolean);
+}
+
+export interface RoleTwoFactor__Output {
+  'role': (_proto_UserRole__Output);
+  'required': (boolean);
+}
@@ -0,0 +1,22 @@
+// Original file: users.proto
+
+
+export interface Session {
+  'id'?: (string);
+  'created'?: (string);
+  'last_used'?: (string);
+  'user_agent'?: (string);
+  'ip'?: (string);
+  'current'?: (boolean);
+  'impersonated'?: (boolean);
+}
+
+export interface Session__Output {
+  'id': (string);
+  'created': (string);
+  'last_used': (string);
+  'user_agent': (string);
+  'ip': (string);
+  'current': (boolean);
+  'impersonated': (boolean);
+}
@@ -0,0 +1,10 @@
+// Original file: users.proto
+
+
+export interface TwoFactorCode {
+  'code'?: (string);
+}
+
+export interface TwoFactorCode__Output {
+  'code': (string);
+}
@@ -0,0 +1,12 @@
+// Original file: users.proto
+
+
+export interface TwoFactorSetup {
+  'secret'?: (string);
+  'uri'?: (string);
+}
+
+export interface TwoFactorSetup__Output {
+  'secret': (string);
+  'uri': (string);
+}
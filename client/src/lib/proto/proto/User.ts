@@ -15,6 +15,8 @@ export interface User {
   'subscription_end'?: (string);
   'subscription_check'?: (string);
   'subscription_active'?: (boolean);
+  'two_factor_enabled'?: (boolean);
+  'two_factor_required'?: (boolean);
 }
 
 export interface User__Output {
@@ -30,4 +32,6 @@ export interface User__Output {
   'subscription_end': (string);
   'subscription_check': (string);
   'subscription_active': (boolean);
+  'two_factor_enabled': (boolean);
+  'two_factor_required': (boolean);
 }
@@ -0,0 +1,12 @@
+// Original file: main.proto
+
+
+export interface UserDataDeletion {
+  'user_id'?: (string);
+  'purge'?: (boolean);
+}
+
+export interface UserDataDeletion__Output {
+  'user_id': (string);
+  'purge': (boolean);
+}
@@ -0,0 +1,20 @@
+// Original file: main.proto
+
+import type { Identity as _proto_Identity, Identity__Output as _proto_Identity__Output } from '../proto/Identity';
+import type { Profile as _proto_Profile, Profile__Output as _proto_Profile__Output } from '../proto/Profile';
+import type { User as _proto_User, User__Output as _proto_User__Output } from '../proto/User';
+import type { Long } from '@grpc/proto-loader';
+
+export interface UserDetails {
+  'user'?: (_proto_User | null);
+  'profile'?: (_proto_Profile | null);
+  'identities'?: (_proto_Identity)[];
+  'active_sessions'?: (number | string | Long);
+}
+
+export interface UserDetails__Output {
+  'user': (_proto_User__Output | null);
+  'profile': (_proto_Profile__Output | null);
+  'identities': (_proto_Identity__Output)[];
+  'active_sessions': (string);
+}
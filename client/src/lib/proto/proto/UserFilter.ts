@@ -0,0 +1,17 @@
+// Original file: users.proto
+
+import type { Long } from '@grpc/proto-loader';
+
+export interface UserFilter {
+  'search'?: (string);
+  'deleted'?: (boolean);
+  'offset'?: (number | string | Long);
+  'limit'?: (number | string | Long);
+}
+
+export interface UserFilter__Output {
+  'search': (string);
+  'deleted': (boolean);
+  'offset': (string);
+  'limit': (string);
+}
@@ -0,0 +1,13 @@
+// Original file: users.proto
+
+import type { UserRole as _proto_UserRole, UserRole__Output as _proto_UserRole__Output } from '../proto/UserRole';
+
+export interface UserRoleChange {
+  'user_id'?: (string);
+  'role'?: (_proto_UserRole);
+}
+
+export interface UserRoleChange__Output {
+  'user_id': (string);
+  'role': (_proto_UserRole__Output);
+}
@@ -2,11 +2,27 @@
 
 import type * as grpc from '@grpc/grpc-js'
 import type { MethodDefinition } from '@grpc/proto-loader'
+import type { AuditEvent as _proto_AuditEvent, AuditEvent__Output as _proto_AuditEvent__Output } from '../proto/AuditEvent';
+import type { AuditEventFilter as _proto_AuditEventFilter, AuditEventFilter__Output as _proto_AuditEventFilter__Output } from '../proto/AuditEventFilter';
 import type { AuthResponse as _proto_AuthResponse, AuthResponse__Output as _proto_AuthResponse__Output } from '../proto/AuthResponse';
+import type { Count as _proto_Count, Count__Output as _proto_Count__Output } from '../proto/Count';
 import type { Empty as _proto_Empty, Empty__Output as _proto_Empty__Output } from '../proto/Empty';
 import type { Id as _proto_Id, Id__Output as _proto_Id__Output } from '../proto/Id';
+import type { Identity as _proto_Identity, Identity__Output as _proto_Identity__Output } from '../proto/Identity';
+import type { Ids as _proto_Ids, Ids__Output as _proto_Ids__Output } from '../proto/Ids';
+import type { LoginResponse as _proto_LoginResponse, LoginResponse__Output as _proto_LoginResponse__Output } from '../proto/LoginResponse';
+import type { Passkey as _proto_Passkey, Passkey__Output as _proto_Passkey__Output } from '../proto/Passkey';
 import type { Profile as _proto_Profile, Profile__Output as _proto_Profile__Output } from '../proto/Profile';
+import type { RecoveryCodes as _proto_RecoveryCodes, RecoveryCodes__Output as _proto_RecoveryCodes__Output } from '../proto/RecoveryCodes';
+import type { RoleTwoFactor as _proto_RoleTwoFactor, RoleTwoFactor__Output as _proto_RoleTwoFactor__Output } from '../proto/RoleTwoFactor';
+import type { Session as _proto_Session, Session__Output as _proto_Session__Output } from '../proto/Session';
 import type { StripeUrlResponse as _proto_StripeUrlResponse, StripeUrlResponse__Output as _proto_StripeUrlResponse__Output } from '../proto/StripeUrlResponse';
+import type { TwoFactorCode as _proto_TwoFactorCode, TwoFactorCode__Output as _proto_TwoFactorCode__Output } from '../proto/TwoFactorCode';
+import type { TwoFactorSetup as _proto_TwoFactorSetup, TwoFactorSetup__Output as _proto_TwoFactorSetup__Output } from '../proto/TwoFactorSetup';
+import type { User as _proto_User, User__Output as _proto_User__Output } from '../proto/User';
+import type { UserDetails as _proto_UserDetails, UserDetails__Output as _proto_UserDetails__Output } from '../proto/UserDetails';
+import type { UserFilter as _proto_UserFilter, UserFilter__Output as _proto_UserFilter__Output } from '../proto/UserFilter';
+import type { UserRoleChange as _proto_UserRoleChange, UserRoleChange__Output as _proto_UserRoleChange__Output } from '../proto/UserRoleChange';
 
 export interface UsersServiceClient extends grpc.Client {
   Auth(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
@@ -18,6 +34,51 @@ export interface UsersServiceClient extends grpc.Client {
   auth(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
   auth(argument: _proto_Empty, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
   
+  CountUsers(argument: _proto_UserFilter, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  CountUsers(argument: _proto_UserFilter, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  CountUsers(argument: _proto_UserFilter, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  CountUsers(argument: _proto_UserFilter, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  countUsers(argument: _proto_UserFilter, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  countUsers(argument: _proto_UserFilter, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  countUsers(argument: _proto_UserFilter, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  countUsers(argument: _proto_UserFilter, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
+  
+  CreateAuditEvent(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreateAuditEvent(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreateAuditEvent(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreateAuditEvent(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createAuditEvent(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createAuditEvent(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createAuditEvent(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createAuditEvent(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  CreateDataExport(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreateDataExport(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreateDataExport(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreateDataExport(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  CreateLinkCode(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  CreateLinkCode(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  CreateLinkCode(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  CreateLinkCode(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  createLinkCode(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  createLinkCode(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  createLinkCode(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  createLinkCode(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  
+  CreatePasskey(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreatePasskey(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreatePasskey(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  CreatePasskey(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createPasskey(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createPasskey(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createPasskey(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  createPasskey(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
   CreateProfile(argument: _proto_Profile, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
   CreateProfile(argument: _proto_Profile, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
   CreateProfile(argument: _proto_Profile, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
@@ -45,14 +106,83 @@ export interface UsersServiceClient extends grpc.Client {
   createStripePortal(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_StripeUrlResponse__Output>): grpc.ClientUnaryCall;
   createStripePortal(argument: _proto_Empty, callback: grpc.requestCallback<_proto_StripeUrlResponse__Output>): grpc.ClientUnaryCall;
   
-  CreateUser(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  CreateUser(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  CreateUser(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  CreateUser(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  createUser(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  createUser(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  createUser(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
-  createUser(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  CreateUser(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  CreateUser(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  CreateUser(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  CreateUser(argument: _proto_Empty, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  createUser(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  createUser(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  createUser(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  createUser(argument: _proto_Empty, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  
+  DeleteAccount(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteAccount(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteAccount(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteAccount(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteAccount(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteAccount(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteAccount(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteAccount(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  DeletePasskey(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeletePasskey(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeletePasskey(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeletePasskey(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deletePasskey(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deletePasskey(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deletePasskey(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deletePasskey(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  DeleteUser(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUser(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUser(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUser(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUser(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUser(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUser(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUser(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  DisableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DisableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DisableTwoFactor(argument: _proto_TwoFactorCode, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DisableTwoFactor(argument: _proto_TwoFactorCode, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  disableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  disableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  disableTwoFactor(argument: _proto_TwoFactorCode, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  disableTwoFactor(argument: _proto_TwoFactorCode, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  EnableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  EnableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  EnableTwoFactor(argument: _proto_TwoFactorCode, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  EnableTwoFactor(argument: _proto_TwoFactorCode, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  enableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  enableTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  enableTwoFactor(argument: _proto_TwoFactorCode, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  enableTwoFactor(argument: _proto_TwoFactorCode, callback: grpc.requestCallback<_proto_RecoveryCodes__Output>): grpc.ClientUnaryCall;
+  
+  GetAuditEvents(argument: _proto_AuditEventFilter, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_AuditEvent__Output>;
+  GetAuditEvents(argument: _proto_AuditEventFilter, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_AuditEvent__Output>;
+  getAuditEvents(argument: _proto_AuditEventFilter, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_AuditEvent__Output>;
+  getAuditEvents(argument: _proto_AuditEventFilter, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_AuditEvent__Output>;
+  
+  GetIdentities(argument: _proto_Empty, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Identity__Output>;
+  GetIdentities(argument: _proto_Empty, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Identity__Output>;
+  getIdentities(argument: _proto_Empty, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Identity__Output>;
+  getIdentities(argument: _proto_Empty, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Identity__Output>;
+  
+  GetPasskey(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  GetPasskey(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  GetPasskey(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  GetPasskey(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  getPasskey(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  getPasskey(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  getPasskey(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  getPasskey(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Passkey__Output>): grpc.ClientUnaryCall;
+  
+  GetPasskeys(argument: _proto_Empty, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Passkey__Output>;
+  GetPasskeys(argument: _proto_Empty, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Passkey__Output>;
+  getPasskeys(argument: _proto_Empty, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Passkey__Output>;
+  getPasskeys(argument: _proto_Empty, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Passkey__Output>;
   
   GetProfileByUserId(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
   GetProfileByUserId(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
@@ -63,28 +193,274 @@ export interface UsersServiceClient extends grpc.Client {
   getProfileByUserId(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
   getProfileByUserId(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Profile__Output>): grpc.ClientUnaryCall;
   
+  GetProfilesByUserIds(argument: _proto_Ids, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Profile__Output>;
+  GetProfilesByUserIds(argument: _proto_Ids, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Profile__Output>;
+  getProfilesByUserIds(argument: _proto_Ids, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Profile__Output>;
+  getProfilesByUserIds(argument: _proto_Ids, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Profile__Output>;
+  
+  GetSessions(argument: _proto_Empty, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Session__Output>;
+  GetSessions(argument: _proto_Empty, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Session__Output>;
+  getSessions(argument: _proto_Empty, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Session__Output>;
+  getSessions(argument: _proto_Empty, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Session__Output>;
+  
+  GetUserDetails(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  GetUserDetails(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  GetUserDetails(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  GetUserDetails(argument: _proto_Id, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  getUserDetails(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  getUserDetails(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  getUserDetails(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  getUserDetails(argument: _proto_Id, callback: grpc.requestCallback<_proto_UserDetails__Output>): grpc.ClientUnaryCall;
+  
+  GetUsers(argument: _proto_UserFilter, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_User__Output>;
+  GetUsers(argument: _proto_UserFilter, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_User__Output>;
+  getUsers(argument: _proto_UserFilter, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_User__Output>;
+  getUsers(argument: _proto_UserFilter, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_User__Output>;
+  
+  ImpersonateUser(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  ImpersonateUser(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  ImpersonateUser(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  ImpersonateUser(argument: _proto_Id, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  impersonateUser(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  impersonateUser(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  impersonateUser(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  impersonateUser(argument: _proto_Id, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  
+  LinkIdentity(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  LinkIdentity(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  LinkIdentity(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  LinkIdentity(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  linkIdentity(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  linkIdentity(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  linkIdentity(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  linkIdentity(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  Logout(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  Logout(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  Logout(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  Logout(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  logout(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  logout(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  logout(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  logout(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  PasskeyLogin(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  PasskeyLogin(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  PasskeyLogin(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  PasskeyLogin(argument: _proto_Empty, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  passkeyLogin(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  passkeyLogin(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  passkeyLogin(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  passkeyLogin(argument: _proto_Empty, callback: grpc.requestCallback<_proto_LoginResponse__Output>): grpc.ClientUnaryCall;
+  
+  RefreshToken(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  RefreshToken(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  RefreshToken(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  RefreshToken(argument: _proto_Empty, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  refreshToken(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  refreshToken(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  refreshToken(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  refreshToken(argument: _proto_Empty, callback: grpc.requestCallback<_proto_AuthResponse__Output>): grpc.ClientUnaryCall;
+  
+  RestoreUser(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RestoreUser(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RestoreUser(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RestoreUser(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  restoreUser(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  restoreUser(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  restoreUser(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  restoreUser(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  RevokeOtherSessions(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeOtherSessions(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeOtherSessions(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeOtherSessions(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeOtherSessions(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeOtherSessions(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeOtherSessions(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeOtherSessions(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  RevokeSession(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeSession(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeSession(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeSession(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeSession(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeSession(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeSession(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeSession(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  RevokeUserSessions(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeUserSessions(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeUserSessions(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  RevokeUserSessions(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeUserSessions(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeUserSessions(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeUserSessions(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  revokeUserSessions(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  SetRoleTwoFactor(argument: _proto_RoleTwoFactor, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  SetRoleTwoFactor(argument: _proto_RoleTwoFactor, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  SetRoleTwoFactor(argument: _proto_RoleTwoFactor, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  SetRoleTwoFactor(argument: _proto_RoleTwoFactor, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  setRoleTwoFactor(argument: _proto_RoleTwoFactor, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  setRoleTwoFactor(argument: _proto_RoleTwoFactor, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  setRoleTwoFactor(argument: _proto_RoleTwoFactor, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  setRoleTwoFactor(argument: _proto_RoleTwoFactor, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  SetUserRole(argument: _proto_UserRoleChange, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  SetUserRole(argument: _proto_UserRoleChange, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  SetUserRole(argument: _proto_UserRoleChange, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  SetUserRole(argument: _proto_UserRoleChange, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  setUserRole(argument: _proto_UserRoleChange, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  setUserRole(argument: _proto_UserRoleChange, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  setUserRole(argument: _proto_UserRoleChange, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  setUserRole(argument: _proto_UserRoleChange, callback: grpc.requestCallback<_proto_User__Output>): grpc.ClientUnaryCall;
+  
+  SetupTwoFactor(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  SetupTwoFactor(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  SetupTwoFactor(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  SetupTwoFactor(argument: _proto_Empty, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  setupTwoFactor(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  setupTwoFactor(argument: _proto_Empty, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  setupTwoFactor(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  setupTwoFactor(argument: _proto_Empty, callback: grpc.requestCallback<_proto_TwoFactorSetup__Output>): grpc.ClientUnaryCall;
+  
+  UnlinkIdentity(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  UnlinkIdentity(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  UnlinkIdentity(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  UnlinkIdentity(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  unlinkIdentity(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  unlinkIdentity(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  unlinkIdentity(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  unlinkIdentity(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
+  VerifyTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  VerifyTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  VerifyTwoFactor(argument: _proto_TwoFactorCode, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  VerifyTwoFactor(argument: _proto_TwoFactorCode, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  verifyTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  verifyTwoFactor(argument: _proto_TwoFactorCode, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  verifyTwoFactor(argument: _proto_TwoFactorCode, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  verifyTwoFactor(argument: _proto_TwoFactorCode, callback: grpc.requestCallback<_proto_Id__Output>): grpc.ClientUnaryCall;
+  
 }
 
 export interface UsersServiceHandlers extends grpc.UntypedServiceImplementation {
   Auth: grpc.handleUnaryCall<_proto_Empty__Output, _proto_AuthResponse>;
   
+  CountUsers: grpc.handleUnaryCall<_proto_UserFilter__Output, _proto_Count>;
+  
+  CreateAuditEvent: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Empty>;
+  
+  CreateDataExport: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Empty>;
+  
+  CreateLinkCode: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Id>;
+  
+  CreatePasskey: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Empty>;
+  
   CreateProfile: grpc.handleUnaryCall<_proto_Profile__Output, _proto_Profile>;
   
   CreateStripeCheckout: grpc.handleUnaryCall<_proto_Empty__Output, _proto_StripeUrlResponse>;
   
   CreateStripePortal: grpc.handleUnaryCall<_proto_Empty__Output, _proto_StripeUrlResponse>;
   
-  CreateUser: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Id>;
+  CreateUser: grpc.handleUnaryCall<_proto_Empty__Output, _proto_LoginResponse>;
+  
+  DeleteAccount: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Empty>;
+  
+  DeletePasskey: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  DeleteUser: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  DisableTwoFactor: grpc.handleUnaryCall<_proto_TwoFactorCode__Output, _proto_Empty>;
+  
+  EnableTwoFactor: grpc.handleUnaryCall<_proto_TwoFactorCode__Output, _proto_RecoveryCodes>;
+  
+  GetAuditEvents: grpc.handleServerStreamingCall<_proto_AuditEventFilter__Output, _proto_AuditEvent>;
+  
+  GetIdentities: grpc.handleServerStreamingCall<_proto_Empty__Output, _proto_Identity>;
+  
+  GetPasskey: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Passkey>;
+  
+  GetPasskeys: grpc.handleServerStreamingCall<_proto_Empty__Output, _proto_Passkey>;
   
   GetProfileByUserId: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Profile>;
   
+  GetProfilesByUserIds: grpc.handleServerStreamingCall<_proto_Ids__Output, _proto_Profile>;
+  
+  GetSessions: grpc.handleServerStreamingCall<_proto_Empty__Output, _proto_Session>;
+  
+  GetUserDetails: grpc.handleUnaryCall<_proto_Id__Output, _proto_UserDetails>;
+  
+  GetUsers: grpc.handleServerStreamingCall<_proto_UserFilter__Output, _proto_User>;
+  
+  ImpersonateUser: grpc.handleUnaryCall<_proto_Id__Output, _proto_AuthResponse>;
+  
+  LinkIdentity: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  Logout: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Empty>;
+  
+  PasskeyLogin: grpc.handleUnaryCall<_proto_Empty__Output, _proto_LoginResponse>;
+  
+  RefreshToken: grpc.handleUnaryCall<_proto_Empty__Output, _proto_AuthResponse>;
+  
+  RestoreUser: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  RevokeOtherSessions: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Empty>;
+  
+  RevokeSession: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  RevokeUserSessions: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  SetRoleTwoFactor: grpc.handleUnaryCall<_proto_RoleTwoFactor__Output, _proto_Empty>;
+  
+  SetUserRole: grpc.handleUnaryCall<_proto_UserRoleChange__Output, _proto_User>;
+  
+  SetupTwoFactor: grpc.handleUnaryCall<_proto_Empty__Output, _proto_TwoFactorSetup>;
+  
+  UnlinkIdentity: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
+  
+  VerifyTwoFactor: grpc.handleUnaryCall<_proto_TwoFactorCode__Output, _proto_Id>;
+  
 }
 
 export interface UsersServiceDefinition extends grpc.ServiceDefinition {
   Auth: MethodDefinition<_proto_Empty, _proto_AuthResponse, _proto_Empty__Output, _proto_AuthResponse__Output>
+  CountUsers: MethodDefinition<_proto_UserFilter, _proto_Count, _proto_UserFilter__Output, _proto_Count__Output>
+  CreateAuditEvent: MethodDefinition<_proto_Empty, _proto_Empty, _proto_Empty__Output, _proto_Empty__Output>
+  CreateDataExport: MethodDefinition<_proto_Empty, _proto_Empty, _proto_Empty__Output, _proto_Empty__Output>
+  CreateLinkCode: MethodDefinition<_proto_Empty, _proto_Id, _proto_Empty__Output, _proto_Id__Output>
+  CreatePasskey: MethodDefinition<_proto_Empty, _proto_Empty, _proto_Empty__Output, _proto_Empty__Output>
   CreateProfile: MethodDefinition<_proto_Profile, _proto_Profile, _proto_Profile__Output, _proto_Profile__Output>
   CreateStripeCheckout: MethodDefinition<_proto_Empty, _proto_StripeUrlResponse, _proto_Empty__Output, _proto_StripeUrlResponse__Output>
   CreateStripePortal: MethodDefinition<_proto_Empty, _proto_StripeUrlResponse, _proto_Empty__Output, _proto_StripeUrlResponse__Output>
-  CreateUser: MethodDefinition<_proto_Empty, _proto_Id, _proto_Empty__Output, _proto_Id__Output>
+  CreateUser: MethodDefinition<_proto_Empty, _proto_LoginResponse, _proto_Empty__Output, _proto_LoginResponse__Output>
+  DeleteAccount: MethodDefinition<_proto_Empty, _proto_Empty, _proto_Empty__Output, _proto_Empty__Output>
+  DeletePasskey: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  DeleteUser: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  DisableTwoFactor: MethodDefinition<_proto_TwoFactorCode, _proto_Empty, _proto_TwoFactorCode__Output, _proto_Empty__Output>
+  EnableTwoFactor: MethodDefinition<_proto_TwoFactorCode, _proto_RecoveryCodes, _proto_TwoFactorCode__Output, _proto_RecoveryCodes__Output>
+  GetAuditEvents: MethodDefinition<_proto_AuditEventFilter, _proto_AuditEvent, _proto_AuditEventFilter__Output, _proto_AuditEvent__Output>
+  GetIdentities: MethodDefinition<_proto_Empty, _proto_Identity, _proto_Empty__Output, _proto_Identity__Output>
+  GetPasskey: MethodDefinition<_proto_Empty, _proto_Passkey, _proto_Empty__Output, _proto_Passkey__Output>
+  GetPasskeys: MethodDefinition<_proto_Empty, _proto_Passkey, _proto_Empty__Output, _proto_Passkey__Output>
   GetProfileByUserId: MethodDefinition<_proto_Empty, _proto_Profile, _proto_Empty__Output, _proto_Profile__Output>
+  GetProfilesByUserIds: MethodDefinition<_proto_Ids, _proto_Profile, _proto_Ids__Output, _proto_Profile__Output>
+  GetSessions: MethodDefinition<_proto_Empty, _proto_Session, _proto_Empty__Output, _proto_Session__Output>
+  GetUserDetails: MethodDefinition<_proto_Id, _proto_UserDetails, _proto_Id__Output, _proto_UserDetails__Output>
+  GetUsers: MethodDefinition<_proto_UserFilter, _proto_User, _proto_UserFilter__Output, _proto_User__Output>
+  ImpersonateUser: MethodDefinition<_proto_Id, _proto_AuthResponse, _proto_Id__Output, _proto_AuthResponse__Output>
+  LinkIdentity: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  Logout: MethodDefinition<_proto_Empty, _proto_Empty, _proto_Empty__Output, _proto_Empty__Output>
+  PasskeyLogin: MethodDefinition<_proto_Empty, _proto_LoginResponse, _proto_Empty__Output, _proto_LoginResponse__Output>
+  RefreshToken: MethodDefinition<_proto_Empty, _proto_AuthResponse, _proto_Empty__Output, _proto_AuthResponse__Output>
+  RestoreUser: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  RevokeOtherSessions: MethodDefinition<_proto_Empty, _proto_Empty, _proto_Empty__Output, _proto_Empty__Output>
+  RevokeSession: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  RevokeUserSessions: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  SetRoleTwoFactor: MethodDefinition<_proto_RoleTwoFactor, _proto_Empty, _proto_RoleTwoFactor__Output, _proto_Empty__Output>
+  SetUserRole: MethodDefinition<_proto_UserRoleChange, _proto_User, _proto_UserRoleChange__Output, _proto_User__Output>
+  SetupTwoFactor: MethodDefinition<_proto_Empty, _proto_TwoFactorSetup, _proto_Empty__Output, _proto_TwoFactorSetup__Output>
+  UnlinkIdentity: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  VerifyTwoFactor: MethodDefinition<_proto_TwoFactorCode, _proto_Id, _proto_TwoFactorCode__Output, _proto_Id__Output>
 }
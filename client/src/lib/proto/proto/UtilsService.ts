@@ -3,11 +3,13 @@
 import type * as grpc from '@grpc/grpc-js'
 import type { MethodDefinition } from '@grpc/proto-loader'
 import type { Count as _proto_Count, Count__Output as _proto_Count__Output } from '../proto/Count';
+import type { DataExportRequest as _proto_DataExportRequest, DataExportRequest__Output as _proto_DataExportRequest__Output } from '../proto/DataExportRequest';
 import type { Email as _proto_Email, Email__Output as _proto_Email__Output } from '../proto/Email';
 import type { Empty as _proto_Empty, Empty__Output as _proto_Empty__Output } from '../proto/Empty';
 import type { File as _proto_File, File__Output as _proto_File__Output } from '../proto/File';
 import type { Id as _proto_Id, Id__Output as _proto_Id__Output } from '../proto/Id';
 import type { Page as _proto_Page, Page__Output as _proto_Page__Output } from '../proto/Page';
+import type { UserDataDeletion as _proto_UserDataDeletion, UserDataDeletion__Output as _proto_UserDataDeletion__Output } from '../proto/UserDataDeletion';
 
 export interface UtilsServiceClient extends grpc.Client {
   CountEmailsByTargetId(argument: _proto_Empty, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
@@ -28,6 +30,15 @@ export interface UtilsServiceClient extends grpc.Client {
   countFilesByTargetId(argument: _proto_Empty, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
   countFilesByTargetId(argument: _proto_Empty, callback: grpc.requestCallback<_proto_Count__Output>): grpc.ClientUnaryCall;
   
+  CreateDataExport(argument: _proto_DataExportRequest, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  CreateDataExport(argument: _proto_DataExportRequest, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  CreateDataExport(argument: _proto_DataExportRequest, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  CreateDataExport(argument: _proto_DataExportRequest, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_DataExportRequest, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_DataExportRequest, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_DataExportRequest, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  createDataExport(argument: _proto_DataExportRequest, callback: grpc.requestCallback<_proto_File__Output>): grpc.ClientUnaryCall;
+  
   DeleteFileById(argument: _proto_Id, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
   DeleteFileById(argument: _proto_Id, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
   DeleteFileById(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
@@ -37,6 +48,15 @@ export interface UtilsServiceClient extends grpc.Client {
   deleteFileById(argument: _proto_Id, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
   deleteFileById(argument: _proto_Id, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
   
+  DeleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUserData(argument: _proto_UserDataDeletion, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  DeleteUserData(argument: _proto_UserDataDeletion, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, metadata: grpc.Metadata, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, options: grpc.CallOptions, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  deleteUserData(argument: _proto_UserDataDeletion, callback: grpc.requestCallback<_proto_Empty__Output>): grpc.ClientUnaryCall;
+  
   GetEmailsByTargetId(argument: _proto_Page, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Email__Output>;
   GetEmailsByTargetId(argument: _proto_Page, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Email__Output>;
   getEmailsByTargetId(argument: _proto_Page, metadata: grpc.Metadata, options?: grpc.CallOptions): grpc.ClientReadableStream<_proto_Email__Output>;
@@ -73,8 +93,12 @@ export interface UtilsServiceHandlers extends grpc.UntypedServiceImplementation
   
   CountFilesByTargetId: grpc.handleUnaryCall<_proto_Empty__Output, _proto_Count>;
   
+  CreateDataExport: grpc.handleUnaryCall<_proto_DataExportRequest__Output, _proto_File>;
+  
   DeleteFileById: grpc.handleUnaryCall<_proto_Id__Output, _proto_Empty>;
   
+  DeleteUserData: grpc.handleUnaryCall<_proto_UserDataDeletion__Output, _proto_Empty>;
+  
   GetEmailsByTargetId: grpc.handleServerStreamingCall<_proto_Page__Output, _proto_Email>;
   
   GetFileById: grpc.handleServerStreamingCall<_proto_Id__Output, _proto_File>;
@@ -90,7 +114,9 @@ export interface UtilsServiceHandlers extends grpc.UntypedServiceImplementation
 export interface UtilsServiceDefinition extends grpc.ServiceDefinition {
   CountEmailsByTargetId: MethodDefinition<_proto_Empty, _proto_Count, _proto_Empty__Output, _proto_Count__Output>
   CountFilesByTargetId: MethodDefinition<_proto_Empty, _proto_Count, _proto_Empty__Output, _proto_Count__Output>
+  CreateDataExport: MethodDefinition<_proto_DataExportRequest, _proto_File, _proto_DataExportRequest__Output, _proto_File__Output>
   DeleteFileById: MethodDefinition<_proto_Id, _proto_Empty, _proto_Id__Output, _proto_Empty__Output>
+  DeleteUserData: MethodDefinition<_proto_UserDataDeletion, _proto_Empty, _proto_UserDataDeletion__Output, _proto_Empty__Output>
   GetEmailsByTargetId: MethodDefinition<_proto_Page, _proto_Email, _proto_Page__Output, _proto_Email__Output>
   GetFileById: MethodDefinition<_proto_Id, _proto_File, _proto_Id__Output, _proto_File__Output>
   GetFilesByTargetId: MethodDefinition<_proto_Page, _proto_File, _proto_Page__Output, _proto_File__Output>
@@ -8,8 +8,21 @@ type SubtypeConstructor<Constructor extends new (...args: any) => any, Subtype>
 
 export interface ProtoGrpcType {
   proto: {
+    AuditEvent: MessageTypeDefinition
+    AuditEventFilter: MessageTypeDefinition
+    AuditEventType: EnumTypeDefinition
+    Identity: MessageTypeDefinition
+    Passkey: MessageTypeDefinition
+    Permission: EnumTypeDefinition
+    RecoveryCodes: MessageTypeDefinition
+    RoleTwoFactor: MessageTypeDefinition
+    Session: MessageTypeDefinition
+    TwoFactorCode: MessageTypeDefinition
+    TwoFactorSetup: MessageTypeDefinition
     User: MessageTypeDefinition
+    UserFilter: MessageTypeDefinition
     UserRole: EnumTypeDefinition
+    UserRoleChange: MessageTypeDefinition
   }
 }
 
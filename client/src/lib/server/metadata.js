@@ -1,26 +1,86 @@
 import { Metadata } from "@grpc/grpc-js";
+import { sign } from "node:crypto";
 import jwt from "jsonwebtoken";
-import { JWT_SECRET } from "$env/static/private";
+import {
+    JWT_ALGORITHM,
+    JWT_KID,
+    JWT_PRIVATE_KEY,
+    JWT_SECRET,
+} from "$env/static/private";
 
 /**
- * Create a Metadata object with the correct authorization headers
+ * Sign the token with the client private key, so the services only need its public key
+ * jsonwebtoken does not support EdDSA, so both asymmetric algorithms are signed by hand
+ * @param {Record<string, unknown>} payload - The token payload
+ * @returns {string} - The signed token
+ */
+function signAsymmetric(payload) {
+    const header = { alg: JWT_ALGORITHM, typ: "JWT", kid: JWT_KID };
+    const data = [header, payload]
+        .map((part) => Buffer.from(JSON.stringify(part)).toString("base64url"))
+        .join(".");
+    const signature = sign(
+        JWT_ALGORITHM === "EdDSA" ? null : "sha256",
+        Buffer.from(data),
+        JWT_PRIVATE_KEY,
+    );
+    return `${data}.${signature.toString("base64url")}`;
+}
+
+/**
+ * Sign the payload into a Metadata object with the correct authorization headers
  * Short lived token only for getting the data
- * @param {string} id - The token id or user id
+ * @param {Record<string, unknown>} tokenPayload - The token payload
  * @returns {Metadata} - Metadata object with the correct authorization headers
  */
-export function createMetadata(id) {
+function signMetadata(tokenPayload) {
     const metadata = new Metadata();
 
-    const tokenPayload = {
-        id: id,
-    };
-
     // Generate and sign the token
-    const oauthToken = jwt.sign(tokenPayload, JWT_SECRET, {
-        algorithm: "HS256",
-        expiresIn: "1h",
-    });
+    let oauthToken = "";
+    if (JWT_ALGORITHM === "RS256" || JWT_ALGORITHM === "EdDSA") {
+        const now = Math.floor(Date.now() / 1000);
+        oauthToken = signAsymmetric({
+            ...tokenPayload,
+            iat: now,
+            exp: now + 60 * 60,
+        });
+    } else {
+        // Legacy HS256, shared JWT_SECRET
+        oauthToken = jwt.sign(tokenPayload, JWT_SECRET, {
+            algorithm: "HS256",
+            expiresIn: "1h",
+        });
+    }
 
     metadata.set("x-authorization", `bearer ${oauthToken}`);
     return metadata;
 }
+
+/**
+ * Create a Metadata object with the correct authorization headers
+ * @param {string} id - The token id or user id
+ * @returns {Metadata} - Metadata object with the correct authorization headers
+ */
+export function createMetadata(id) {
+    return signMetadata({ id: id });
+}
+
+/**
+ * Create a Metadata object for the signed in user, checked by the services with `Claims::require`
+ * The role and permissions come from `Auth`, the admin impersonating the user is the `act` claim
+ * @param {App.Locals} locals - The locals set by the hooks
+ * @returns {Metadata} - Metadata object with the correct authorization headers
+ */
+export function createUserMetadata(locals) {
+    /** @type {Record<string, unknown>} */
+    const tokenPayload = {
+        id: locals.user.id,
+        role: locals.user.role,
+        permissions: locals.permissions,
+    };
+    if (locals.impersonator) {
+        tokenPayload.act = { sub: locals.impersonator };
+    }
+    return signMetadata(tokenPayload);
+}
@@ -3,5 +3,6 @@ export function load({ locals }) {
     return {
         email: locals.user.email,
         avatar: locals.user.avatar,
+        impersonator: locals.impersonator,
     };
 }
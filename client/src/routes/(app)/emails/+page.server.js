@@ -2,14 +2,14 @@ import { getFormValue } from "$lib/utils";
 import { grpcSafe } from "$lib/safe";
 import { utilsService } from "$lib/server/grpc";
 import { perf } from "$lib/server/logger";
-import { createMetadata } from "$lib/server/metadata";
+import { createUserMetadata } from "$lib/server/metadata";
 import { fail } from "@sveltejs/kit";
 import { safe } from "$lib/safe";
 
 /** @type {import('./$types').PageServerLoad} */
 export async function load({ locals, url }) {
     const end = perf("load_emails");
-    const metadata = createMetadata(locals.user.id);
+    const metadata = createUserMetadata(locals);
 
     /**
      * Count emails
@@ -81,7 +81,7 @@ export const actions = {
         const res = await new Promise((r) => {
             utilsService.SendEmail(
                 data,
-                createMetadata(locals.user.id),
+                createUserMetadata(locals),
                 grpcSafe(r),
             );
         });
@@ -1,7 +1,7 @@
 import { grpcSafe } from "$lib/safe";
 import { utilsService } from "$lib/server/grpc";
 import { perf } from "$lib/server/logger";
-import { createMetadata } from "$lib/server/metadata";
+import { createUserMetadata } from "$lib/server/metadata";
 import { fail } from "@sveltejs/kit";
 import { safe } from "$lib/safe";
 import { FileTarget } from "$lib/proto/proto/FileTarget";
@@ -10,7 +10,7 @@ import { getFormValue } from "$lib/utils";
 /** @type {import('./$types').PageServerLoad} */
 export async function load({ locals, url }) {
     const end = perf("load_files");
-    const metadata = createMetadata(locals.user.id);
+    const metadata = createUserMetadata(locals);
 
     /**
      * Count files
@@ -89,7 +89,7 @@ export const actions = {
         }
         const buffer = Buffer.from(arrayBuffer.data);
 
-        const metadata = createMetadata(locals.user.id);
+        const metadata = createUserMetadata(locals);
         const stream = utilsService.UploadFile(metadata);
 
         const chunkSize = 1024 * 64;
@@ -138,7 +138,7 @@ export const actions = {
         const form = await request.formData();
         const id = getFormValue(form, "id");
 
-        const metadata = createMetadata(locals.user.id);
+        const metadata = createUserMetadata(locals);
         const stream = utilsService.GetFileById({ id }, metadata);
 
         /** @type {import("$lib/proto/proto/File").File__Output} */
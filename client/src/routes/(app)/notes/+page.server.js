@@ -1,14 +1,14 @@
 import { getFormValue } from "$lib/utils";
 import { grpcSafe, safe } from "$lib/safe";
 import { notesService } from "$lib/server/grpc";
-import { createMetadata } from "$lib/server/metadata";
+import { createUserMetadata } from "$lib/server/metadata";
 import { fail } from "@sveltejs/kit";
 import { perf } from "$lib/server/logger";
 
 /** @type {import('./$types').PageServerLoad} */
 export async function load({ locals, url }) {
     const end = perf("load_notes");
-    const metadata = createMetadata(locals.user.id);
+    const metadata = createUserMetadata(locals);
 
     /**
      * Count notes
@@ -86,7 +86,7 @@ export const actions = {
             title: getFormValue(form, "title"),
             content: getFormValue(form, "content"),
         };
-        const metadata = createMetadata(locals.user.id);
+        const metadata = createUserMetadata(locals);
         /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Note").Note__Output>} */
         const req = await new Promise((r) => {
             notesService.CreateNote(data, metadata, grpcSafe(r));
@@ -1,6 +1,6 @@
 import { error, fail } from "@sveltejs/kit";
 import { getFormValue } from "$lib/utils";
-import { createMetadata } from "$lib/server/metadata";
+import { createUserMetadata } from "$lib/server/metadata";
 import { notesService } from "$lib/server/grpc";
 import { grpcSafe } from "$lib/safe";
 import { perf } from "$lib/server/logger";
@@ -13,7 +13,7 @@ export async function load({ locals, params }) {
         throw error(409, "Missing note id");
     }
 
-    const metadata = createMetadata(locals.user.id);
+    const metadata = createUserMetadata(locals);
     /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Note").Note__Output>} */
     const req = await new Promise((r) => {
         notesService.GetNoteById({ id }, metadata, grpcSafe(r));
@@ -42,7 +42,7 @@ export const actions = {
             content: getFormValue(form, "content"),
         };
 
-        const metadata = createMetadata(locals.user.id);
+        const metadata = createUserMetadata(locals);
         /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Note").Note__Output>} */
         const req = await new Promise((r) => {
             notesService.CreateNote(data, metadata, grpcSafe(r));
@@ -65,7 +65,7 @@ export const actions = {
         const data = {
             id: getFormValue(form, "id"),
         };
-        const metadata = createMetadata(locals.user.id);
+        const metadata = createUserMetadata(locals);
         /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty>} */
         const req = await new Promise((r) => {
             notesService.DeleteNoteById(data, metadata, grpcSafe(r));
@@ -4,7 +4,7 @@ import { grpcSafe } from "$lib/safe";
 import { upsendApi } from "$lib/server/api";
 import { usersService } from "$lib/server/grpc";
 import { logger, perf } from "$lib/server/logger";
-import { createMetadata } from "$lib/server/metadata";
+import { createUserMetadata } from "$lib/server/metadata";
 import { error, fail } from "@sveltejs/kit";
 
 /** @type {import('./$types').PageServerLoad} */
@@ -13,7 +13,7 @@ export async function load({ locals }) {
     const profile = await new Promise((r) => {
         usersService.GetProfileByUserId(
             {},
-            createMetadata(locals.user.id),
+            createUserMetadata(locals),
             grpcSafe(r),
         );
     });
@@ -148,7 +148,7 @@ export const actions = {
         const res = await new Promise((r) => {
             usersService.CreateProfile(
                 data,
-                createMetadata(locals.user.id),
+                createUserMetadata(locals),
                 grpcSafe(r),
             );
         });
@@ -0,0 +1,460 @@
+import { getFormValue } from "$lib/utils";
+import { grpcSafe, safe } from "$lib/safe";
+import { usersService } from "$lib/server/grpc";
+import { logger, perf } from "$lib/server/logger";
+import { createMetadata } from "$lib/server/metadata";
+import { AUTH_URI, COOKIE_DOMAIN } from "$env/static/private";
+import { PUBLIC_AUTH_URL } from "$env/static/public";
+import { Permission } from "$lib/proto/proto/Permission";
+import { fail, redirect } from "@sveltejs/kit";
+
+/**
+ * Back from a login started by `linkIdentity`, service-auth verified the identity
+ * It's linked with the session of this browser, not the one that started the login
+ * @param {string} code
+ * @param {string} accessToken
+ * @returns {Promise<import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>>}
+ */
+async function linkIdentity(code, accessToken) {
+    /** @type {import("$lib/safe").Safe<{ token: string, return_to: string }>} */
+    const exchange = await safe(
+        fetch(`${AUTH_URI}/oauth-exchange`, {
+            method: "POST",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ code, link: true }),
+        }).then((res) => {
+            if (!res.ok) {
+                throw new Error(`Failed to exchange link code: ${res.status}`);
+            }
+            return res.json();
+        }),
+    );
+    if (exchange.error) {
+        logger.error("Error during link code exchange");
+        return { error: true, msg: "The link has expired, try again" };
+    }
+    return await new Promise((r) => {
+        usersService.LinkIdentity(
+            { id: exchange.data.token },
+            createMetadata(accessToken),
+            grpcSafe(r),
+        );
+    });
+}
+
+/** @type {import('./$types').PageServerLoad} */
+export async function load({ locals, url }) {
+    const link = url.searchParams.get("link");
+    if (link) {
+        const end = perf("link_identity");
+        const s = await linkIdentity(link, locals.accessToken);
+        end();
+        if (!s.error) {
+            throw redirect(303, "/settings");
+        }
+        throw redirect(303, `/settings?link_error=${encodeURIComponent(s.msg)}`);
+    }
+
+    const end = perf("load_settings");
+
+    /**
+     * Sessions are authorized with the access token, so the current one can be marked
+     */
+    /** @typedef {import("$lib/proto/proto/Session").Session__Output} Session */
+    /** @type {import("@grpc/grpc-js").ClientReadableStream<Session>} */
+    const sessionsStream = usersService.GetSessions(
+        {},
+        createMetadata(locals.accessToken),
+    );
+    /** @type {Promise<Session[]>} */
+    const p1 = new Promise((res, rej) => {
+        /** @type {Session[]} */
+        const sessions = [];
+        sessionsStream.on("data", (session) => sessions.push(session));
+        sessionsStream.on("error", (err) => rej(err));
+        sessionsStream.on("end", () => res(sessions));
+    });
+
+    /** @typedef {import("$lib/proto/proto/Identity").Identity__Output} Identity */
+    /** @type {import("@grpc/grpc-js").ClientReadableStream<Identity>} */
+    const identitiesStream = usersService.GetIdentities(
+        {},
+        createMetadata(locals.accessToken),
+    );
+    /** @type {Promise<Identity[]>} */
+    const p2 = new Promise((res, rej) => {
+        /** @type {Identity[]} */
+        const identities = [];
+        identitiesStream.on("data", (identity) => identities.push(identity));
+        identitiesStream.on("error", (err) => rej(err));
+        identitiesStream.on("end", () => res(identities));
+    });
+
+    /** @typedef {import("$lib/proto/proto/Passkey").Passkey__Output} Passkey */
+    /** @type {import("@grpc/grpc-js").ClientReadableStream<Passkey>} */
+    const passkeysStream = usersService.GetPasskeys(
+        {},
+        createMetadata(locals.accessToken),
+    );
+    /** @type {Promise<Passkey[]>} */
+    const p3 = new Promise((res, rej) => {
+        /** @type {Passkey[]} */
+        const passkeys = [];
+        passkeysStream.on("data", (passkey) => passkeys.push(passkey));
+        passkeysStream.on("error", (err) => rej(err));
+        passkeysStream.on("end", () => res(passkeys));
+    });
+
+    const [s1, s2, s3] = await Promise.all([safe(p1), safe(p2), safe(p3)]);
+
+    const twoFactor = {
+        enabled: locals.user.two_factor_enabled,
+        required: locals.user.two_factor_required,
+        // Redirected here by the hooks, until it's set up
+        prompt: url.searchParams.get("two_factor") === "required",
+    };
+    const admin = {
+        manageRoles: locals.permissions.includes(Permission.MANAGE_ROLES),
+        impersonateUsers: locals.permissions.includes(
+            Permission.IMPERSONATE_USERS,
+        ),
+    };
+
+    end();
+    if (s1.error || s2.error || s3.error) {
+        return {
+            error: s1.error ? s1.msg : s2.error ? s2.msg : s3.error ? s3.msg : "",
+            sessions: [],
+            identities: [],
+            passkeys: [],
+            twoFactor,
+            admin,
+        };
+    }
+    return {
+        // Shown once, after a failed `linkIdentity`
+        error: url.searchParams.get("link_error") ?? "",
+        sessions: s1.data,
+        identities: s2.data,
+        passkeys: s3.data,
+        twoFactor,
+        admin,
+    };
+}
+
+/** @type {import('./$types').Actions} */
+export const actions = {
+    revokeSession: async ({ locals, request }) => {
+        const end = perf("revoke_session");
+        const form = await request.formData();
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.RevokeSession(
+                { id: getFormValue(form, "id") },
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    revokeOtherSessions: async ({ locals }) => {
+        const end = perf("revoke_other_sessions");
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.RevokeOtherSessions(
+                {},
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    /**
+     * Redirect to the OAuth login with a one-time link code
+     * Once the user signs in with the provider, service-auth comes back here with `?link=`
+     */
+    linkIdentity: async ({ locals, request }) => {
+        const end = perf("link_identity");
+        const form = await request.formData();
+        const provider = getFormValue(form, "provider");
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Id").Id__Output>} */
+        const s = await new Promise((r) => {
+            usersService.CreateLinkCode(
+                {},
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        throw redirect(
+            303,
+            `${PUBLIC_AUTH_URL}/oauth-login/${encodeURIComponent(provider)}?link=${s.data.id}`,
+        );
+    },
+    unlinkIdentity: async ({ locals, request }) => {
+        const end = perf("unlink_identity");
+        const form = await request.formData();
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.UnlinkIdentity(
+                { id: getFormValue(form, "id") },
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    /**
+     * The page registers the passkey with service-auth, the link code proves which user it's for
+     */
+    createPasskeyCode: async ({ locals }) => {
+        const end = perf("create_passkey_code");
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Id").Id__Output>} */
+        const s = await new Promise((r) => {
+            usersService.CreateLinkCode(
+                {},
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { passkeyCode: s.data.id, email: locals.user.email };
+    },
+    deletePasskey: async ({ locals, request }) => {
+        const end = perf("delete_passkey");
+        const form = await request.formData();
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.DeletePasskey(
+                { id: getFormValue(form, "id") },
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    /**
+     * Start the two-factor enrolment, the secret is shown until it's confirmed with a code
+     */
+    setupTwoFactor: async ({ locals }) => {
+        const end = perf("setup_two_factor");
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/TwoFactorSetup").TwoFactorSetup__Output>} */
+        const s = await new Promise((r) => {
+            usersService.SetupTwoFactor(
+                {},
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { setup: s.data };
+    },
+    /**
+     * The recovery codes are returned only once, so they are shown right away
+     */
+    enableTwoFactor: async ({ locals, request }) => {
+        const end = perf("enable_two_factor");
+        const form = await request.formData();
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/RecoveryCodes").RecoveryCodes__Output>} */
+        const s = await new Promise((r) => {
+            usersService.EnableTwoFactor(
+                { code: getFormValue(form, "code") },
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { recoveryCodes: s.data.codes };
+    },
+    disableTwoFactor: async ({ locals, request }) => {
+        const end = perf("disable_two_factor");
+        const form = await request.formData();
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.DisableTwoFactor(
+                { code: getFormValue(form, "code") },
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    /**
+     * Needs MANAGE_ROLES, checked by the users service
+     */
+    setRoleTwoFactor: async ({ locals, request }) => {
+        const end = perf("set_role_two_factor");
+        const form = await request.formData();
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.SetRoleTwoFactor(
+                {
+                    role: /** @type {import("$lib/proto/proto/UserRole").UserRole} */ (
+                        Number(getFormValue(form, "role"))
+                    ),
+                    required: getFormValue(form, "required") === "true",
+                },
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    /**
+     * The archive is built in the background, the user gets an email when it's ready
+     */
+    createDataExport: async ({ locals }) => {
+        const end = perf("create_data_export");
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.CreateDataExport(
+                {},
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        return { success: true };
+    },
+    /**
+     * Every session is revoked, /auth clears the cookies
+     */
+    deleteAccount: async ({ locals, request }) => {
+        const end = perf("delete_account");
+        const form = await request.formData();
+        if (getFormValue(form, "confirm") !== "delete") {
+            return fail(400, { error: 'Type "delete" to confirm' });
+        }
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Empty").Empty__Output>} */
+        const s = await new Promise((r) => {
+            usersService.DeleteAccount(
+                {},
+                createMetadata(locals.accessToken),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        end();
+        throw redirect(303, "/auth");
+    },
+    /**
+     * Needs IMPERSONATE_USERS, checked by the users service
+     * The admin is signed out, the impersonated session has only an access token
+     */
+    impersonateUser: async ({ cookies, getClientAddress, locals, request }) => {
+        const end = perf("impersonate_user");
+        const form = await request.formData();
+
+        const metadata = createMetadata(locals.accessToken);
+        metadata.set("x-user-agent", request.headers.get("user-agent") ?? "");
+        metadata.set("x-client-ip", getClientAddress());
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/AuthResponse").AuthResponse__Output>} */
+        const s = await new Promise((r) => {
+            usersService.ImpersonateUser(
+                { id: getFormValue(form, "user_id") },
+                metadata,
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        // We don't check for errors, the session is replaced either way
+        await new Promise((r) => {
+            usersService.Logout({}, createMetadata(locals.token), grpcSafe(r));
+        });
+        cookies.set("token", "", {
+            domain: COOKIE_DOMAIN,
+            path: "/",
+            maxAge: 0,
+        });
+        cookies.set("access_token", s.data.access_token, {
+            domain: COOKIE_DOMAIN,
+            path: "/",
+            // 15 minutes, same as the access token itself
+            maxAge: 900,
+        });
+
+        end();
+        throw redirect(303, "/dashboard");
+    },
+};
@@ -15,7 +15,8 @@ export function load({ locals }) {
 export const actions = {
     createStripeCheckout: async ({ locals }) => {
         const end = perf("create_stripe_checkout");
-        const metadata = createMetadata(locals.user.id);
+        // Checked against the session, impersonated sessions can't manage billing
+        const metadata = createMetadata(locals.accessToken);
 
         /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/StripeUrlResponse").StripeUrlResponse__Output>} */
         const s = await new Promise((r) =>
@@ -31,7 +32,7 @@ export const actions = {
     },
     createStripePortal: async ({ locals }) => {
         const end = perf("create_stripe_portal");
-        const metadata = createMetadata(locals.user.id);
+        const metadata = createMetadata(locals.accessToken);
 
         /** @type {import("$lib/safe").Safe<
          * import("$lib/proto/proto/StripeUrlResponse").StripeUrlResponse__Output
@@ -0,0 +1,49 @@
+import { AUTH_URI, ENV } from "$env/static/private";
+import { getFormValue } from "$lib/utils";
+import { safe } from "$lib/safe";
+import { perf } from "$lib/server/logger";
+import { fail } from "@sveltejs/kit";
+
+/** @type {import('./$types').PageServerLoad} */
+export function load() {
+    // The fake `dev` provider of the auth service, see DEV_OAUTH
+    return { devLogin: ENV === "development" };
+}
+
+/** @type {import('./$types').Actions} */
+export const actions = {
+    /**
+     * Ask the auth service to email a sign in link
+     * The response doesn't tell if the account exists, so neither does the page
+     */
+    magicLink: async ({ request }) => {
+        const end = perf("magic_link");
+        const form = await request.formData();
+        const email = getFormValue(form, "email");
+
+        const res = await safe(
+            fetch(`${AUTH_URI}/magic-link`, {
+                method: "POST",
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ email }),
+            }),
+        );
+        if (res.error) {
+            return fail(500, { error: res.msg });
+        }
+        if (res.data.status === 400) {
+            return fail(400, { error: "Invalid email address" });
+        }
+        if (res.data.status === 429) {
+            return fail(429, {
+                error: "Too many sign in links requested, try again later",
+            });
+        }
+        if (!res.data.ok) {
+            return fail(500, { error: "Cannot send the sign in link" });
+        }
+
+        end();
+        return { success: true };
+    },
+};
@@ -0,0 +1,76 @@
+import { COOKIE_DOMAIN } from "$env/static/private";
+import { getFormValue } from "$lib/utils";
+import { grpcSafe } from "$lib/safe";
+import { usersService } from "$lib/server/grpc";
+import { perf } from "$lib/server/logger";
+import { createMetadata } from "$lib/server/metadata";
+import { fail, redirect } from "@sveltejs/kit";
+
+/** @type {import('./$types').PageServerLoad} */
+export async function load({ url }) {
+    const challenge = url.searchParams.get("challenge");
+    if (!challenge) {
+        throw redirect(302, "/auth");
+    }
+    return { challenge, returnTo: url.searchParams.get("return_to") ?? "" };
+}
+
+/**
+ * The auth service only passes allowed urls, but this one comes from the query string,
+ * so only the pages of this app are accepted
+ * @param {string} returnTo
+ * @param {URL} url - The current url
+ * @returns {string}
+ */
+function localReturnTo(returnTo, url) {
+    if (!returnTo) {
+        return "/dashboard";
+    }
+    try {
+        const target = new URL(returnTo, url.origin);
+        if (target.origin !== url.origin) {
+            return "/dashboard";
+        }
+        return target.pathname + target.search + target.hash;
+    } catch {
+        return "/dashboard";
+    }
+}
+
+/** @type {import('./$types').Actions} */
+export const actions = {
+    /**
+     * Complete the challenge with a code from the authenticator app or a recovery code
+     * The session token is set the same way as after the code exchange
+     */
+    verify: async ({ cookies, request, url }) => {
+        const end = perf("verify_two_factor");
+        const form = await request.formData();
+        const challenge = getFormValue(form, "challenge");
+        const code = getFormValue(form, "code");
+        const returnTo = getFormValue(form, "return_to");
+
+        /** @type {import("$lib/safe").Safe<import("$lib/proto/proto/Id").Id__Output>} */
+        const s = await new Promise((r) => {
+            usersService.VerifyTwoFactor(
+                { code },
+                createMetadata(challenge),
+                grpcSafe(r),
+            );
+        });
+
+        if (s.error) {
+            return fail(400, { error: s.msg });
+        }
+
+        cookies.set("token", s.data.id, {
+            domain: COOKIE_DOMAIN,
+            path: "/",
+            // 10 seconds, it should be enough to be read by the backend on the next request
+            maxAge: 10,
+        });
+
+        end();
+        throw redirect(303, localReturnTo(returnTo, url));
+    },
+};
@@ -0,0 +1,55 @@
+# End-to-end check of the login flow with the `dev` provider, without a browser or the client:
+# login -> dev authorize -> callback -> /oauth-exchange.
+# Needs service-auth with DEV_OAUTH=true, and service-users.
+# Usage: sh e2e-dev-login.sh [AUTH_URL] [EMAIL]
+set -eu
+AUTH_URL=${1:-http://localhost:8090}
+EMAIL=${2:-dev@example.com}
+
+fail() {
+    echo "$1" >&2
+    exit 1
+}
+
+# Every step is a redirect, follow one at a time to check where it goes
+location() {
+    curl -sS -o /dev/null -w '%{redirect_url}' "$1"
+}
+
+# The code, then extra curl options
+exchange() {
+    body="{\"code\":\"$1\"}"
+    shift
+    curl -sS -X POST -H "Content-Type: application/json" -d "$body" "$@" "$AUTH_URL/oauth-exchange"
+}
+
+authorize=$(location "$AUTH_URL/oauth-login/dev?login_hint=$(echo "$EMAIL" | sed 's/@/%40/')")
+case "$authorize" in
+"$AUTH_URL/dev-oauth/authorize?"*) ;;
+*) fail "Login didn't redirect to the dev provider: $authorize" ;;
+esac
+
+callback=$(location "$authorize")
+case "$callback" in
+"$AUTH_URL/oauth-callback/dev?"*) ;;
+*) fail "Dev provider didn't redirect to the callback: $callback" ;;
+esac
+
+session=$(location "$callback")
+code=${session#*\?code=}
+if [ "$code" = "$session" ]; then
+    fail "Callback didn't redirect with a code: $session"
+fi
+
+token=$(exchange "$code" -f | jq -r .token)
+if [ -z "$token" ] || [ "$token" = "null" ]; then
+    fail "Exchange didn't return a token"
+fi
+
+# The code is single use
+status=$(exchange "$code" -o /dev/null -w '%{http_code}')
+if [ "$status" != "401" ]; then
+    fail "Code was exchanged twice: $status"
+fi
+
+echo "Signed in as $EMAIL"
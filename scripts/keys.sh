@@ -1,7 +1,26 @@
-openssl genpkey -algorithm RSA -out private.key -pkeyopt rsa_keygen_bits:2048
-openssl rsa -pubout -in private.key -out public.key
+# Generate one signing key for each part that signs tokens (client, auth, notes, users)
+# and a JWKS with all of their public keys, used by every service to verify them.
+# Usage: sh keys.sh [rsa|ed25519]
+ALG=${1:-rsa}
 
-cp private.key ../client/src/lib/server/private.key
-cp public.key ../service-users/public.key
-cp public.key ../service-notes/public.key
-cp public.key ../service-utils/public.key
+mkdir -p keys
+for name in client auth notes users; do
+    if [ "$ALG" = "ed25519" ]; then
+        openssl genpkey -algorithm ed25519 -out keys/$name.key
+    else
+        openssl genpkey -algorithm RSA -out keys/$name.key -pkeyopt rsa_keygen_bits:2048
+    fi
+done
+
+node -e '
+const { createPublicKey } = require("node:crypto");
+const { readFileSync } = require("node:fs");
+const alg = process.argv[1] === "ed25519" ? "EdDSA" : "RS256";
+const keys = ["client", "auth", "notes", "users"].map((kid) => ({
+    ...createPublicKey(readFileSync(`keys/${kid}.key`)).export({ format: "jwk" }),
+    kid,
+    alg,
+    use: "sig",
+}));
+console.log(JSON.stringify({ keys }));
+' "$ALG" > keys/jwks.json
@@ -0,0 +1,101 @@
+use crate::{
+    auth_error::AuthFailure,
+    proto::{users_service_client::UsersServiceClient, AuditEventType},
+    AppState,
+};
+use anyhow::Result;
+use axum::http::HeaderMap;
+use std::{net::SocketAddr, sync::Arc};
+
+/**
+ * Who made the request, recorded with the audit events and the sessions.
+ */
+pub struct ClientInfo {
+    pub ip: String,
+    pub user_agent: String,
+}
+
+impl ClientInfo {
+    pub fn new(state: &AppState, headers: &HeaderMap, addr: SocketAddr) -> Self {
+        let ip = crate::rate_limit::client_ip(&state.env.rate_limit, headers, addr);
+        let user_agent = headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        ClientInfo {
+            ip: ip.to_string(),
+            user_agent: user_agent.chars().take(512).collect(),
+        }
+    }
+
+    /**
+     * Forward them to the users service, the same way the client does.
+     */
+    pub fn insert_metadata(&self, metadata: &mut tonic::metadata::MetadataMap) {
+        if let Ok(ip) = self.ip.parse() {
+            metadata.insert("x-client-ip", ip);
+        }
+        if let Ok(user_agent) = self.user_agent.parse() {
+            metadata.insert("x-user-agent", user_agent);
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct AuditClaims<'a> {
+    r#type: i32,
+    user_id: &'a str,
+    provider: &'a str,
+    ip: &'a str,
+    user_agent: &'a str,
+    detail: String,
+    exp: i64,
+}
+
+async fn create_audit_event(
+    state: &AppState,
+    provider: &str,
+    client: &ClientInfo,
+    detail: String,
+) -> Result<()> {
+    let claims = AuditClaims {
+        r#type: AuditEventType::LoginFailure as i32,
+        user_id: "",
+        provider,
+        ip: &client.ip,
+        user_agent: &client.user_agent,
+        detail,
+        // 5 min
+        exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
+    };
+    let token = state
+        .env
+        .jwt_keys
+        .encode(&claims, &service_auth::AUDIT_TOKEN)?;
+    let mut request = tonic::Request::new(crate::proto::Empty {});
+    request
+        .metadata_mut()
+        .insert("x-authorization", format!("bearer {}", token).parse()?);
+    let mut client = UsersServiceClient::connect(state.env.users_url.to_owned()).await?;
+    client.create_audit_event(request).await?;
+    Ok(())
+}
+
+/**
+ * Record a login that failed before reaching the users service, in the background.
+ * Failures of `CreateUser` itself are recorded by the users service.
+ */
+pub fn spawn_login_failure(
+    state: Arc<AppState>,
+    provider: String,
+    client: ClientInfo,
+    failure: &AuthFailure,
+) {
+    // The same code and id as the redirect, so the event can be matched to the log line
+    let detail = format!("{} {}", failure.error.code(), failure.error_id);
+    tokio::spawn(async move {
+        if let Err(err) = create_audit_event(&state, &provider, &client, detail).await {
+            tracing::error!("Failed to record login failure: {:?}", err);
+        }
+    });
+}
@@ -4,37 +4,43 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub struct Verifier {
-    pub id: Uuid,
     pub created: OffsetDateTime,
-    pub csrf_token: String,
     pub pkce_verifier: String,
+    pub nonce: String,
+    // Set when a signed in user links another provider to their account
+    pub link_code: String,
+    // Where to send the user after login, validated again before use
+    pub return_to: String,
 }
 
 impl TryFrom<tokio_postgres::Row> for Verifier {
     type Error = anyhow::Error;
 
     fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
-        let id: Uuid = value.try_get("id")?;
         let created: OffsetDateTime = value.try_get("created")?;
-        let csrf_token: String = value.try_get("csrf_token")?;
         let pkce_verifier: String = value.try_get("pkce_verifier")?;
+        let nonce: String = value.try_get("nonce")?;
+        let link_code: String = value.try_get("link_code")?;
+        let return_to: String = value.try_get("return_to")?;
 
         Ok(Verifier {
-            id,
             created,
-            csrf_token,
             pkce_verifier,
+            nonce,
+            link_code,
+            return_to,
         })
     }
 }
 
-pub async fn select_verifiers_by_csrf(
+// Select and delete in one statement, so a CSRF token can only be used once
+pub async fn delete_verifiers_by_csrf(
     client: &Object,
     csrf_token: &str,
 ) -> Result<Option<Verifier>> {
     let row = client
         .query_opt(
-            "select * from verifiers where csrf_token = $1",
+            "delete from verifiers where csrf_token = $1 returning *",
             &[&csrf_token],
         )
         .await?;
@@ -48,22 +54,244 @@ pub async fn create_verifiers(
     client: &Object,
     csrf_token: &str,
     pkce_verifier: &str,
+    nonce: &str,
+    link_code: &str,
+    return_to: &str,
 ) -> Result<Verifier> {
     let uuid = Uuid::now_v7();
     let row = client
         .query_one(
-            "insert into verifiers (id, csrf_token, pkce_verifier) values ($1, $2, $3) returning *",
-            &[&uuid, &csrf_token, &pkce_verifier],
+            "insert into verifiers (id, csrf_token, pkce_verifier, nonce, link_code, return_to) values ($1, $2, $3, $4, $5, $6) returning *",
+            &[&uuid, &csrf_token, &pkce_verifier, &nonce, &link_code, &return_to],
         )
         .await?;
     Verifier::try_from(row)
 }
 
-// 5 minutes
+pub async fn count_verifiers(client: &Object) -> Result<i64> {
+    let row = client
+        .query_one(
+            "select count(*) from verifiers where created > now() - interval '10 minutes'",
+            &[],
+        )
+        .await?;
+    Ok(row.try_get(0)?)
+}
+
+// 10 minutes, see `VERIFIER_TTL`
 pub async fn delete_old_verifiers(client: &Object) -> Result<()> {
     client
         .execute(
-            "delete from verifiers where created < now() - interval '5 minutes'",
+            "delete from verifiers where created < now() - interval '10 minutes'",
+            &[],
+        )
+        .await?;
+    Ok(())
+}
+
+pub struct Code {
+    pub created: OffsetDateTime,
+    pub token: String,
+    pub return_to: String,
+    // The token is a verified identity to link, not a session
+    pub link: bool,
+}
+
+impl TryFrom<tokio_postgres::Row> for Code {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let created: OffsetDateTime = value.try_get("created")?;
+        let token: String = value.try_get("token")?;
+        let return_to: String = value.try_get("return_to")?;
+        let link: bool = value.try_get("link")?;
+
+        Ok(Code {
+            created,
+            token,
+            return_to,
+            link,
+        })
+    }
+}
+
+pub async fn create_code(
+    client: &Object,
+    code: &str,
+    token: &str,
+    return_to: &str,
+    link: bool,
+) -> Result<Code> {
+    let uuid = Uuid::now_v7();
+    let row = client
+        .query_one(
+            "insert into codes (id, code, token, return_to, link) values ($1, $2, $3, $4, $5) returning *",
+            &[&uuid, &code, &token, &return_to, &link],
+        )
+        .await?;
+    Code::try_from(row)
+}
+
+// Codes are single use, so they are deleted as soon as they are read.
+pub async fn delete_code(client: &Object, code: &str) -> Result<Option<Code>> {
+    let row = client
+        .query_opt("delete from codes where code = $1 returning *", &[&code])
+        .await?;
+    match row {
+        Some(row) => Ok(Some(Code::try_from(row)?)),
+        None => Ok(None),
+    }
+}
+
+// 1 minute
+pub async fn delete_old_codes(client: &Object) -> Result<()> {
+    client
+        .execute(
+            "delete from codes where created < now() - interval '1 minute'",
+            &[],
+        )
+        .await?;
+    Ok(())
+}
+
+pub struct MagicLink {
+    pub id: Uuid,
+    pub email: String,
+}
+
+impl TryFrom<tokio_postgres::Row> for MagicLink {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let id: Uuid = value.try_get("id")?;
+        let email: String = value.try_get("email")?;
+
+        Ok(MagicLink { id, email })
+    }
+}
+
+/**
+ * Only the hash of the token is stored, the token itself is only in the email.
+ * Returns None when the address already requested `max_links` in the last 15 minutes. The count
+ * is taken and raised in the same statement, under the row lock of the upsert, so concurrent
+ * requests can't both see the last free slot.
+ */
+pub async fn create_magic_link(
+    client: &Object,
+    email: &str,
+    token_hash: &str,
+    max_links: i32,
+) -> Result<Option<MagicLink>> {
+    let uuid = Uuid::now_v7();
+    let row = client
+        .query_opt(
+            "
+            with allowed as (
+                insert into magic_link_limits as limits (email) values ($2)
+                on conflict (email) do update set
+                    window_start = case when limits.window_start < now() - interval '15 minutes' then now() else limits.window_start end,
+                    count = case when limits.window_start < now() - interval '15 minutes' then 1 else limits.count + 1 end
+                where limits.window_start < now() - interval '15 minutes' or limits.count < $4
+                returning email
+            )
+            insert into magic_links (id, email, token_hash, expires)
+            select $1, email, $3, now() + interval '15 minutes' from allowed
+            returning *
+            ",
+            &[&uuid, &email, &token_hash, &max_links],
+        )
+        .await?;
+    row.map(MagicLink::try_from).transpose()
+}
+
+// Magic links are single use, so they are marked as used as soon as they are read.
+pub async fn use_magic_link(client: &Object, token_hash: &str) -> Result<Option<MagicLink>> {
+    let row = client
+        .query_opt(
+            "update magic_links set used = now() where token_hash = $1 and used = 'infinity' and expires > now() returning *",
+            &[&token_hash],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(MagicLink::try_from(row)?)),
+        None => Ok(None),
+    }
+}
+
+// 1 day, together with the limits of the addresses that stopped requesting links
+pub async fn delete_old_magic_links(client: &Object) -> Result<()> {
+    client
+        .execute(
+            "delete from magic_links where created < now() - interval '1 day'",
+            &[],
+        )
+        .await?;
+    client
+        .execute(
+            "delete from magic_link_limits where window_start < now() - interval '1 day'",
+            &[],
+        )
+        .await?;
+    Ok(())
+}
+
+pub struct PasskeyChallenge {
+    pub challenge: String,
+    // Set for registrations, the link code of the signed in user
+    pub link_code: String,
+}
+
+impl TryFrom<tokio_postgres::Row> for PasskeyChallenge {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let challenge: String = value.try_get("challenge")?;
+        let link_code: String = value.try_get("link_code")?;
+
+        Ok(PasskeyChallenge {
+            challenge,
+            link_code,
+        })
+    }
+}
+
+pub async fn create_passkey_challenge(
+    client: &Object,
+    challenge: &str,
+    link_code: &str,
+) -> Result<Uuid> {
+    let uuid = Uuid::now_v7();
+    client
+        .execute(
+            "insert into passkey_challenges (id, challenge, link_code) values ($1, $2, $3)",
+            &[&uuid, &challenge, &link_code],
+        )
+        .await?;
+    Ok(uuid)
+}
+
+// Challenges are single use, so they are deleted as soon as they are read.
+pub async fn delete_passkey_challenge(
+    client: &Object,
+    id: &Uuid,
+) -> Result<Option<PasskeyChallenge>> {
+    let row = client
+        .query_opt(
+            "delete from passkey_challenges where id = $1 and created > now() - interval '5 minutes' returning *",
+            &[&id],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(PasskeyChallenge::try_from(row)?)),
+        None => Ok(None),
+    }
+}
+
+// 5 minutes
+pub async fn delete_old_passkey_challenges(client: &Object) -> Result<()> {
+    client
+        .execute(
+            "delete from passkey_challenges where created < now() - interval '5 minutes'",
             &[],
         )
         .await?;
@@ -0,0 +1,241 @@
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form, Json,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use oauth2::CsrfToken;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+
+// A fake OAuth provider, so the whole login flow runs offline, in development and CI.
+// It's only mounted with DEV_OAUTH=true, and signs in as anyone from DEV_OAUTH_USERS without a password.
+
+struct Grant {
+    created: OffsetDateTime,
+    email: String,
+    redirect_uri: String,
+    code_challenge: String,
+}
+
+/**
+ * The codes and access tokens issued by the provider, kept in memory.
+ */
+#[derive(Default)]
+pub struct DevOAuth {
+    codes: Mutex<HashMap<String, Grant>>,
+    tokens: Mutex<HashMap<String, (OffsetDateTime, String)>>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AuthorizeQuery {
+    redirect_uri: String,
+    state: String,
+    code_challenge: String,
+    code_challenge_method: String,
+    // Skips the page, e.g. `/oauth-login/dev?login_hint=dev@example.com` in end-to-end tests
+    login_hint: Option<String>,
+    // Set by the deny button of the page
+    deny: Option<String>,
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn callback_url(redirect_uri: &str, params: &[(&str, &str)]) -> Result<String, StatusCode> {
+    let mut url = reqwest::Url::parse(redirect_uri).map_err(|_| StatusCode::BAD_REQUEST)?;
+    url.query_pairs_mut().extend_pairs(params);
+    Ok(url.to_string())
+}
+
+/**
+ * The consent page, with a button for every fake user.
+ */
+pub async fn authorize(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuthorizeQuery>,
+) -> Result<Response, StatusCode> {
+    // Only redirect to our own callback, like a real provider with a registered redirect url
+    if query.redirect_uri != format!("{}/oauth-callback/dev", state.env.auth_url) {
+        tracing::error!("Invalid redirect_uri: {:?}", query.redirect_uri);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if query.code_challenge_method != "S256" {
+        tracing::error!(
+            "Unsupported code_challenge_method: {:?}",
+            query.code_challenge_method
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if query.deny.is_some() {
+        let url = callback_url(
+            &query.redirect_uri,
+            &[("error", "access_denied"), ("state", &query.state)],
+        )?;
+        return Ok(Redirect::to(&url).into_response());
+    }
+
+    let Some(email) = query.login_hint else {
+        let users: String = state
+            .env
+            .dev_oauth_users
+            .iter()
+            .map(|email| {
+                format!(
+                    r#"<button name="login_hint" value="{0}">{0}</button>"#,
+                    escape(email)
+                )
+            })
+            .collect();
+        let hidden: String = [
+            ("redirect_uri", &query.redirect_uri),
+            ("state", &query.state),
+            ("code_challenge", &query.code_challenge),
+            ("code_challenge_method", &query.code_challenge_method),
+        ]
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                r#"<input type="hidden" name="{}" value="{}" />"#,
+                name,
+                escape(value)
+            )
+        })
+        .collect();
+        return Ok(Html(format!(
+            r#"<!doctype html>
+            <title>Dev login</title>
+            <h1>Sign in as</h1>
+            <form method="get">{hidden}{users}<button name="deny" value="1">Deny</button></form>"#
+        ))
+        .into_response());
+    };
+    if !state.env.dev_oauth_users.contains(&email) {
+        tracing::error!("Unknown dev user: {:?}", email);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let code = CsrfToken::new_random().secret().to_owned();
+    state
+        .dev_oauth
+        .codes
+        .lock()
+        .expect("Dev OAuth lock poisoned")
+        .insert(
+            code.to_owned(),
+            Grant {
+                created: OffsetDateTime::now_utc(),
+                email,
+                redirect_uri: query.redirect_uri.to_owned(),
+                code_challenge: query.code_challenge,
+            },
+        );
+    let url = callback_url(
+        &query.redirect_uri,
+        &[("code", &code), ("state", &query.state)],
+    )?;
+    Ok(Redirect::to(&url).into_response())
+}
+
+#[derive(serde::Deserialize)]
+pub struct TokenRequest {
+    grant_type: String,
+    code: String,
+    redirect_uri: String,
+    code_verifier: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct TokenResponse {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: i64,
+}
+
+pub async fn token(
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<TokenRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    if form.grant_type != "authorization_code" {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let grant = state
+        .dev_oauth
+        .codes
+        .lock()
+        .expect("Dev OAuth lock poisoned")
+        .remove(&form.code)
+        .ok_or_else(|| {
+            tracing::error!("Invalid dev code");
+            StatusCode::BAD_REQUEST
+        })?;
+    if grant.created + time::Duration::minutes(1) < OffsetDateTime::now_utc() {
+        tracing::error!("Dev code has expired");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if grant.redirect_uri != form.redirect_uri {
+        tracing::error!("Dev code redirect_uri mismatch");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(form.code_verifier.as_bytes()));
+    if challenge != grant.code_challenge {
+        tracing::error!("Dev code PKCE mismatch");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let access_token = CsrfToken::new_random().secret().to_owned();
+    let now = OffsetDateTime::now_utc();
+    let mut tokens = state
+        .dev_oauth
+        .tokens
+        .lock()
+        .expect("Dev OAuth lock poisoned");
+    tokens.retain(|_, (created, _)| *created + time::Duration::hours(1) > now);
+    tokens.insert(access_token.to_owned(), (now, grant.email));
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "bearer",
+        expires_in: 3600,
+    }))
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct DevUser {
+    pub sub: String,
+    pub email: String,
+    pub email_verified: bool,
+}
+
+pub async fn userinfo(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<DevUser>, StatusCode> {
+    let access_token = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let tokens = state
+        .dev_oauth
+        .tokens
+        .lock()
+        .expect("Dev OAuth lock poisoned");
+    let (_, email) = tokens.get(access_token).ok_or(StatusCode::UNAUTHORIZED)?;
+    Ok(Json(DevUser {
+        sub: email.to_owned(),
+        email: email.to_owned(),
+        email_verified: true,
+    }))
+}
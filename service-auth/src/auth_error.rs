@@ -0,0 +1,206 @@
+use axum::response::{IntoResponse, Redirect, Response};
+use prost::Message;
+
+use crate::proto::{LoginError, LoginFailure};
+
+/**
+ * Why a login failed, sent to the client as `/auth?error={code}&error_id={id}`.
+ * The codes are stable, the client shows a message for each of them.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    // The user declined the consent screen
+    AccessDenied,
+    // The provider sent back any other `error=`
+    ProviderError,
+    UnknownProvider,
+    // Missing or unknown `state`, the login was not started here
+    InvalidState,
+    // The login took longer than 10 minutes
+    ExpiredState,
+    // The provider didn't send a code, or it couldn't be exchanged
+    InvalidCode,
+    // The profile couldn't be read or verified
+    InvalidProfile,
+    EmailNotVerified,
+    // The email belongs to another account, or the identity is linked to one
+    AccountExists,
+    // The account was deleted by an admin
+    AccountDisabled,
+    InvalidLinkCode,
+    InvalidMagicLink,
+    UsersUnavailable,
+    // Too many logins in progress
+    TooManyRequests,
+    Internal,
+}
+
+impl AuthError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthError::AccessDenied => "access_denied",
+            AuthError::ProviderError => "provider_error",
+            AuthError::UnknownProvider => "unknown_provider",
+            AuthError::InvalidState => "invalid_state",
+            AuthError::ExpiredState => "expired_state",
+            AuthError::InvalidCode => "invalid_code",
+            AuthError::InvalidProfile => "invalid_profile",
+            AuthError::EmailNotVerified => "email_not_verified",
+            AuthError::AccountExists => "account_exists",
+            AuthError::AccountDisabled => "account_disabled",
+            AuthError::InvalidLinkCode => "invalid_link_code",
+            AuthError::InvalidMagicLink => "invalid_magic_link",
+            AuthError::UsersUnavailable => "users_unavailable",
+            AuthError::TooManyRequests => "too_many_requests",
+            AuthError::Internal => "internal",
+        }
+    }
+
+    /**
+     * The `error` sent by the provider to the callback, see RFC 6749 section 4.1.2.1.
+     */
+    pub fn from_provider(error: &str) -> Self {
+        match error {
+            "access_denied" | "consent_required" | "interaction_required" | "login_required" => {
+                AuthError::AccessDenied
+            }
+            _ => AuthError::ProviderError,
+        }
+    }
+
+    /**
+     * Errors of the users service that the user can act on, anything else is an outage.
+     */
+    pub fn from_status(status: &tonic::Status) -> Self {
+        match login_error(status) {
+            Some(LoginError::AccountExists) => AuthError::AccountExists,
+            Some(LoginError::AccountDisabled) => AuthError::AccountDisabled,
+            Some(LoginError::InvalidLinkCode) => AuthError::InvalidLinkCode,
+            Some(LoginError::Unset) | None => match status.code() {
+                tonic::Code::Unavailable => AuthError::UsersUnavailable,
+                _ => AuthError::Internal,
+            },
+        }
+    }
+
+    /**
+     * The error attached to `err`, if any, otherwise `fallback`.
+     */
+    pub fn from_anyhow(err: &anyhow::Error, fallback: AuthError) -> Self {
+        if let Some(error) = err.downcast_ref::<AuthError>() {
+            return *error;
+        }
+        if let Some(status) = err.downcast_ref::<tonic::Status>() {
+            return AuthError::from_status(status);
+        }
+        fallback
+    }
+
+    /**
+     * Log the failure with a new correlation id, and send the user back to the login page.
+     * The same id is shown to the user, so a support request can be matched to this log line.
+     */
+    pub fn redirect(self, client_url: &str, message: impl std::fmt::Display) -> AuthFailure {
+        let error_id = uuid::Uuid::now_v7();
+        tracing::error!(error = self.code(), error_id = %error_id, "{}", message);
+        AuthFailure {
+            error: self,
+            error_id,
+            redirect: Redirect::to(&format!(
+                "{}/auth?error={}&error_id={}",
+                client_url,
+                self.code(),
+                error_id
+            )),
+        }
+    }
+}
+
+/**
+ * The reason service-users attached to the error, see `LoginFailure`.
+ */
+pub fn login_error(status: &tonic::Status) -> Option<LoginError> {
+    let failure = LoginFailure::decode(status.details()).ok()?;
+    LoginError::from_i32(failure.reason)
+}
+
+/**
+ * A failed login, responds with the redirect to the login page.
+ * The error and its id are kept for the audit log.
+ */
+pub struct AuthFailure {
+    pub error: AuthError,
+    pub error_id: uuid::Uuid,
+    redirect: Redirect,
+}
+
+impl IntoResponse for AuthFailure {
+    fn into_response(self) -> Response {
+        self.redirect.into_response()
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthError;
+    use crate::proto::{LoginError, LoginFailure};
+    use prost::Message;
+
+    fn status(code: tonic::Code, reason: LoginError) -> tonic::Status {
+        let failure = LoginFailure {
+            reason: reason as i32,
+        };
+        tonic::Status::with_details(code, "", failure.encode_to_vec().into())
+    }
+
+    #[test]
+    fn from_status_reads_the_reason() {
+        assert_eq!(
+            AuthError::from_status(&status(
+                tonic::Code::AlreadyExists,
+                LoginError::AccountExists
+            )),
+            AuthError::AccountExists
+        );
+        assert_eq!(
+            AuthError::from_status(&status(
+                tonic::Code::PermissionDenied,
+                LoginError::AccountDisabled
+            )),
+            AuthError::AccountDisabled
+        );
+        assert_eq!(
+            AuthError::from_status(&status(
+                tonic::Code::Unauthenticated,
+                LoginError::InvalidLinkCode
+            )),
+            AuthError::InvalidLinkCode
+        );
+    }
+
+    #[test]
+    fn from_status_without_a_reason_is_an_outage() {
+        assert_eq!(
+            AuthError::from_status(&tonic::Status::unauthenticated("Invalid token")),
+            AuthError::Internal
+        );
+        assert_eq!(
+            AuthError::from_status(&tonic::Status::already_exists(
+                "Passkey is already registered"
+            )),
+            AuthError::Internal
+        );
+        assert_eq!(
+            AuthError::from_status(&tonic::Status::unavailable("Connection refused")),
+            AuthError::UsersUnavailable
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    auth_audit::ClientInfo,
+    auth_error::{AuthError, AuthFailure},
+    auth_oauth::OAuthUser,
+    proto::utils_service_client::UtilsServiceClient,
+    AppState,
+};
+use anyhow::Result;
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form, Json,
+};
+use oauth2::CsrfToken;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+// Links requested for one address in the last 15 minutes
+const MAX_RECENT_LINKS: i32 = 3;
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[derive(serde::Deserialize)]
+pub struct MagicLinkRequest {
+    email: String,
+}
+
+/**
+ * Send a sign in link to the email address.
+ * The users are not looked up here, so the response is the same whether the account exists or not.
+ */
+pub async fn magic_link(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<MagicLinkRequest>,
+) -> StatusCode {
+    let email = body.email.trim().to_lowercase();
+    if email.len() < 3 || email.len() > 320 || !email.contains('@') {
+        return StatusCode::BAD_REQUEST;
+    }
+
+    let conn = match state.pool.get().await {
+        Ok(conn) => conn,
+        Err(err) => {
+            tracing::error!("Failed to get DB connection: {:?}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    };
+
+    let token = CsrfToken::new_random();
+    let link = match crate::auth_db::create_magic_link(
+        &conn,
+        &email,
+        &hash_token(token.secret()),
+        MAX_RECENT_LINKS,
+    )
+    .await
+    {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            tracing::warn!("Too many magic links requested");
+            return StatusCode::TOO_MANY_REQUESTS;
+        }
+        Err(err) => {
+            tracing::error!("Failed to save magic link: {:?}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    };
+
+    // Delete old magic links asynchronously. If this fails, it's not a big deal.
+    tokio::spawn(async move {
+        if let Err(err) = crate::auth_db::delete_old_magic_links(&conn).await {
+            tracing::error!("Failed to delete old magic links: {:?}", err);
+        }
+    });
+
+    if let Err(err) = send_magic_link(&state, &link.id.to_string(), &email, token.secret()).await {
+        tracing::error!("Failed to send magic link: {:?}", err);
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    }
+
+    tracing::info!("Magic link sent");
+    StatusCode::ACCEPTED
+}
+
+async fn send_magic_link(state: &AppState, link_id: &str, email: &str, token: &str) -> Result<()> {
+    let url = format!("{}/magic-link/callback?token={}", state.env.auth_url, token);
+    let mut client = UtilsServiceClient::connect(state.env.utils_url.to_owned()).await?;
+    let mut request = tonic::Request::new(crate::proto::Email {
+        target_id: link_id.to_string(),
+        email_to: email.to_string(),
+        email_from: state.env.email_from.to_owned(),
+        email_from_name: "Rusve".to_string(),
+        email_subject: "Sign in to Rusve".to_string(),
+        email_body: format!(
+            r#"
+            <p>Click the link below to sign in, it's valid for 15 minutes and can only be used once.</p>
+            <p><a href="{url}">Sign in</a></p>
+            <p>If You didn't request it, You can ignore this email.</p>
+            "#
+        ),
+        ..Default::default()
+    });
+    // Emails are stored by target, here it's the magic link itself
+    let jwt_token = service_auth::generate_service_token(&state.env.jwt_keys, "service-utils")?;
+    request.metadata_mut().insert("x-authorization", jwt_token);
+    client.send_email(request).await?;
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+pub struct MagicLinkToken {
+    token: String,
+}
+
+/**
+ * The link from the email only shows a button that posts the token. Mail scanners open the links
+ * of an email before the user does, and would use up the token if a GET signed in.
+ */
+pub async fn magic_link_confirm(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, AuthFailure> {
+    // Tokens are url safe base64, anything else can't be one and isn't put in the page
+    let token = query
+        .get("token")
+        .filter(|token| {
+            !token.is_empty()
+                && token
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+        })
+        .ok_or_else(|| {
+            AuthError::InvalidMagicLink.redirect(&state.env.client_url, "Invalid token")
+        })?;
+    Ok((
+        [
+            (header::CACHE_CONTROL, "no-store"),
+            (header::REFERRER_POLICY, "no-referrer"),
+            (header::X_FRAME_OPTIONS, "DENY"),
+        ],
+        Html(format!(
+            r#"<!doctype html>
+            <meta name="viewport" content="width=device-width, initial-scale=1" />
+            <title>Sign in to Rusve</title>
+            <h1>Sign in to Rusve</h1>
+            <form method="post"><input type="hidden" name="token" value="{token}" /><button>Sign in</button></form>"#
+        )),
+    )
+        .into_response())
+}
+
+/**
+ * Posted by the page of `magic_link_confirm`. The address is verified by the click, so the user
+ * goes through the same `CreateUser` path as the OAuth providers, with `email` as the provider.
+ */
+pub async fn magic_link_callback(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<MagicLinkToken>,
+) -> Result<Redirect, AuthFailure> {
+    let client = ClientInfo::new(&state, &headers, addr);
+    let result = finish_magic_link(&state, &client, &form.token).await;
+    if let Err(failure) = &result {
+        crate::auth_audit::spawn_login_failure(state.clone(), "email".to_string(), client, failure);
+    }
+    result
+}
+
+async fn finish_magic_link(
+    state: &AppState,
+    client: &ClientInfo,
+    token: &str,
+) -> Result<Redirect, AuthFailure> {
+    let conn = state.pool.get().await.map_err(|err| {
+        AuthError::Internal.redirect(
+            &state.env.client_url,
+            format!("Failed to get DB connection: {:?}", err),
+        )
+    })?;
+
+    let link = match crate::auth_db::use_magic_link(&conn, &hash_token(token)).await {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            return Err(AuthError::InvalidMagicLink
+                .redirect(&state.env.client_url, "Invalid or expired magic link"));
+        }
+        Err(err) => {
+            return Err(AuthError::Internal.redirect(
+                &state.env.client_url,
+                format!("Failed to use magic link: {:?}", err),
+            ));
+        }
+    };
+
+    let user = OAuthUser {
+        provider: "email".to_string(),
+        sub: link.email.to_owned(),
+        email: link.email,
+        email_verified: true,
+        avatar: String::new(),
+        link_code: String::new(),
+        // 5 min
+        exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
+    };
+    let jwt_token = state
+        .env
+        .jwt_keys
+        .encode(&user, &service_auth::OAUTH_TOKEN)
+        .and_then(|token| Ok(format!("bearer {}", token).parse()?))
+        .map_err(|err| {
+            AuthError::Internal.redirect(
+                &state.env.client_url,
+                format!("Failed to generate JWT: {:?}", err),
+            )
+        })?;
+    crate::auth_service::create_session(state, client, jwt_token, "").await
+}
@@ -1,32 +1,70 @@
-use anyhow::Result;
-use oauth2::{basic::BasicClient, AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl};
-use service_auth::Env;
+use crate::auth_error::AuthError;
+use crate::auth_oidc::OidcCache;
+use crate::AppState;
+use anyhow::{Context, Result};
+use oauth2::basic::{
+    BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
+    BasicTokenType,
+};
+use oauth2::{
+    AuthUrl, ClientId, ClientSecret, ExtraTokenFields, RedirectUrl, StandardRevocableToken,
+    StandardTokenResponse, TokenResponse, TokenUrl,
+};
 use serde::{Deserialize, Serialize};
+use service_auth::JwtKeys;
 use tonic::metadata::{Ascii, MetadataValue};
 
 pub enum OAuthProvider {
-    Google,
+    // Google, and any provider configured through OIDC_PROVIDERS
+    Oidc,
     Github,
+    // The fake provider of `auth_dev`
+    Dev,
 }
 
+/**
+ * BasicClient drops everything except the access token, OIDC providers also return an id_token.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+impl ExtraTokenFields for IdTokenFields {}
+
+pub type OAuthTokenResponse = StandardTokenResponse<IdTokenFields, BasicTokenType>;
+pub type OAuthClient = oauth2::Client<
+    BasicErrorResponse,
+    OAuthTokenResponse,
+    BasicTokenType,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+>;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OAuthUser {
+    pub provider: String,
     pub sub: String,
     pub email: String,
+    pub email_verified: bool,
     pub avatar: String,
+    // Link the identity to the account that created this code, instead of signing in
+    pub link_code: String,
     pub exp: i64,
 }
 
 pub trait OAuth {
-    fn get_config_by_provider(provider: &str, env: Env) -> Result<Self>
+    async fn get_config_by_provider(provider: &str, state: &AppState) -> Result<Self>
     where
         Self: Sized;
-    fn build_oauth_client(&self) -> BasicClient;
-    async fn get_user_info(&self, token: &str) -> Result<OAuthUser>;
+    fn build_oauth_client(&self) -> Result<OAuthClient>;
+    async fn get_user_info(&self, token: &OAuthTokenResponse, nonce: &str) -> Result<OAuthUser>;
     fn generate_jwt(&self, user: OAuthUser) -> Result<MetadataValue<Ascii>>;
 }
 
 pub struct OAuthConfig {
+    name: String,
     provider: OAuthProvider,
     client_id: String,
     client_secret: String,
@@ -34,40 +72,69 @@ pub struct OAuthConfig {
     token_url: String,
     redirect_url: String,
     pub scopes: Vec<String>,
+    // OIDC providers only call it when the id_token has no email
     user_info_url: String,
-    jwt_secret: String,
+    // Only used by OIDC providers
+    issuers: Vec<String>,
+    jwks_url: String,
+    require_email_verified: bool,
+    jwt_keys: JwtKeys,
+    oidc_cache: OidcCache,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
-struct GoogleUser {
-    sub: String,
-    email: String,
-    email_verified: bool,
-    picture: String,
+struct GithubUser {
+    id: i64,
+    avatar_url: String,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
-struct GithubUser {
-    id: i64,
+struct GithubEmail {
     email: String,
-    avatar_url: String,
+    primary: bool,
+    verified: bool,
+}
+
+/**
+ * The primary email, only if it's verified. Another verified email isn't used, and neither is the
+ * `email` of `/user`, which is null for private addresses and never says if it's verified.
+ */
+fn github_email(emails: Vec<GithubEmail>) -> Result<String> {
+    let email = emails
+        .into_iter()
+        .find(|email| email.primary && email.verified)
+        .ok_or(AuthError::EmailNotVerified)
+        .context("User has no primary verified email")?;
+    Ok(email.email)
 }
 
 impl OAuth for OAuthConfig {
-    fn get_config_by_provider(provider: &str, env: Env) -> Result<Self> {
+    async fn get_config_by_provider(provider: &str, state: &AppState) -> Result<Self> {
+        let env = state.env.clone();
+        let oidc_cache = state.oidc.clone();
         match provider {
             "google" => Ok(Self {
-                provider: OAuthProvider::Google,
+                name: provider.to_string(),
+                provider: OAuthProvider::Oidc,
                 client_id: env.google_client_id,
                 client_secret: env.google_client_secret,
                 auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
                 token_url: "https://www.googleapis.com/oauth2/v3/token".to_string(),
                 redirect_url: format!("{}/oauth-callback/google", env.auth_url),
-                user_info_url: "https://www.googleapis.com/oauth2/v3/userinfo".to_string(),
+                user_info_url: "https://openidconnect.googleapis.com/v1/userinfo".to_string(),
                 scopes: vec!["email".to_string(), "openid".to_string()],
-                jwt_secret: env.jwt_secret,
+                // Google uses both forms of the issuer
+                issuers: vec![
+                    "https://accounts.google.com".to_string(),
+                    "accounts.google.com".to_string(),
+                ],
+                jwks_url: "https://www.googleapis.com/oauth2/v3/certs".to_string(),
+                require_email_verified: true,
+                jwt_keys: env.jwt_keys,
+                oidc_cache,
             }),
             "github" => Ok(Self {
+                name: provider.to_string(),
                 provider: OAuthProvider::Github,
                 client_id: env.github_client_id,
                 client_secret: env.github_client_secret,
@@ -76,64 +143,185 @@ impl OAuth for OAuthConfig {
                 redirect_url: format!("{}/oauth-callback/github", env.auth_url),
                 user_info_url: "https://api.github.com/user".to_string(),
                 scopes: vec!["user:email".to_string()],
-                jwt_secret: env.jwt_secret,
+                issuers: Vec::new(),
+                jwks_url: String::new(),
+                require_email_verified: false,
+                jwt_keys: env.jwt_keys,
+                oidc_cache,
             }),
-            _ => Err(anyhow::anyhow!(format!(
-                "Invalid OAuth provider: {}",
-                provider
-            ))),
+            "dev" if !env.dev_oauth_users.is_empty() => Ok(Self {
+                name: provider.to_string(),
+                provider: OAuthProvider::Dev,
+                client_id: "dev".to_string(),
+                client_secret: "dev".to_string(),
+                auth_url: format!("{}/dev-oauth/authorize", env.auth_url),
+                // Called by this service, so the AUTH_URL of the browser may not be reachable
+                token_url: format!("http://localhost:{}/dev-oauth/token", env.port),
+                redirect_url: format!("{}/oauth-callback/dev", env.auth_url),
+                user_info_url: format!("http://localhost:{}/dev-oauth/userinfo", env.port),
+                scopes: Vec::new(),
+                issuers: Vec::new(),
+                jwks_url: String::new(),
+                require_email_verified: false,
+                jwt_keys: env.jwt_keys,
+                oidc_cache,
+            }),
+            _ => {
+                let oidc = env
+                    .oidc_providers
+                    .into_iter()
+                    .find(|p| p.name == provider)
+                    .ok_or(AuthError::UnknownProvider)
+                    .with_context(|| format!("Invalid OAuth provider: {}", provider))?;
+                let metadata = oidc_cache.discover(&oidc.issuer).await?;
+                Ok(Self {
+                    name: oidc.name.to_owned(),
+                    provider: OAuthProvider::Oidc,
+                    client_id: oidc.client_id,
+                    client_secret: oidc.client_secret,
+                    auth_url: metadata.authorization_endpoint,
+                    token_url: metadata.token_endpoint,
+                    redirect_url: format!("{}/oauth-callback/{}", env.auth_url, oidc.name),
+                    user_info_url: metadata.userinfo_endpoint.unwrap_or_default(),
+                    scopes: oidc.scopes,
+                    issuers: vec![metadata.issuer],
+                    jwks_url: metadata.jwks_uri,
+                    require_email_verified: oidc.require_email_verified,
+                    jwt_keys: env.jwt_keys,
+                    oidc_cache,
+                })
+            }
         }
     }
-    fn build_oauth_client(&self) -> BasicClient {
-        let auth_url =
-            AuthUrl::new(self.auth_url.to_owned()).expect("Invalid authorization endpoint URL");
-        let token_url =
-            TokenUrl::new(self.token_url.to_owned()).expect("Invalid token endpoint URL");
-        let redirect_url =
-            RedirectUrl::new(self.redirect_url.to_owned()).expect("Invalid redirect URL");
-        BasicClient::new(
+    /**
+     * The endpoints of an OIDC provider come from its discovery document, a malformed one fails
+     * the login instead of the request.
+     */
+    fn build_oauth_client(&self) -> Result<OAuthClient> {
+        let auth_url = AuthUrl::new(self.auth_url.to_owned()).map_err(|err| {
+            anyhow::Error::new(AuthError::ProviderError).context(format!(
+                "Invalid authorization endpoint URL {}: {}",
+                self.auth_url, err
+            ))
+        })?;
+        let token_url = TokenUrl::new(self.token_url.to_owned()).map_err(|err| {
+            anyhow::Error::new(AuthError::ProviderError).context(format!(
+                "Invalid token endpoint URL {}: {}",
+                self.token_url, err
+            ))
+        })?;
+        // Built from AUTH_URL, so it's our own configuration that is wrong
+        let redirect_url = RedirectUrl::new(self.redirect_url.to_owned()).map_err(|err| {
+            anyhow::Error::new(AuthError::Internal).context(format!(
+                "Invalid redirect URL {}: {}",
+                self.redirect_url, err
+            ))
+        })?;
+        Ok(OAuthClient::new(
             ClientId::new(self.client_id.to_owned()),
             Some(ClientSecret::new(self.client_secret.to_owned())),
             auth_url,
             Some(token_url),
         )
-        .set_redirect_uri(redirect_url)
+        .set_redirect_uri(redirect_url))
     }
 
-    async fn get_user_info(&self, token: &str) -> Result<OAuthUser> {
+    async fn get_user_info(&self, token: &OAuthTokenResponse, nonce: &str) -> Result<OAuthUser> {
+        let access_token = token.access_token().secret();
         match self.provider {
-            OAuthProvider::Google => {
+            OAuthProvider::Github => {
                 let user_profile = reqwest::Client::new()
                     .get(&self.user_info_url)
-                    .header(reqwest::header::AUTHORIZATION, format!("Bearer {}", token))
+                    .header(
+                        reqwest::header::AUTHORIZATION,
+                        format!("Bearer {}", access_token),
+                    )
+                    .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
+                    .header(reqwest::header::USER_AGENT, "Rusve")
                     .send()
                     .await?;
+                let user_profile = user_profile.json::<GithubUser>().await?;
+
+                // `/user` returns null email for private addresses, so ask for the list instead.
+                let emails = reqwest::Client::new()
+                    .get(format!("{}/emails", self.user_info_url))
+                    .header(
+                        reqwest::header::AUTHORIZATION,
+                        format!("Bearer {}", access_token),
+                    )
+                    .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
+                    .header(reqwest::header::USER_AGENT, "Rusve")
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<Vec<GithubEmail>>()
+                    .await?;
+                let email = github_email(emails)?;
 
-                let user_profile = user_profile.json::<GoogleUser>().await?;
-                if !user_profile.email_verified {
-                    return Err(anyhow::anyhow!("User email is not verified"));
-                }
                 Ok(OAuthUser {
-                    sub: user_profile.sub,
-                    email: user_profile.email,
-                    avatar: user_profile.picture,
+                    provider: self.name.to_owned(),
+                    sub: user_profile.id.to_string(),
+                    email,
+                    // Only the primary verified email is used
+                    email_verified: true,
+                    avatar: user_profile.avatar_url,
+                    link_code: String::new(),
                     // 5 min
                     exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
                 })
             }
-            OAuthProvider::Github => {
-                let user_profile = reqwest::Client::new()
+            OAuthProvider::Dev => {
+                let user = reqwest::Client::new()
                     .get(&self.user_info_url)
-                    .header(reqwest::header::AUTHORIZATION, format!("Bearer {}", token))
-                    .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
-                    .header(reqwest::header::USER_AGENT, "Rusve")
+                    .bearer_auth(access_token)
                     .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<crate::auth_dev::DevUser>()
                     .await?;
-                let user_profile = user_profile.json::<GithubUser>().await?;
                 Ok(OAuthUser {
-                    sub: user_profile.id.to_string(),
-                    email: user_profile.email,
-                    avatar: user_profile.avatar_url,
+                    provider: self.name.to_owned(),
+                    sub: user.sub,
+                    email: user.email,
+                    email_verified: user.email_verified,
+                    avatar: String::new(),
+                    link_code: String::new(),
+                    // 5 min
+                    exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
+                })
+            }
+            OAuthProvider::Oidc => {
+                let id_token = token
+                    .extra_fields()
+                    .id_token
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("Missing id_token"))?;
+                let kid = jsonwebtoken::decode_header(id_token)?.kid;
+                let jwks = self.oidc_cache.jwks(&self.jwks_url, kid.as_deref()).await?;
+                let claims = crate::auth_oidc::verify_id_token(
+                    id_token,
+                    &jwks,
+                    &self.issuers,
+                    &self.client_id,
+                    nonce,
+                )?;
+                let claims =
+                    crate::auth_oidc::with_userinfo(claims, &self.user_info_url, access_token)
+                        .await?;
+
+                let email = claims
+                    .email
+                    .ok_or_else(|| anyhow::anyhow!("Missing email claim"))?;
+                if self.require_email_verified && claims.email_verified != Some(true) {
+                    return Err(AuthError::EmailNotVerified).context("User email is not verified");
+                }
+                Ok(OAuthUser {
+                    provider: self.name.to_owned(),
+                    sub: claims.sub,
+                    email,
+                    email_verified: claims.email_verified == Some(true),
+                    avatar: claims.picture.unwrap_or_default(),
+                    link_code: String::new(),
                     // 5 min
                     exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
                 })
@@ -142,11 +330,70 @@ impl OAuth for OAuthConfig {
     }
 
     fn generate_jwt(&self, user: OAuthUser) -> Result<MetadataValue<Ascii>> {
-        let token = jsonwebtoken::encode(
-            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
-            &user,
-            &jsonwebtoken::EncodingKey::from_secret(self.jwt_secret.as_bytes()),
-        )?;
+        let token = self.jwt_keys.encode(&user, &service_auth::OAUTH_TOKEN)?;
         Ok(format!("bearer {}", token).parse()?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emails(json: &str) -> Vec<GithubEmail> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn is_not_verified(err: anyhow::Error) -> bool {
+        err.downcast_ref::<AuthError>() == Some(&AuthError::EmailNotVerified)
+    }
+
+    #[test]
+    fn primary_verified_email_is_used() {
+        let emails = emails(
+            r#"[
+                {"email": "other@example.com", "primary": false, "verified": true},
+                {"email": "user@example.com", "primary": true, "verified": true}
+            ]"#,
+        );
+        assert_eq!(github_email(emails).unwrap(), "user@example.com");
+    }
+
+    #[test]
+    fn unverified_primary_email_is_rejected() {
+        let emails = emails(
+            r#"[
+                {"email": "user@example.com", "primary": true, "verified": false},
+                {"email": "other@example.com", "primary": false, "verified": true}
+            ]"#,
+        );
+        assert!(is_not_verified(github_email(emails).unwrap_err()));
+    }
+
+    #[test]
+    fn verified_email_that_isnt_primary_is_rejected() {
+        let emails =
+            emails(r#"[{"email": "other@example.com", "primary": false, "verified": true}]"#);
+        assert!(is_not_verified(github_email(emails).unwrap_err()));
+    }
+
+    #[test]
+    fn no_emails_is_rejected() {
+        assert!(is_not_verified(github_email(Vec::new()).unwrap_err()));
+    }
+
+    #[test]
+    fn private_email_of_user_is_read_from_the_list() {
+        let user: GithubUser = serde_json::from_str(
+            r#"{"id": 1, "avatar_url": "https://avatars.githubusercontent.com/u/1", "email": null}"#,
+        )
+        .unwrap();
+        assert_eq!(user.id, 1);
+        let emails = emails(
+            r#"[{"email": "1+user@users.noreply.github.com", "primary": true, "verified": true}]"#,
+        );
+        assert_eq!(
+            github_email(emails).unwrap(),
+            "1+user@users.noreply.github.com"
+        );
+    }
+}
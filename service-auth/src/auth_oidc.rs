@@ -0,0 +1,557 @@
+use anyhow::{Context, Result};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+// Providers rotate their keys slowly and publish the new ones ahead of time
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+// An unknown kid refetches the JWKS, but not more often than this
+const JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/**
+ * Subset of the OpenID Provider Metadata we need to run the authorization code flow.
+ * https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    pub userinfo_endpoint: Option<String>,
+}
+
+/**
+ * Standard id_token claims we map to `OAuthUser`.
+ */
+#[derive(Debug, Deserialize)]
+pub struct OidcClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub picture: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/**
+ * Claims of the userinfo endpoint, for providers that leave the email out of the id_token.
+ * https://openid.net/specs/openid-connect-core-1_0.html#UserInfoResponse
+ */
+#[derive(Debug, Deserialize)]
+struct OidcUserInfo {
+    sub: String,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    picture: Option<String>,
+}
+
+/**
+ * Discovery documents by issuer and key sets by url, shared by every login.
+ * Without it each login would fetch both before it could redirect or verify the id_token.
+ */
+#[derive(Clone)]
+pub struct OidcCache {
+    ttl: Duration,
+    discovery: Arc<RwLock<HashMap<String, (Instant, OidcMetadata)>>>,
+    jwks: Arc<RwLock<HashMap<String, CachedJwks>>>,
+}
+
+struct CachedJwks {
+    fetched: Instant,
+    // Fetched again because a token had a kid missing from the previous set
+    refetched: bool,
+    jwks: JwkSet,
+}
+
+impl Default for OidcCache {
+    fn default() -> Self {
+        Self::new(CACHE_TTL)
+    }
+}
+
+impl OidcCache {
+    pub fn new(ttl: Duration) -> Self {
+        OidcCache {
+            ttl,
+            discovery: Arc::default(),
+            jwks: Arc::default(),
+        }
+    }
+
+    pub async fn discover(&self, issuer: &str) -> Result<OidcMetadata> {
+        if let Some((fetched, metadata)) = self
+            .discovery
+            .read()
+            .expect("OIDC cache lock poisoned")
+            .get(issuer)
+        {
+            if fetched.elapsed() < self.ttl {
+                return Ok(metadata.clone());
+            }
+        }
+        let metadata = discover(issuer).await?;
+        self.discovery
+            .write()
+            .expect("OIDC cache lock poisoned")
+            .insert(issuer.to_string(), (Instant::now(), metadata.clone()));
+        Ok(metadata)
+    }
+
+    /**
+     * The key set to verify a token signed with `kid`. A kid missing from the cached set may be
+     * a new key of the provider, so the set is fetched again, at most once a minute.
+     */
+    pub async fn jwks(&self, jwks_uri: &str, kid: Option<&str>) -> Result<JwkSet> {
+        let mut refetch = false;
+        if let Some(cached) = self
+            .jwks
+            .read()
+            .expect("OIDC cache lock poisoned")
+            .get(jwks_uri)
+        {
+            if cached.fetched.elapsed() < self.ttl {
+                let known = kid.is_none_or(|kid| cached.jwks.find(kid).is_some());
+                if known || (cached.refetched && cached.fetched.elapsed() < JWKS_REFETCH_INTERVAL) {
+                    return Ok(cached.jwks.clone());
+                }
+                refetch = true;
+            }
+        }
+        let jwks = fetch_jwks(jwks_uri).await?;
+        self.jwks.write().expect("OIDC cache lock poisoned").insert(
+            jwks_uri.to_string(),
+            CachedJwks {
+                fetched: Instant::now(),
+                refetched: refetch,
+                jwks: jwks.clone(),
+            },
+        );
+        Ok(jwks)
+    }
+}
+
+async fn discover(issuer: &str) -> Result<OidcMetadata> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    );
+    let metadata = reqwest::Client::new()
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<OidcMetadata>()
+        .await
+        .context("Invalid OpenID configuration")?;
+
+    // The issuer in the document must be exactly the one we were configured with.
+    if metadata.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+        return Err(anyhow::anyhow!(
+            "Issuer mismatch: expected {}, got {}",
+            issuer,
+            metadata.issuer
+        ));
+    }
+    // Endpoints end up in the oauth2 client, make sure they are valid urls first.
+    for endpoint in [
+        &metadata.authorization_endpoint,
+        &metadata.token_endpoint,
+        &metadata.jwks_uri,
+    ]
+    .into_iter()
+    .chain(&metadata.userinfo_endpoint)
+    {
+        reqwest::Url::parse(endpoint).with_context(|| format!("Invalid endpoint: {}", endpoint))?;
+    }
+    Ok(metadata)
+}
+
+async fn fetch_jwks(jwks_uri: &str) -> Result<JwkSet> {
+    let jwks = reqwest::Client::new()
+        .get(jwks_uri)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<JwkSet>()
+        .await
+        .context("Invalid JWKS")?;
+    Ok(jwks)
+}
+
+/**
+ * Verify the id_token signature against the provider JWKS, together with its issuer, audience,
+ * expiry and the nonce we sent in the authorization request.
+ */
+pub fn verify_id_token(
+    id_token: &str,
+    jwks: &JwkSet,
+    issuers: &[String],
+    client_id: &str,
+    nonce: &str,
+) -> Result<OidcClaims> {
+    let header = jsonwebtoken::decode_header(id_token)?;
+
+    // Only asymmetric algorithms, the client secret is never used to sign id_tokens here.
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(anyhow::anyhow!("Unsupported id_token algorithm"));
+    }
+
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| anyhow::anyhow!("No matching key for id_token"))?;
+    let key = DecodingKey::from_jwk(jwk)?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(issuers);
+    validation.set_audience(&[client_id]);
+    let token = jsonwebtoken::decode::<OidcClaims>(id_token, &key, &validation)?;
+
+    if nonce.is_empty() || token.claims.nonce.as_deref() != Some(nonce) {
+        return Err(anyhow::anyhow!("Invalid id_token nonce"));
+    }
+    Ok(token.claims)
+}
+
+/**
+ * Some providers only put `email` and `email_verified` in the id_token for some scopes, or not at
+ * all. Ask the userinfo endpoint for both then, the `sub` it answers with must be the one of the
+ * verified id_token.
+ */
+pub async fn with_userinfo(
+    claims: OidcClaims,
+    userinfo_endpoint: &str,
+    access_token: &str,
+) -> Result<OidcClaims> {
+    if (claims.email.is_some() && claims.email_verified.is_some()) || userinfo_endpoint.is_empty() {
+        return Ok(claims);
+    }
+    let userinfo = reqwest::Client::new()
+        .get(userinfo_endpoint)
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<OidcUserInfo>()
+        .await
+        .context("Invalid userinfo response")?;
+    if userinfo.sub != claims.sub {
+        return Err(anyhow::anyhow!(
+            "Userinfo sub doesn't match the id_token: expected {}, got {}",
+            claims.sub,
+            userinfo.sub
+        ));
+    }
+    // Both from the same source, so `email_verified` is about this `email`
+    Ok(OidcClaims {
+        email: userinfo.email,
+        email_verified: userinfo.email_verified,
+        picture: claims.picture.or(userinfo.picture),
+        ..claims
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::get, Json, Router};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use jsonwebtoken::{EncodingKey, Header};
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    const CLIENT_ID: &str = "rusve";
+    const NONCE: &str = "nonce";
+
+    struct SigningKey {
+        kid: String,
+        pkcs8: Vec<u8>,
+    }
+
+    impl SigningKey {
+        fn new(kid: &str) -> Self {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+            SigningKey {
+                kid: kid.to_string(),
+                pkcs8: pkcs8.as_ref().to_vec(),
+            }
+        }
+
+        fn jwk(&self) -> Value {
+            let key = Ed25519KeyPair::from_pkcs8(&self.pkcs8).unwrap();
+            json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(key.public_key().as_ref()),
+                "kid": self.kid,
+                "alg": "EdDSA",
+                "use": "sig",
+            })
+        }
+
+        fn sign(&self, claims: &Value) -> String {
+            let mut header = Header::new(Algorithm::EdDSA);
+            header.kid = Some(self.kid.to_owned());
+            jsonwebtoken::encode(&header, claims, &EncodingKey::from_ed_der(&self.pkcs8)).unwrap()
+        }
+    }
+
+    /**
+     * An OpenID provider with only the discovery document, the key set and userinfo.
+     */
+    struct Issuer {
+        url: String,
+        // Sent as the issuer of the discovery document
+        issuer: Mutex<String>,
+        keys: Mutex<Vec<Value>>,
+        userinfo: Mutex<Value>,
+        discovery_hits: AtomicUsize,
+        jwks_hits: AtomicUsize,
+        userinfo_hits: AtomicUsize,
+    }
+
+    async fn configuration(State(issuer): State<Arc<Issuer>>) -> Json<Value> {
+        issuer.discovery_hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({
+            "issuer": issuer.issuer.lock().unwrap().clone(),
+            "authorization_endpoint": format!("{}/authorize", issuer.url),
+            "token_endpoint": format!("{}/token", issuer.url),
+            "jwks_uri": format!("{}/jwks", issuer.url),
+            "userinfo_endpoint": format!("{}/userinfo", issuer.url),
+        }))
+    }
+
+    async fn jwks(State(issuer): State<Arc<Issuer>>) -> Json<Value> {
+        issuer.jwks_hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({ "keys": issuer.keys.lock().unwrap().clone() }))
+    }
+
+    async fn userinfo(State(issuer): State<Arc<Issuer>>) -> Json<Value> {
+        issuer.userinfo_hits.fetch_add(1, Ordering::SeqCst);
+        Json(issuer.userinfo.lock().unwrap().clone())
+    }
+
+    async fn mock_issuer(keys: &[&SigningKey]) -> Arc<Issuer> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let issuer = Arc::new(Issuer {
+            issuer: Mutex::new(url.to_owned()),
+            url,
+            keys: Mutex::new(keys.iter().map(|key| key.jwk()).collect()),
+            userinfo: Mutex::new(json!({
+                "sub": "248289761001",
+                "email": "jane.doe@example.com",
+                "email_verified": true,
+            })),
+            discovery_hits: AtomicUsize::new(0),
+            jwks_hits: AtomicUsize::new(0),
+            userinfo_hits: AtomicUsize::new(0),
+        });
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(configuration))
+            .route("/jwks", get(jwks))
+            .route("/userinfo", get(userinfo))
+            .with_state(issuer.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        issuer
+    }
+
+    fn claims(issuer: &str) -> Value {
+        json!({
+            "iss": issuer,
+            "aud": CLIENT_ID,
+            "exp": time::OffsetDateTime::now_utc().unix_timestamp() + 60,
+            "sub": "248289761001",
+            "email": "jane@example.com",
+            "email_verified": true,
+            "nonce": NONCE,
+        })
+    }
+
+    async fn verify(cache: &OidcCache, issuer: &Issuer, id_token: &str) -> Result<OidcClaims> {
+        let metadata = cache.discover(&issuer.url).await?;
+        let kid = jsonwebtoken::decode_header(id_token)?.kid;
+        let jwks = cache.jwks(&metadata.jwks_uri, kid.as_deref()).await?;
+        verify_id_token(id_token, &jwks, &[metadata.issuer], CLIENT_ID, NONCE)
+    }
+
+    #[tokio::test]
+    async fn discovery_is_cached() {
+        let issuer = mock_issuer(&[]).await;
+        let cache = OidcCache::default();
+        let metadata = cache.discover(&issuer.url).await.unwrap();
+        assert_eq!(metadata.jwks_uri, format!("{}/jwks", issuer.url));
+        cache.discover(&issuer.url).await.unwrap();
+        assert_eq!(issuer.discovery_hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn discovery_is_fetched_again_after_the_ttl() {
+        let issuer = mock_issuer(&[]).await;
+        let cache = OidcCache::new(Duration::ZERO);
+        cache.discover(&issuer.url).await.unwrap();
+        cache.discover(&issuer.url).await.unwrap();
+        assert_eq!(issuer.discovery_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn issuer_mismatch_is_rejected() {
+        let issuer = mock_issuer(&[]).await;
+        *issuer.issuer.lock().unwrap() = "https://evil.example".to_string();
+        let err = OidcCache::default().discover(&issuer.url).await;
+        assert!(err
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Issuer mismatch"));
+    }
+
+    #[tokio::test]
+    async fn id_token_is_verified_with_the_cached_jwks() {
+        let key = SigningKey::new("2024-01");
+        let issuer = mock_issuer(&[&key]).await;
+        let cache = OidcCache::default();
+        for _ in 0..2 {
+            let claims = verify(&cache, &issuer, &key.sign(&claims(&issuer.url)))
+                .await
+                .unwrap();
+            assert_eq!(claims.sub, "248289761001");
+            assert_eq!(claims.email.as_deref(), Some("jane@example.com"));
+            assert_eq!(claims.email_verified, Some(true));
+        }
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_kid_fetches_the_jwks_again() {
+        let old_key = SigningKey::new("2024-01");
+        let issuer = mock_issuer(&[&old_key]).await;
+        let cache = OidcCache::default();
+        verify(&cache, &issuer, &old_key.sign(&claims(&issuer.url)))
+            .await
+            .unwrap();
+
+        // The provider rotates its key
+        let new_key = SigningKey::new("2024-06");
+        *issuer.keys.lock().unwrap() = vec![new_key.jwk()];
+        verify(&cache, &issuer, &new_key.sign(&claims(&issuer.url)))
+            .await
+            .unwrap();
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 2);
+
+        // Another unknown kid right after doesn't fetch it again
+        let unknown_key = SigningKey::new("unknown");
+        let err = verify(&cache, &issuer, &unknown_key.sign(&claims(&issuer.url))).await;
+        assert_eq!(
+            err.err().unwrap().to_string(),
+            "No matching key for id_token"
+        );
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn invalid_id_tokens_are_rejected() {
+        let key = SigningKey::new("2024-01");
+        let issuer = mock_issuer(&[&key]).await;
+        let cache = OidcCache::default();
+        let check = |change: fn(&mut Value)| {
+            let mut claims = claims(&issuer.url);
+            change(&mut claims);
+            key.sign(&claims)
+        };
+
+        let wrong_nonce = check(|claims| claims["nonce"] = json!("other"));
+        let wrong_audience = check(|claims| claims["aud"] = json!("other"));
+        let wrong_issuer = check(|claims| claims["iss"] = json!("https://evil.example"));
+        let expired = check(|claims| claims["exp"] = json!(1));
+        let no_nonce = check(|claims| {
+            claims.as_object_mut().unwrap().remove("nonce");
+        });
+        for id_token in [wrong_nonce, wrong_audience, wrong_issuer, expired, no_nonce] {
+            assert!(verify(&cache, &issuer, &id_token).await.is_err());
+        }
+
+        // Signed with a shared secret, e.g. the client secret
+        let hs256 = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims(&issuer.url),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        let err = verify(&cache, &issuer, &hs256).await;
+        assert_eq!(
+            err.err().unwrap().to_string(),
+            "Unsupported id_token algorithm"
+        );
+    }
+
+    async fn verify_with_userinfo(
+        cache: &OidcCache,
+        issuer: &Issuer,
+        id_token: &str,
+    ) -> Result<OidcClaims> {
+        let claims = verify(cache, issuer, id_token).await?;
+        let metadata = cache.discover(&issuer.url).await?;
+        with_userinfo(claims, &metadata.userinfo_endpoint.unwrap(), "access_token").await
+    }
+
+    #[tokio::test]
+    async fn id_token_email_is_used_without_userinfo() {
+        let key = SigningKey::new("2024-01");
+        let issuer = mock_issuer(&[&key]).await;
+        let cache = OidcCache::default();
+        let claims = verify_with_userinfo(&cache, &issuer, &key.sign(&claims(&issuer.url)))
+            .await
+            .unwrap();
+        assert_eq!(claims.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(issuer.userinfo_hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn missing_email_falls_back_to_userinfo() {
+        let key = SigningKey::new("2024-01");
+        let issuer = mock_issuer(&[&key]).await;
+        let cache = OidcCache::default();
+        for claim in ["email", "email_verified"] {
+            let mut claims = claims(&issuer.url);
+            claims.as_object_mut().unwrap().remove(claim);
+            let claims = verify_with_userinfo(&cache, &issuer, &key.sign(&claims))
+                .await
+                .unwrap();
+            assert_eq!(claims.email.as_deref(), Some("jane.doe@example.com"));
+            assert_eq!(claims.email_verified, Some(true));
+        }
+        assert_eq!(issuer.userinfo_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn userinfo_of_another_sub_is_rejected() {
+        let key = SigningKey::new("2024-01");
+        let issuer = mock_issuer(&[&key]).await;
+        *issuer.userinfo.lock().unwrap() = json!({
+            "sub": "other",
+            "email": "jane.doe@example.com",
+            "email_verified": true,
+        });
+        let mut claims = claims(&issuer.url);
+        claims.as_object_mut().unwrap().remove("email");
+        let err = verify_with_userinfo(&OidcCache::default(), &issuer, &key.sign(&claims)).await;
+        assert!(err
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Userinfo sub doesn't match the id_token"));
+    }
+}
@@ -0,0 +1,496 @@
+use crate::{
+    auth_audit::ClientInfo,
+    proto::{users_service_client::UsersServiceClient, LoginError},
+    webauthn, AppState,
+};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use oauth2::CsrfToken;
+use serde_json::json;
+use std::{net::SocketAddr, sync::Arc};
+
+// 5 minutes, the same as the challenge itself
+const TIMEOUT_MS: u64 = 5 * 60 * 1000;
+
+/**
+ * Claims sent to service-users, see `PasskeyClaims` there.
+ */
+#[derive(serde::Serialize)]
+struct PasskeyClaims {
+    credential_id: String,
+    public_key: String,
+    sign_count: i64,
+    user_verified: bool,
+    name: String,
+    link_code: String,
+    exp: i64,
+}
+
+impl PasskeyClaims {
+    fn to_metadata(
+        &self,
+        state: &AppState,
+    ) -> Result<tonic::metadata::MetadataValue<tonic::metadata::Ascii>> {
+        let token = state
+            .env
+            .jwt_keys
+            .encode(self, &service_auth::PASSKEY_TOKEN)?;
+        Ok(format!("bearer {}", token).parse()?)
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct ChallengeResponse {
+    id: String,
+    // Options for `navigator.credentials`, binary fields are base64url
+    #[serde(rename = "publicKey")]
+    public_key: serde_json::Value,
+}
+
+async fn create_challenge(state: &AppState, link_code: &str) -> Result<(String, String)> {
+    let conn = state.pool.get().await?;
+    let challenge = CsrfToken::new_random().secret().to_owned();
+    let id = crate::auth_db::create_passkey_challenge(&conn, &challenge, link_code).await?;
+
+    // Delete old challenges asynchronously. If this fails, it's not a big deal.
+    tokio::spawn(async move {
+        if let Err(err) = crate::auth_db::delete_old_passkey_challenges(&conn).await {
+            tracing::error!("Failed to delete old passkey challenges: {:?}", err);
+        }
+    });
+    Ok((id.to_string(), challenge))
+}
+
+async fn use_challenge(state: &AppState, id: &str) -> Result<crate::auth_db::PasskeyChallenge> {
+    let conn = state.pool.get().await?;
+    let id = uuid::Uuid::parse_str(id)?;
+    crate::auth_db::delete_passkey_challenge(&conn, &id)
+        .await?
+        .context("Passkey challenge expired or already used")
+}
+
+#[derive(serde::Deserialize)]
+pub struct RegisterStartRequest {
+    // Link code from `CreateLinkCode`, proves which user is registering
+    code: String,
+    // Shown by the authenticator, usually the email
+    name: String,
+}
+
+/**
+ * Start the registration of a passkey for a signed in user.
+ */
+pub async fn register_start(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<RegisterStartRequest>,
+) -> Result<Json<ChallengeResponse>, StatusCode> {
+    if body.code.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let (id, challenge) = create_challenge(&state, &body.code).await.map_err(|err| {
+        tracing::error!("Failed to create passkey challenge: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Credentials are looked up by their id, so the user handle doesn't need to be the user id
+    let user_handle = CsrfToken::new_random();
+    let algorithms: Vec<_> = [webauthn::ES256, webauthn::EDDSA, webauthn::RS256]
+        .iter()
+        .map(|alg| json!({ "type": "public-key", "alg": alg }))
+        .collect();
+    let public_key = json!({
+        "challenge": challenge,
+        "rp": { "id": state.env.webauthn_rp_id, "name": "Rusve" },
+        "user": {
+            "id": user_handle.secret(),
+            "name": body.name,
+            "displayName": body.name,
+        },
+        "pubKeyCredParams": algorithms,
+        "timeout": TIMEOUT_MS,
+        "attestation": "none",
+        "authenticatorSelection": {
+            "residentKey": "required",
+            "userVerification": "preferred",
+        },
+    });
+    Ok(Json(ChallengeResponse { id, public_key }))
+}
+
+#[derive(serde::Deserialize)]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    #[serde(rename = "attestationObject")]
+    attestation_object: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RegistrationCredential {
+    id: String,
+    response: AttestationResponse,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RegisterFinishRequest {
+    id: String,
+    credential: RegistrationCredential,
+}
+
+/**
+ * Verify the new credential and save it to the user of the link code.
+ */
+pub async fn register_finish(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<RegisterFinishRequest>,
+) -> StatusCode {
+    let challenge = match use_challenge(&state, &body.id).await {
+        Ok(challenge) if !challenge.link_code.is_empty() => challenge,
+        Ok(_) => {
+            tracing::error!("Passkey challenge is not a registration");
+            return StatusCode::BAD_REQUEST;
+        }
+        Err(err) => {
+            tracing::error!("Failed to use passkey challenge: {:?}", err);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    let credential = match verify_registration(
+        &state.env.webauthn_rp_id,
+        &state.env.client_url,
+        &challenge.challenge,
+        &body.credential,
+    ) {
+        Ok(credential) => credential,
+        Err(err) => {
+            tracing::error!("Invalid passkey registration: {:?}", err);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    let name = headers
+        .get(http::header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .chars()
+        .take(512)
+        .collect();
+    let claims = PasskeyClaims {
+        credential_id: body.credential.id,
+        public_key: URL_SAFE_NO_PAD.encode(&credential.public_key),
+        sign_count: credential.sign_count,
+        user_verified: credential.user_verified,
+        name,
+        link_code: challenge.link_code,
+        // 5 min
+        exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
+    };
+    if let Err(err) = create_passkey(&state, &claims).await {
+        tracing::error!("Failed to create passkey: {:?}", err);
+        let status = err.downcast_ref::<tonic::Status>();
+        return match status.map(|s| (s.code(), crate::auth_error::login_error(s))) {
+            Some((_, Some(LoginError::InvalidLinkCode))) => StatusCode::UNAUTHORIZED,
+            Some((tonic::Code::AlreadyExists, _)) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+    }
+
+    tracing::info!("Passkey registered");
+    StatusCode::NO_CONTENT
+}
+
+struct VerifiedRegistration {
+    public_key: Vec<u8>,
+    sign_count: i64,
+    user_verified: bool,
+}
+
+fn verify_registration(
+    rp_id: &str,
+    origin: &str,
+    challenge: &str,
+    credential: &RegistrationCredential,
+) -> Result<VerifiedRegistration> {
+    let client_data_json = URL_SAFE_NO_PAD.decode(&credential.response.client_data_json)?;
+    webauthn::verify_client_data(&client_data_json, "webauthn.create", challenge, origin)?;
+
+    let attestation_object = URL_SAFE_NO_PAD.decode(&credential.response.attestation_object)?;
+    let authenticator_data = webauthn::parse_attestation_object(&attestation_object)?;
+    let authenticator_data = webauthn::parse_authenticator_data(&authenticator_data, rp_id)?;
+    let attested = authenticator_data
+        .credential
+        .context("Registration has no attested credential")?;
+    if URL_SAFE_NO_PAD.encode(&attested.id) != credential.id {
+        anyhow::bail!("Credential id mismatch");
+    }
+    webauthn::check_public_key(&attested.public_key)?;
+
+    Ok(VerifiedRegistration {
+        public_key: attested.public_key,
+        sign_count: authenticator_data.sign_count.into(),
+        user_verified: authenticator_data.user_verified,
+    })
+}
+
+async fn create_passkey(state: &AppState, claims: &PasskeyClaims) -> Result<()> {
+    let mut client = UsersServiceClient::connect(state.env.users_url.to_owned()).await?;
+    let mut request = tonic::Request::new(crate::proto::Empty {});
+    request
+        .metadata_mut()
+        .insert("x-authorization", claims.to_metadata(state)?);
+    client.create_passkey(request).await?;
+    Ok(())
+}
+
+/**
+ * Start a login, any passkey of the domain can be used.
+ */
+pub async fn login_start(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ChallengeResponse>, StatusCode> {
+    let (id, challenge) = create_challenge(&state, "").await.map_err(|err| {
+        tracing::error!("Failed to create passkey challenge: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let public_key = json!({
+        "challenge": challenge,
+        "rpId": state.env.webauthn_rp_id,
+        "timeout": TIMEOUT_MS,
+        "userVerification": "preferred",
+        "allowCredentials": [],
+    });
+    Ok(Json(ChallengeResponse { id, public_key }))
+}
+
+#[derive(serde::Deserialize)]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    #[serde(rename = "authenticatorData")]
+    authenticator_data: String,
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AssertionCredential {
+    id: String,
+    response: AssertionResponse,
+}
+
+#[derive(serde::Deserialize)]
+pub struct LoginFinishRequest {
+    id: String,
+    credential: AssertionCredential,
+}
+
+#[derive(serde::Serialize)]
+pub struct LoginFinishResponse {
+    // Same destination as the redirect at the end of the OAuth flow
+    redirect: String,
+}
+
+/**
+ * Verify the assertion, and sign in the same way as `CreateUser`.
+ */
+pub async fn login_finish(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<LoginFinishRequest>,
+) -> Result<Json<LoginFinishResponse>, StatusCode> {
+    let challenge = match use_challenge(&state, &body.id).await {
+        Ok(challenge) if challenge.link_code.is_empty() => challenge,
+        Ok(_) => {
+            tracing::error!("Passkey challenge is not a login");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Err(err) => {
+            tracing::error!("Failed to use passkey challenge: {:?}", err);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    let client = ClientInfo::new(&state, &headers, addr);
+    let login = verify_login(&state, &client, &challenge.challenge, &body.credential)
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to sign in with passkey: {:?}", err);
+            StatusCode::UNAUTHORIZED
+        })?;
+
+    let redirect = crate::auth_service::session_url(&state, login, "")
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to create session: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(LoginFinishResponse { redirect }))
+}
+
+async fn verify_login(
+    state: &AppState,
+    client_info: &ClientInfo,
+    challenge: &str,
+    credential: &AssertionCredential,
+) -> Result<crate::proto::LoginResponse> {
+    let mut client = UsersServiceClient::connect(state.env.users_url.to_owned()).await?;
+    let mut request = tonic::Request::new(crate::proto::Empty {});
+    request.metadata_mut().insert(
+        "x-authorization",
+        service_auth::generate_passkey_lookup_token(&state.env.jwt_keys, &credential.id)?,
+    );
+    let passkey = client.get_passkey(request).await?.into_inner();
+    let parsed = verify_assertion(
+        &state.env.webauthn_rp_id,
+        &state.env.client_url,
+        challenge,
+        credential,
+        &passkey.public_key,
+    )?;
+
+    // The counter is checked by service-users, so concurrent logins can't both pass
+    let claims = PasskeyClaims {
+        credential_id: credential.id.to_owned(),
+        public_key: String::new(),
+        sign_count: parsed.sign_count.into(),
+        user_verified: parsed.user_verified,
+        name: String::new(),
+        link_code: String::new(),
+        // 5 min
+        exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
+    };
+    let mut request = tonic::Request::new(crate::proto::Empty {});
+    request
+        .metadata_mut()
+        .insert("x-authorization", claims.to_metadata(state)?);
+    client_info.insert_metadata(request.metadata_mut());
+    Ok(client.passkey_login(request).await?.into_inner())
+}
+
+/**
+ * Check the assertion against the stored COSE key of the passkey.
+ */
+fn verify_assertion(
+    rp_id: &str,
+    origin: &str,
+    challenge: &str,
+    credential: &AssertionCredential,
+    public_key: &[u8],
+) -> Result<webauthn::AuthenticatorData> {
+    let client_data_json = URL_SAFE_NO_PAD.decode(&credential.response.client_data_json)?;
+    let client_data_hash =
+        webauthn::verify_client_data(&client_data_json, "webauthn.get", challenge, origin)?;
+    let authenticator_data = URL_SAFE_NO_PAD.decode(&credential.response.authenticator_data)?;
+    let signature = URL_SAFE_NO_PAD.decode(&credential.response.signature)?;
+    let parsed = webauthn::parse_authenticator_data(&authenticator_data, rp_id)?;
+    webauthn::verify_signature(
+        public_key,
+        &authenticator_data,
+        &client_data_hash,
+        &signature,
+    )?;
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webauthn::tests::{SoftAuthenticator, CHALLENGE, ORIGIN, RP_ID};
+
+    fn registration(authenticator: &SoftAuthenticator) -> RegistrationCredential {
+        let client_data = SoftAuthenticator::client_data("webauthn.create", CHALLENGE, ORIGIN);
+        RegistrationCredential {
+            id: URL_SAFE_NO_PAD.encode(&authenticator.credential_id),
+            response: AttestationResponse {
+                client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                attestation_object: URL_SAFE_NO_PAD.encode(authenticator.attestation_object()),
+            },
+        }
+    }
+
+    fn assertion(
+        authenticator: &SoftAuthenticator,
+        kind: &str,
+        origin: &str,
+    ) -> AssertionCredential {
+        let client_data = SoftAuthenticator::client_data(kind, CHALLENGE, origin);
+        let data = authenticator.authenticator_data(false);
+        AssertionCredential {
+            id: URL_SAFE_NO_PAD.encode(&authenticator.credential_id),
+            response: AssertionResponse {
+                signature: URL_SAFE_NO_PAD.encode(authenticator.sign(&data, &client_data)),
+                client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                authenticator_data: URL_SAFE_NO_PAD.encode(data),
+            },
+        }
+    }
+
+    #[test]
+    fn registration_is_verified() {
+        let mut authenticator = SoftAuthenticator::es256();
+        // Present but not verified, and without a counter
+        authenticator.flags = 0x01;
+        authenticator.sign_count = 0;
+        let credential = registration(&authenticator);
+        let verified = verify_registration(RP_ID, ORIGIN, CHALLENGE, &credential).unwrap();
+        assert_eq!(verified.public_key, authenticator.cose_key());
+        assert_eq!(verified.sign_count, 0);
+        assert!(!verified.user_verified);
+    }
+
+    #[test]
+    fn registration_of_another_credential_is_rejected() {
+        let authenticator = SoftAuthenticator::es256();
+        let mut credential = registration(&authenticator);
+        credential.id = URL_SAFE_NO_PAD.encode([8; 16]);
+        let err = verify_registration(RP_ID, ORIGIN, CHALLENGE, &credential);
+        assert_eq!(err.err().unwrap().to_string(), "Credential id mismatch");
+    }
+
+    #[test]
+    fn registration_for_another_rp_is_rejected() {
+        let credential = registration(&SoftAuthenticator::es256());
+        assert!(verify_registration("example.com", ORIGIN, CHALLENGE, &credential).is_err());
+        assert!(verify_registration(RP_ID, ORIGIN, "other", &credential).is_err());
+    }
+
+    #[test]
+    fn assertion_is_verified() {
+        let mut authenticator = SoftAuthenticator::eddsa();
+        authenticator.sign_count = 42;
+        let credential = assertion(&authenticator, "webauthn.get", ORIGIN);
+        let cose = authenticator.cose_key();
+        let parsed = verify_assertion(RP_ID, ORIGIN, CHALLENGE, &credential, &cose).unwrap();
+        assert_eq!(parsed.sign_count, 42);
+        assert!(parsed.user_verified);
+    }
+
+    #[test]
+    fn assertion_of_another_ceremony_or_origin_is_rejected() {
+        let authenticator = SoftAuthenticator::es256();
+        let cose = authenticator.cose_key();
+        let credential = assertion(&authenticator, "webauthn.create", ORIGIN);
+        assert!(verify_assertion(RP_ID, ORIGIN, CHALLENGE, &credential, &cose).is_err());
+        let credential = assertion(&authenticator, "webauthn.get", "https://evil.example");
+        assert!(verify_assertion(RP_ID, ORIGIN, CHALLENGE, &credential, &cose).is_err());
+        let credential = assertion(&authenticator, "webauthn.get", ORIGIN);
+        assert!(verify_assertion(RP_ID, ORIGIN, "other", &credential, &cose).is_err());
+    }
+
+    #[test]
+    fn assertion_with_another_key_is_rejected() {
+        let credential = assertion(&SoftAuthenticator::es256(), "webauthn.get", ORIGIN);
+        let cose = SoftAuthenticator::es256().cose_key();
+        let err = verify_assertion(RP_ID, ORIGIN, CHALLENGE, &credential, &cose);
+        assert_eq!(err.err().unwrap().to_string(), "Invalid signature");
+    }
+}
@@ -1,35 +1,63 @@
 use crate::{
+    auth_audit::ClientInfo,
+    auth_error::{AuthError, AuthFailure},
     auth_oauth::{OAuth, OAuthConfig},
     proto::users_service_client::UsersServiceClient,
+    verifier_store::{NewVerifier, VerifierStore, VERIFIER_TTL},
     AppState,
 };
 use anyhow::Result;
 use axum::{
-    extract::{Path, Query, State},
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
     response::Redirect,
+    Json,
 };
-use oauth2::{CsrfToken, PkceCodeChallenge, Scope, TokenResponse};
-use std::{collections::HashMap, sync::Arc};
+use jsonwebtoken::jwk::JwkSet;
+use oauth2::{CsrfToken, PkceCodeChallenge, Scope};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use tonic::metadata::{Ascii, MetadataValue};
 
 pub async fn oauth_login(
     Path(provider): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Redirect, Redirect> {
-    let conn = state.pool.get().await.map_err(|err| {
-        tracing::error!("Failed to get DB connection: {:?}", err);
-        Redirect::to(&format!("{}/auth?error=1", state.env.client_url))
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Redirect, AuthFailure> {
+    // Every login keeps a verifier until the callback, don't let them pile up.
+    let pending = state.verifiers.count().await.map_err(|err| {
+        AuthError::Internal.redirect(
+            &state.env.client_url,
+            format!("Failed to count verifiers: {:?}", err),
+        )
     })?;
+    if pending >= state.env.rate_limit.max_verifiers {
+        return Err(AuthError::TooManyRequests.redirect(
+            &state.env.client_url,
+            format!("Too many logins in progress: {}", pending),
+        ));
+    }
 
-    let oauth_config =
-        OAuthConfig::get_config_by_provider(&provider, state.env.clone()).map_err(|err| {
-            tracing::error!("Failed to get OAuth provider: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=1", state.env.client_url))
+    let oauth_config = OAuthConfig::get_config_by_provider(&provider, &state)
+        .await
+        .map_err(|err| {
+            AuthError::from_anyhow(&err, AuthError::ProviderError).redirect(
+                &state.env.client_url,
+                format!("Failed to get OAuth provider: {:?}", err),
+            )
         })?;
-    let client = oauth_config.build_oauth_client();
+    let client = oauth_config.build_oauth_client().map_err(|err| {
+        AuthError::from_anyhow(&err, AuthError::ProviderError).redirect(
+            &state.env.client_url,
+            format!("Failed to build OAuth client: {:?}", err),
+        )
+    })?;
 
     // Generate a PKCE challenge.
     let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
 
+    // Generate a nonce, OIDC providers put it in the id_token so it can't be replayed.
+    let nonce = CsrfToken::new_random();
+
     // Generate the full authorization URL.
     let mut client = client
         .authorize_url(CsrfToken::new_random)
@@ -37,15 +65,44 @@ pub async fn oauth_login(
     for scope in oauth_config.scopes {
         client = client.add_scope(Scope::new(scope));
     }
-    let (auth_url, csrf_token) = client.add_extra_param("access_type", "offline").url();
+    let mut client = client
+        .add_extra_param("access_type", "offline")
+        .add_extra_param("nonce", nonce.secret());
+    // Passed on to the provider to preselect the account, the `dev` one signs in directly with it.
+    if let Some(login_hint) = query.get("login_hint") {
+        client = client.add_extra_param("login_hint", login_hint);
+    }
+    let (auth_url, csrf_token) = client.url();
+
+    // A signed in user linking this provider to their account, the code comes from the users service.
+    let link_code = query.get("link").map(String::as_str).unwrap_or_default();
 
-    // Save the CSRF token and PKCE verifier so we can verify them later.
-    // Here we are using the PostgreSQL database, but you can use whatever you want, e.g. Redis.
-    crate::auth_db::create_verifiers(&conn, csrf_token.secret(), pkce_verifier.secret())
+    // The page to go back to after login, anything not on the allowlist is ignored.
+    let return_to = match query.get("return_to") {
+        Some(return_to) => state.env.return_to.validate(return_to).unwrap_or_else(|| {
+            tracing::warn!("Ignoring return_to not on the allowlist: {:?}", return_to);
+            String::new()
+        }),
+        None => String::new(),
+    };
+
+    // Save the CSRF token, PKCE verifier and nonce so we can verify them later.
+    // The store is selected by VERIFIER_STORE, see `VerifierStore` to add another one, e.g. Redis.
+    state
+        .verifiers
+        .create(NewVerifier {
+            csrf_token: csrf_token.secret(),
+            pkce_verifier: pkce_verifier.secret(),
+            nonce: nonce.secret(),
+            link_code,
+            return_to: &return_to,
+        })
         .await
         .map_err(|err| {
-            tracing::error!("Failed to save verifier: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=1", state.env.client_url))
+            AuthError::Internal.redirect(
+                &state.env.client_url,
+                format!("Failed to save verifier: {:?}", err),
+            )
         })?;
 
     Ok(Redirect::to(auth_url.as_ref()))
@@ -54,111 +111,307 @@ pub async fn oauth_login(
 pub async fn oauth_callback(
     Path(provider): Path<String>,
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Query(query): Query<HashMap<String, String>>,
-) -> Result<Redirect, Redirect> {
-    let conn = state.pool.get().await.map_err(|err| {
-        tracing::error!("Failed to get DB connection: {:?}", err);
-        Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
-    })?;
+) -> Result<Redirect, AuthFailure> {
+    let client = ClientInfo::new(&state, &headers, addr);
+    let result = finish_oauth_login(&state, &provider, &client, &query).await;
+    if let Err(failure) = &result {
+        crate::auth_audit::spawn_login_failure(state.clone(), provider, client, failure);
+    }
+    result
+}
 
-    let code = query.get("code").ok_or_else(|| {
-        tracing::error!("Missing code");
-        Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
-    })?;
+async fn finish_oauth_login(
+    state: &AppState,
+    provider: &str,
+    client_info: &ClientInfo,
+    query: &HashMap<String, String>,
+) -> Result<Redirect, AuthFailure> {
+    // The user declined, or the provider couldn't authenticate them.
+    if let Some(error) = query.get("error") {
+        return Err(AuthError::from_provider(error).redirect(
+            &state.env.client_url,
+            format!(
+                "Provider returned an error: {:?}, {:?}",
+                error,
+                query.get("error_description")
+            ),
+        ));
+    }
+
+    let code = query
+        .get("code")
+        .ok_or_else(|| AuthError::InvalidCode.redirect(&state.env.client_url, "Missing code"))?;
     let csrf = query.get("state").ok_or_else(|| {
-        tracing::error!("Missing CSRF token");
-        Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+        AuthError::InvalidState.redirect(&state.env.client_url, "Missing CSRF token")
     })?;
 
-    let verifiers = match crate::auth_db::select_verifiers_by_csrf(&conn, csrf).await {
+    // Consumed here, even if the login fails later on.
+    let verifiers = match state.verifiers.take(csrf).await {
         Ok(Some(verifiers)) => verifiers,
         Ok(None) => {
-            return Err(Redirect::to(&format!(
-                "{}/auth?error=2",
-                state.env.client_url
-            )))
+            return Err(
+                AuthError::InvalidState.redirect(&state.env.client_url, "Unknown CSRF token")
+            )
         }
         Err(err) => {
-            tracing::error!("Failed to select verifiers: {:?}", err);
-            return Err(Redirect::to(&format!(
-                "{}/auth?error=2",
-                state.env.client_url
-            )));
+            return Err(AuthError::Internal.redirect(
+                &state.env.client_url,
+                format!("Failed to take verifiers: {:?}", err),
+            ));
         }
     };
 
-    // Delete old verifiers asynchronously. If this fails, it's not a big deal.
-    tokio::spawn(async move {
-        if let Err(err) = crate::auth_db::delete_old_verifiers(&conn).await {
-            tracing::error!("Failed to delete old verifiers: {:?}", err);
-        }
-    });
-
     // Check if the CSRF token is valid.
-    if verifiers.created + time::Duration::minutes(10) < time::OffsetDateTime::now_utc() {
-        return Err(Redirect::to(&format!(
-            "{}/auth?error=2",
-            state.env.client_url
-        )));
+    if verifiers.created + VERIFIER_TTL < time::OffsetDateTime::now_utc() {
+        return Err(
+            AuthError::ExpiredState.redirect(&state.env.client_url, "CSRF token has expired")
+        );
     }
 
     // Exchange the code with a token.
-    let oauth_config =
-        OAuthConfig::get_config_by_provider(&provider, state.env.clone()).map_err(|err| {
-            tracing::error!("Failed to get OAuth provider: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+    let oauth_config = OAuthConfig::get_config_by_provider(provider, state)
+        .await
+        .map_err(|err| {
+            AuthError::from_anyhow(&err, AuthError::ProviderError).redirect(
+                &state.env.client_url,
+                format!("Failed to get OAuth provider: {:?}", err),
+            )
         })?;
-    let client = oauth_config.build_oauth_client();
+    let client = oauth_config.build_oauth_client().map_err(|err| {
+        AuthError::from_anyhow(&err, AuthError::ProviderError).redirect(
+            &state.env.client_url,
+            format!("Failed to build OAuth client: {:?}", err),
+        )
+    })?;
     let token = client
         .exchange_code(oauth2::AuthorizationCode::new(code.to_string()))
         // Set the PKCE code verifier.
-        .set_pkce_verifier(oauth2::PkceCodeVerifier::new(verifiers.pkce_verifier))
+        .set_pkce_verifier(oauth2::PkceCodeVerifier::new(
+            verifiers.pkce_verifier.to_owned(),
+        ))
         .request_async(oauth2::reqwest::async_http_client)
         .await
         .map_err(|err| {
-            tracing::error!("Failed to exchange code with token: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+            AuthError::InvalidCode.redirect(
+                &state.env.client_url,
+                format!("Failed to exchange code with token: {:?}", err),
+            )
         })?;
 
     // Get the user's profile.
-    let user_profile = oauth_config
-        .get_user_info(token.access_token().secret())
+    let mut user_profile = oauth_config
+        .get_user_info(&token, &verifiers.nonce)
         .await
         .map_err(|err| {
-            tracing::error!("Failed to get user profile: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+            AuthError::from_anyhow(&err, AuthError::InvalidProfile).redirect(
+                &state.env.client_url,
+                format!("Failed to get user profile: {:?}", err),
+            )
         })?;
 
+    let link = !verifiers.link_code.is_empty();
+    user_profile.link_code = verifiers.link_code;
+
     /*
      * This is where you implement you own logic to create or update a user in your database.
      * Here we are calling the users service to create a new user.
      * It returns an id token that we can use to authenticate the user.
      */
     let jwt_token = oauth_config.generate_jwt(user_profile).map_err(|err| {
-        tracing::error!("Failed to generate JWT: {:?}", err);
-        Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+        AuthError::Internal.redirect(
+            &state.env.client_url,
+            format!("Failed to generate JWT: {:?}", err),
+        )
     })?;
+    if link {
+        return link_url(state, &jwt_token)
+            .await
+            .map(|url| Redirect::to(&url))
+            .map_err(|err| {
+                AuthError::Internal.redirect(
+                    &state.env.client_url,
+                    format!("Failed to create link code: {:?}", err),
+                )
+            });
+    }
+    // The allowlist may have changed since the login started.
+    let return_to = state
+        .env
+        .return_to
+        .validate(&verifiers.return_to)
+        .unwrap_or_default();
+    create_session(state, client_info, jwt_token, &return_to).await
+}
+
+/**
+ * Create the user and its session from a verified identity, the last step of every login flow.
+ * Redirects to the client with a one-time code for the session token.
+ * `return_to` must already be validated against the allowlist, or empty.
+ * The client info is forwarded, so the users service can record the login.
+ */
+pub async fn create_session(
+    state: &AppState,
+    client_info: &ClientInfo,
+    jwt_token: MetadataValue<Ascii>,
+    return_to: &str,
+) -> Result<Redirect, AuthFailure> {
     let client = UsersServiceClient::connect(state.env.users_url.to_owned())
         .await
         .map_err(|err| {
-            tracing::error!("Failed to connect to users service: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+            AuthError::UsersUnavailable.redirect(
+                &state.env.client_url,
+                format!("Failed to connect to users service: {:?}", err),
+            )
         });
     let mut request = tonic::Request::new(crate::proto::Empty {});
     let metadata = request.metadata_mut();
     metadata.insert("x-authorization", jwt_token);
-    let token = client?
+    client_info.insert_metadata(metadata);
+    let login = client?
         .create_user(request)
         .await
         .map_err(|err| {
-            tracing::error!("Failed to create user: {:?}", err);
-            Redirect::to(&format!("{}/auth?error=2", state.env.client_url))
+            AuthError::from_status(&err).redirect(
+                &state.env.client_url,
+                format!("Failed to create user: {:?}", err),
+            )
         })?
         .into_inner();
 
+    let url = session_url(state, login, return_to).await.map_err(|err| {
+        AuthError::Internal.redirect(
+            &state.env.client_url,
+            format!("Failed to create session: {:?}", err),
+        )
+    })?;
+    Ok(Redirect::to(&url))
+}
+
+/**
+ * Where the client goes after a successful login, shared by every login flow.
+ */
+pub async fn session_url(
+    state: &AppState,
+    login: crate::proto::LoginResponse,
+    return_to: &str,
+) -> Result<String> {
+    // Two-factor is enabled, the client completes the challenge before a session is issued.
+    if !login.challenge_id.is_empty() {
+        tracing::info!("User needs to complete two-factor challenge");
+        let mut url = reqwest::Url::parse(&format!("{}/auth/2fa", state.env.client_url))?;
+        url.query_pairs_mut()
+            .append_pair("challenge", &login.challenge_id);
+        if !return_to.is_empty() {
+            url.query_pairs_mut().append_pair("return_to", return_to);
+        }
+        return Ok(url.to_string());
+    }
+
+    /*
+     * Don't put the session token in the url, it would end up in browser history, referrer headers
+     * and proxy logs. Instead we redirect with a short-lived, single-use code that the client
+     * exchanges server-side for the token.
+     */
+    let conn = state.pool.get().await?;
+    let code = CsrfToken::new_random();
+    crate::auth_db::create_code(&conn, code.secret(), &login.token, return_to, false).await?;
+
     tracing::info!("User authenticated");
-    Ok(Redirect::to(&format!(
-        "{}/?token={}",
-        state.env.client_url, token.id
-    )))
+    Ok(format!("{}/?code={}", state.env.client_url, code.secret()))
+}
+
+/**
+ * A login started from the settings to link a provider doesn't sign in. The verified identity
+ * goes back to the settings, where the client links it with the session of its own browser.
+ * The browser that started the login might not be the one that finishes it.
+ */
+async fn link_url(state: &AppState, jwt_token: &MetadataValue<Ascii>) -> Result<String> {
+    let token = jwt_token.to_str()?;
+    let token = token.strip_prefix("bearer ").unwrap_or(token);
+    let conn = state.pool.get().await?;
+    let code = CsrfToken::new_random();
+    crate::auth_db::create_code(&conn, code.secret(), token, "", true).await?;
+
+    tracing::info!("Identity verified for linking");
+    Ok(format!(
+        "{}/settings?link={}",
+        state.env.client_url,
+        code.secret()
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExchangeRequest {
+    code: String,
+    // Set by the settings page, to get the identity of a link code
+    #[serde(default)]
+    link: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct ExchangeResponse {
+    token: String,
+    // Empty when the login didn't ask for it
+    return_to: String,
+}
+
+pub async fn oauth_exchange(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ExchangeRequest>,
+) -> Result<Json<ExchangeResponse>, StatusCode> {
+    let conn = state.pool.get().await.map_err(|err| {
+        tracing::error!("Failed to get DB connection: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let code = match crate::auth_db::delete_code(&conn, &body.code).await {
+        Ok(Some(code)) => code,
+        Ok(None) => {
+            tracing::error!("Invalid code");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Err(err) => {
+            tracing::error!("Failed to delete code: {:?}", err);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Delete old codes asynchronously. If this fails, it's not a big deal.
+    tokio::spawn(async move {
+        if let Err(err) = crate::auth_db::delete_old_codes(&conn).await {
+            tracing::error!("Failed to delete old codes: {:?}", err);
+        }
+    });
+
+    // Check if the code is still valid.
+    if code.created + time::Duration::minutes(1) < time::OffsetDateTime::now_utc() {
+        tracing::error!("Code has expired");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    // A link code never becomes a session, and a session code is never linked.
+    if code.link != body.link {
+        tracing::error!("Code is for another flow");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(Json(ExchangeResponse {
+        token: code.token,
+        return_to: code.return_to,
+    }))
+}
+
+/**
+ * Public keys used to verify the tokens between services, keyed by `kid`.
+ * Empty in the legacy HS256 mode.
+ */
+pub async fn jwks(
+    State(state): State<Arc<AppState>>,
+) -> ([(HeaderName, &'static str); 1], Json<JwkSet>) {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.env.jwt_keys.jwks()),
+    )
 }
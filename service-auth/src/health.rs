@@ -0,0 +1,35 @@
+use crate::AppState;
+use axum::{extract::State, http::StatusCode};
+use std::{sync::Arc, time::Duration};
+
+/**
+ * Liveness, the process is up and answering. Nothing else is checked, so a database outage
+ * doesn't get the service restarted.
+ */
+pub async fn healthz() -> (StatusCode, &'static str) {
+    (StatusCode::OK, "ok")
+}
+
+/**
+ * Readiness, the database answers. Traffic should only be routed here while it does.
+ */
+pub async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, &'static str) {
+    // A pool without free connections waits, don't let it hang the probe
+    let check = tokio::time::timeout(Duration::from_secs(5), async {
+        let conn = state.pool.get().await?;
+        conn.simple_query("select 1").await?;
+        anyhow::Ok(())
+    })
+    .await;
+    match check {
+        Ok(Ok(())) => (StatusCode::OK, "ok"),
+        Ok(Err(err)) => {
+            tracing::error!("Service is not ready: {:?}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+        }
+        Err(_) => {
+            tracing::error!("Service is not ready: database check timed out");
+            (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+        }
+    }
+}
@@ -1,17 +1,30 @@
+mod auth_audit;
 mod auth_db;
+mod auth_dev;
+mod auth_error;
+mod auth_magic;
 mod auth_oauth;
+mod auth_oidc;
+mod auth_passkey;
 mod auth_service;
+mod health;
 mod migrations;
 mod proto;
+mod rate_limit;
+mod verifier_store;
+mod webauthn;
 
 use anyhow::Context;
 use anyhow::Result;
-use axum::http::StatusCode;
-use axum::Json;
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
 use http::header::{AUTHORIZATION, CONTENT_TYPE};
 use http::HeaderValue;
 use http::Method;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
@@ -19,6 +32,9 @@ use tower_http::cors::CorsLayer;
 struct AppState {
     env: service_auth::Env,
     pool: deadpool_postgres::Pool,
+    verifiers: verifier_store::Verifiers,
+    dev_oauth: auth_dev::DevOAuth,
+    oidc: auth_oidc::OidcCache,
 }
 
 #[tokio::main]
@@ -30,6 +46,9 @@ async fn main() -> Result<()> {
     let filter = &env.rust_log;
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
+    // Reload the JWT keys periodically, so they can be rotated without a restart
+    env.jwt_keys.spawn_reload();
+
     // Connect to database
     let pool = service_auth::connect_to_db(&env).context("Failed to connect to database")?;
     tracing::info!("Connected to database");
@@ -40,10 +59,18 @@ async fn main() -> Result<()> {
         .context("Failed to run migrations")?;
     tracing::info!("Migrations complete");
 
+    // Store of the logins in progress
+    let verifiers = verifier_store::Verifiers::new(&env.verifier_store, pool.clone())?;
+    verifiers.spawn_cleanup();
+    tracing::info!("Verifier store: {}", env.verifier_store);
+
     // Create shared state
     let shared_state = Arc::new(AppState {
         pool,
         env: env.clone(),
+        verifiers,
+        dev_oauth: auth_dev::DevOAuth::default(),
+        oidc: auth_oidc::OidcCache::default(),
     });
 
     let cors = CorsLayer::new()
@@ -51,13 +78,61 @@ async fn main() -> Result<()> {
         .allow_headers([AUTHORIZATION, CONTENT_TYPE])
         .allow_origin(env.client_url.parse::<HeaderValue>()?);
 
-    let app = Router::new()
-        .route("/", get(root))
+    // Only the auth endpoints are limited, the other services fetch the JWKS
+    let rate_limiter = rate_limit::RateLimiter::new(env.rate_limit.clone());
+    rate_limiter.spawn_cleanup();
+
+    let auth_routes = Router::new()
         .route("/oauth-login/:provider", get(auth_service::oauth_login))
         .route(
             "/oauth-callback/:provider",
             get(auth_service::oauth_callback),
         )
+        .route(
+            "/magic-link/callback",
+            get(auth_magic::magic_link_confirm).post(auth_magic::magic_link_callback),
+        )
+        .route(
+            "/passkey/register/start",
+            post(auth_passkey::register_start),
+        )
+        .route(
+            "/passkey/register/finish",
+            post(auth_passkey::register_finish),
+        )
+        .route("/passkey/login/start", post(auth_passkey::login_start))
+        .route("/passkey/login/finish", post(auth_passkey::login_finish))
+        .route_layer(middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit::rate_limit,
+        ));
+
+    // Called by the client server for every user, so they share its IP. The magic links
+    // are also limited per email, see `auth_magic::MAX_RECENT_LINKS`.
+    let server_routes = Router::new()
+        .route("/oauth-exchange", post(auth_service::oauth_exchange))
+        .route("/magic-link", post(auth_magic::magic_link))
+        .route_layer(middleware::from_fn_with_state(
+            rate_limiter,
+            rate_limit::rate_limit_global,
+        ));
+
+    let mut app = Router::new()
+        // Probes of the orchestrator, `/` is kept for the existing ones
+        .route("/", get(health::healthz))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/.well-known/jwks.json", get(auth_service::jwks))
+        .merge(auth_routes)
+        .merge(server_routes);
+    if !env.dev_oauth_users.is_empty() {
+        tracing::warn!("Dev OAuth provider is enabled, never use it in production");
+        app = app
+            .route("/dev-oauth/authorize", get(auth_dev::authorize))
+            .route("/dev-oauth/token", post(auth_dev::token))
+            .route("/dev-oauth/userinfo", get(auth_dev::userinfo));
+    }
+    let app = app
         .with_state(shared_state.clone())
         .layer(ServiceBuilder::new().layer(cors));
 
@@ -65,16 +140,12 @@ async fn main() -> Result<()> {
     let addr = format!("[::]:{}", env.port);
     tracing::info!("HTTP server started on port: {:?}", env.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app)
-        .await
-        .context("Failed to run HTTP server")?;
+    // The peer address is needed for the rate limits
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .context("Failed to run HTTP server")?;
     Ok(())
 }
-
-/**
- * Ping the database to check if it's up
- */
-async fn root() -> Result<(StatusCode, Json<String>), StatusCode> {
-    tracing::info!("Ping");
-    Ok((StatusCode::OK, Json("Hello, World!".to_string())))
-}
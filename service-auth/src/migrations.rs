@@ -10,8 +10,48 @@ pub async fn run_migrations(pool: &deadpool_postgres::Pool) -> Result<()> {
                 created timestamptz not null default now(),
                 csrf_token text not null,
                 pkce_verifier text not null,
+                nonce text not null default '',
+                link_code text not null default '',
+                return_to text not null default '',
                 unique (csrf_token, pkce_verifier)
             );
+            alter table verifiers add column if not exists nonce text not null default '';
+            alter table verifiers add column if not exists link_code text not null default '';
+            alter table verifiers add column if not exists return_to text not null default '';
+
+            create table if not exists codes (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                code text not null unique,
+                token text not null,
+                return_to text not null default '',
+                link boolean not null default false
+            );
+            alter table codes add column if not exists return_to text not null default '';
+            alter table codes add column if not exists link boolean not null default false;
+
+            create table if not exists magic_links (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                email text not null,
+                token_hash text not null unique,
+                expires timestamptz not null,
+                used timestamptz not null default 'infinity'
+            );
+            create index if not exists magic_links_email_idx on magic_links (email, created);
+            -- Links requested for each address in the current window, see `create_magic_link`
+            create table if not exists magic_link_limits (
+                email text primary key,
+                window_start timestamptz not null default now(),
+                count int not null default 1
+            );
+
+            create table if not exists passkey_challenges (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                challenge text not null,
+                link_code text not null default ''
+            );
         "#,
         )
         .await?;
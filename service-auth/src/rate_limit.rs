@@ -0,0 +1,247 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/**
+ * Token bucket, refilled continuously so a client can't burst twice around a window boundary.
+ */
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(capacity: f64) -> Self {
+        Bucket {
+            tokens: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    // Add the tokens refilled since the last update
+    fn refill(&mut self, per_minute: u32) {
+        let capacity = per_minute as f64;
+        let now = Instant::now();
+        let refill = now.duration_since(self.updated).as_secs_f64() * capacity / 60.0;
+        self.tokens = (self.tokens + refill).min(capacity);
+        self.updated = now;
+    }
+
+    // How long until a token is available, None if there is one
+    fn wait(&self, per_minute: u32) -> Option<Duration> {
+        if self.tokens >= 1.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (1.0 - self.tokens) * 60.0 / per_minute as f64,
+        ))
+    }
+
+    fn is_full(&self, per_minute: u32) -> bool {
+        let refill = self.updated.elapsed().as_secs_f64() * per_minute as f64 / 60.0;
+        self.tokens + refill >= per_minute as f64
+    }
+}
+
+/**
+ * Per IP and global limits of the auth endpoints, configured by `RATE_LIMIT_*`.
+ */
+#[derive(Clone)]
+pub struct RateLimiter {
+    env: service_auth::RateLimitEnv,
+    global: Arc<Mutex<Bucket>>,
+    per_ip: Arc<Mutex<HashMap<IpAddr, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(env: service_auth::RateLimitEnv) -> Self {
+        RateLimiter {
+            global: Arc::new(Mutex::new(Bucket::new(env.global as f64))),
+            per_ip: Arc::default(),
+            env,
+        }
+    }
+
+    /**
+     * A request takes a token from both buckets, and only if both have one. Otherwise a client
+     * over its own limit would still use up the global one.
+     */
+    fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        let mut per_ip = self.per_ip.lock().expect("Rate limiter lock poisoned");
+        let per_ip = per_ip
+            .entry(ip)
+            .or_insert_with(|| Bucket::new(self.env.per_ip as f64));
+        let mut global = self.global.lock().expect("Rate limiter lock poisoned");
+        per_ip.refill(self.env.per_ip);
+        global.refill(self.env.global);
+        match (per_ip.wait(self.env.per_ip), global.wait(self.env.global)) {
+            (None, None) => {
+                per_ip.tokens -= 1.0;
+                global.tokens -= 1.0;
+                Ok(())
+            }
+            (per_ip_wait, global_wait) => Err(per_ip_wait.max(global_wait).unwrap_or_default()),
+        }
+    }
+
+    /**
+     * Only the global bucket, for the endpoints called by the client server on behalf of every
+     * user, all from the same IP.
+     */
+    fn check_global(&self) -> Result<(), Duration> {
+        let mut global = self.global.lock().expect("Rate limiter lock poisoned");
+        global.refill(self.env.global);
+        match global.wait(self.env.global) {
+            None => {
+                global.tokens -= 1.0;
+                Ok(())
+            }
+            Some(wait) => Err(wait),
+        }
+    }
+
+    /**
+     * Forget the IPs that are back to a full bucket every minute, so the map doesn't grow forever.
+     */
+    pub fn spawn_cleanup(&self) {
+        let limiter = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let per_minute = limiter.env.per_ip;
+                limiter
+                    .per_ip
+                    .lock()
+                    .expect("Rate limiter lock poisoned")
+                    .retain(|_, bucket| !bucket.is_full(per_minute));
+            }
+        });
+    }
+}
+
+/**
+ * The IP of the client, from the connection or from our proxy, see `RATE_LIMIT_FORWARDED_FOR`.
+ */
+pub fn client_ip(
+    env: &service_auth::RateLimitEnv,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> IpAddr {
+    if !env.forwarded_for {
+        return addr.ip();
+    }
+    // The last address is the one added by our proxy, the others can be spoofed by the client
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .last()
+        .and_then(|ip| ip.trim().parse().ok())
+        .unwrap_or_else(|| addr.ip())
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let retry_after = retry_after.as_secs().max(1);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, HeaderValue::from(retry_after))],
+        "Too many requests",
+    )
+        .into_response()
+}
+
+pub async fn rate_limit(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = client_ip(&limiter.env, request.headers(), addr);
+    if let Err(retry_after) = limiter.check(ip) {
+        tracing::warn!("Rate limit exceeded: {:?} {}", ip, request.uri().path());
+        return too_many_requests(retry_after);
+    }
+    next.run(request).await
+}
+
+/**
+ * The endpoints called server to server by the client, where the IP is the client server's.
+ */
+pub async fn rate_limit_global(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Err(retry_after) = limiter.check_global() {
+        tracing::warn!("Global rate limit exceeded: {}", request.uri().path());
+        return too_many_requests(retry_after);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(per_ip: u32, global: u32) -> RateLimiter {
+        RateLimiter::new(service_auth::RateLimitEnv {
+            per_ip,
+            global,
+            max_verifiers: 10000,
+            forwarded_for: false,
+        })
+    }
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([203, 0, 113, last])
+    }
+
+    #[test]
+    fn requests_over_the_per_ip_limit_are_rejected() {
+        let limiter = limiter(2, 100);
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check(ip(1)).is_ok());
+        let retry_after = limiter.check(ip(1)).unwrap_err();
+        assert!(retry_after > Duration::from_secs(29) && retry_after <= Duration::from_secs(30));
+        assert!(limiter.check(ip(2)).is_ok());
+    }
+
+    #[test]
+    fn rejected_requests_dont_use_up_the_global_limit() {
+        let limiter = limiter(1, 3);
+        assert!(limiter.check(ip(1)).is_ok());
+        for _ in 0..10 {
+            assert!(limiter.check(ip(1)).is_err());
+        }
+        assert!(limiter.check(ip(2)).is_ok());
+        assert!(limiter.check(ip(3)).is_ok());
+    }
+
+    #[test]
+    fn requests_over_the_global_limit_dont_use_up_the_per_ip_limit() {
+        let limiter = limiter(2, 1);
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check(ip(2)).is_err());
+        let per_ip = limiter.per_ip.lock().unwrap();
+        assert!(per_ip[&ip(2)].tokens > 1.9);
+    }
+
+    #[test]
+    fn global_check_doesnt_use_the_per_ip_limit() {
+        let limiter = limiter(1, 3);
+        assert!(limiter.check_global().is_ok());
+        assert!(limiter.check_global().is_ok());
+        assert!(limiter.check(ip(1)).is_ok());
+        assert!(limiter.check_global().is_err());
+        assert!(limiter.per_ip.lock().unwrap().len() == 1);
+    }
+}
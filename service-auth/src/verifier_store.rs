@@ -0,0 +1,256 @@
+use crate::auth_db::Verifier;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+
+// How long a login can take between the redirect to the provider and the callback
+pub const VERIFIER_TTL: time::Duration = time::Duration::minutes(10);
+
+pub struct NewVerifier<'a> {
+    pub csrf_token: &'a str,
+    pub pkce_verifier: &'a str,
+    pub nonce: &'a str,
+    pub link_code: &'a str,
+    pub return_to: &'a str,
+}
+
+/**
+ * Where the CSRF token, PKCE verifier and nonce wait for the callback of the provider.
+ * A verifier is consumed by `take`, so the same `state` can never be used twice.
+ */
+pub trait VerifierStore {
+    async fn create(&self, verifier: NewVerifier<'_>) -> Result<()>;
+    // Remove the verifier and return it, expired ones are returned too so the caller can tell them apart
+    async fn take(&self, csrf_token: &str) -> Result<Option<Verifier>>;
+    // Logins in progress, to cap how many can be started
+    async fn count(&self) -> Result<i64>;
+    async fn delete_expired(&self) -> Result<()>;
+}
+
+/**
+ * Shared by every instance of the service, the default.
+ */
+pub struct PostgresVerifierStore {
+    pool: deadpool_postgres::Pool,
+}
+
+impl VerifierStore for PostgresVerifierStore {
+    async fn create(&self, verifier: NewVerifier<'_>) -> Result<()> {
+        let conn = self.pool.get().await?;
+        crate::auth_db::create_verifiers(
+            &conn,
+            verifier.csrf_token,
+            verifier.pkce_verifier,
+            verifier.nonce,
+            verifier.link_code,
+            verifier.return_to,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn take(&self, csrf_token: &str) -> Result<Option<Verifier>> {
+        let conn = self.pool.get().await?;
+        crate::auth_db::delete_verifiers_by_csrf(&conn, csrf_token).await
+    }
+
+    async fn count(&self) -> Result<i64> {
+        let conn = self.pool.get().await?;
+        crate::auth_db::count_verifiers(&conn).await
+    }
+
+    async fn delete_expired(&self) -> Result<()> {
+        let conn = self.pool.get().await?;
+        crate::auth_db::delete_old_verifiers(&conn).await
+    }
+}
+
+/**
+ * Kept in the memory of the process, for a single instance or tests.
+ * Logins in progress are lost on restart.
+ */
+#[derive(Default)]
+pub struct MemoryVerifierStore {
+    verifiers: Mutex<HashMap<String, Verifier>>,
+}
+
+impl VerifierStore for MemoryVerifierStore {
+    async fn create(&self, verifier: NewVerifier<'_>) -> Result<()> {
+        let mut verifiers = self.verifiers.lock().expect("Verifier store lock poisoned");
+        verifiers.insert(
+            verifier.csrf_token.to_string(),
+            Verifier {
+                created: OffsetDateTime::now_utc(),
+                pkce_verifier: verifier.pkce_verifier.to_string(),
+                nonce: verifier.nonce.to_string(),
+                link_code: verifier.link_code.to_string(),
+                return_to: verifier.return_to.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn take(&self, csrf_token: &str) -> Result<Option<Verifier>> {
+        let mut verifiers = self.verifiers.lock().expect("Verifier store lock poisoned");
+        Ok(verifiers.remove(csrf_token))
+    }
+
+    async fn count(&self) -> Result<i64> {
+        let verifiers = self.verifiers.lock().expect("Verifier store lock poisoned");
+        let now = OffsetDateTime::now_utc();
+        Ok(verifiers
+            .values()
+            .filter(|verifier| verifier.created + VERIFIER_TTL > now)
+            .count() as i64)
+    }
+
+    async fn delete_expired(&self) -> Result<()> {
+        let mut verifiers = self.verifiers.lock().expect("Verifier store lock poisoned");
+        let now = OffsetDateTime::now_utc();
+        verifiers.retain(|_, verifier| verifier.created + VERIFIER_TTL > now);
+        Ok(())
+    }
+}
+
+/**
+ * The store selected by `VERIFIER_STORE`.
+ */
+#[derive(Clone)]
+pub enum Verifiers {
+    Postgres(Arc<PostgresVerifierStore>),
+    Memory(Arc<MemoryVerifierStore>),
+}
+
+impl Verifiers {
+    pub fn new(kind: &str, pool: deadpool_postgres::Pool) -> Result<Self> {
+        match kind {
+            "postgres" => Ok(Verifiers::Postgres(Arc::new(PostgresVerifierStore {
+                pool,
+            }))),
+            "memory" => Ok(Verifiers::Memory(Arc::default())),
+            _ => bail!("Invalid VERIFIER_STORE: {}", kind),
+        }
+    }
+
+    /**
+     * Delete the expired verifiers every minute, instead of on every callback.
+     */
+    pub fn spawn_cleanup(&self) {
+        let verifiers = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                if let Err(err) = verifiers.delete_expired().await {
+                    tracing::error!("Failed to delete old verifiers: {:?}", err);
+                }
+            }
+        });
+    }
+}
+
+impl VerifierStore for Verifiers {
+    async fn create(&self, verifier: NewVerifier<'_>) -> Result<()> {
+        match self {
+            Verifiers::Postgres(store) => store.create(verifier).await,
+            Verifiers::Memory(store) => store.create(verifier).await,
+        }
+    }
+
+    async fn take(&self, csrf_token: &str) -> Result<Option<Verifier>> {
+        match self {
+            Verifiers::Postgres(store) => store.take(csrf_token).await,
+            Verifiers::Memory(store) => store.take(csrf_token).await,
+        }
+    }
+
+    async fn count(&self) -> Result<i64> {
+        match self {
+            Verifiers::Postgres(store) => store.count().await,
+            Verifiers::Memory(store) => store.count().await,
+        }
+    }
+
+    async fn delete_expired(&self) -> Result<()> {
+        match self {
+            Verifiers::Postgres(store) => store.delete_expired().await,
+            Verifiers::Memory(store) => store.delete_expired().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_verifier(csrf_token: &str) -> NewVerifier<'_> {
+        NewVerifier {
+            csrf_token,
+            pkce_verifier: "pkce",
+            nonce: "nonce",
+            link_code: "",
+            return_to: "",
+        }
+    }
+
+    // Move the verifier back in time, as if the login had started `age` ago
+    fn age(store: &MemoryVerifierStore, csrf_token: &str, age: time::Duration) {
+        let mut verifiers = store.verifiers.lock().unwrap();
+        verifiers.get_mut(csrf_token).unwrap().created -= age;
+    }
+
+    #[tokio::test]
+    async fn take_returns_the_verifier_once() {
+        let store = MemoryVerifierStore::default();
+        store.create(new_verifier("state")).await.unwrap();
+        let verifier = store.take("state").await.unwrap().unwrap();
+        assert_eq!(verifier.pkce_verifier, "pkce");
+        assert_eq!(verifier.nonce, "nonce");
+        assert!(store.take("state").await.unwrap().is_none());
+        assert!(store.take("unknown").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn expired_verifiers_are_not_counted_and_deleted() {
+        let store = MemoryVerifierStore::default();
+        store.create(new_verifier("expired")).await.unwrap();
+        store.create(new_verifier("fresh")).await.unwrap();
+        age(&store, "expired", VERIFIER_TTL + time::Duration::seconds(1));
+        age(&store, "fresh", VERIFIER_TTL - time::Duration::seconds(1));
+        assert_eq!(store.count().await.unwrap(), 1);
+
+        store.delete_expired().await.unwrap();
+        assert!(store.take("expired").await.unwrap().is_none());
+        assert!(store.take("fresh").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn expired_verifier_is_returned_until_deleted() {
+        // The callback tells an expired login from an unknown one
+        let store = MemoryVerifierStore::default();
+        store.create(new_verifier("state")).await.unwrap();
+        age(&store, "state", VERIFIER_TTL + time::Duration::seconds(1));
+        let verifier = store.take("state").await.unwrap().unwrap();
+        assert!(verifier.created + VERIFIER_TTL < OffsetDateTime::now_utc());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_takes_have_one_winner() {
+        let store = Arc::new(MemoryVerifierStore::default());
+        store.create(new_verifier("state")).await.unwrap();
+        let takes = (0..32)
+            .map(|_| {
+                let store = store.clone();
+                tokio::spawn(async move { store.take("state").await.unwrap() })
+            })
+            .collect::<Vec<_>>();
+        let mut winners = 0;
+        for take in takes {
+            if take.await.unwrap().is_some() {
+                winners += 1;
+            }
+        }
+        assert_eq!(winners, 1);
+    }
+}
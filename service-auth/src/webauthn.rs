@@ -0,0 +1,479 @@
+use anyhow::{anyhow, bail, Context, Result};
+use ciborium::value::Value;
+use ring::signature;
+use sha2::{Digest, Sha256};
+
+// The parts of WebAuthn needed for passkeys. Attestation is not requested (`none`), so
+// registrations only check the authenticator data, and assertions their signature.
+
+// COSE algorithms offered to the authenticator, in order of preference
+pub const ES256: i64 = -7;
+pub const EDDSA: i64 = -8;
+pub const RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+#[derive(serde::Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+/**
+ * Check the client data of the ceremony, and return its hash for the signature.
+ */
+pub fn verify_client_data(
+    client_data_json: &[u8],
+    kind: &str,
+    challenge: &str,
+    origin: &str,
+) -> Result<[u8; 32]> {
+    let client_data: ClientData =
+        serde_json::from_slice(client_data_json).context("Invalid client data")?;
+    if client_data.kind != kind {
+        bail!("Unexpected ceremony type: {}", client_data.kind);
+    }
+    if client_data.challenge != challenge {
+        bail!("Challenge mismatch");
+    }
+    if client_data.origin != origin {
+        bail!("Unexpected origin: {}", client_data.origin);
+    }
+    Ok(Sha256::digest(client_data_json).into())
+}
+
+pub struct AttestedCredential {
+    pub id: Vec<u8>,
+    // COSE encoded, as stored by service-users
+    pub public_key: Vec<u8>,
+}
+
+pub struct AuthenticatorData {
+    pub user_verified: bool,
+    pub sign_count: u32,
+    // Only set by registrations
+    pub credential: Option<AttestedCredential>,
+}
+
+pub fn parse_authenticator_data(data: &[u8], rp_id: &str) -> Result<AuthenticatorData> {
+    if data.len() < 37 {
+        bail!("Authenticator data is too short");
+    }
+    if data[..32] != Sha256::digest(rp_id.as_bytes())[..] {
+        bail!("RP ID hash mismatch");
+    }
+    let flags = data[32];
+    if flags & FLAG_USER_PRESENT == 0 {
+        bail!("User is not present");
+    }
+    let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+    let mut credential = None;
+    if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        // 16 bytes of AAGUID, then the length of the credential id
+        let rest = data.get(37..).unwrap_or_default();
+        if rest.len() < 18 {
+            bail!("Attested credential data is too short");
+        }
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if rest.len() < id_len {
+            bail!("Credential id is too short");
+        }
+        let (id, key) = rest.split_at(id_len);
+        // The key is followed by the extensions, so read it to know its length
+        let mut remaining = key;
+        let _: Value = ciborium::de::from_reader(&mut remaining).context("Invalid COSE key")?;
+        let public_key = key[..key.len() - remaining.len()].to_vec();
+        credential = Some(AttestedCredential {
+            id: id.to_vec(),
+            public_key,
+        });
+    }
+
+    Ok(AuthenticatorData {
+        user_verified: flags & FLAG_USER_VERIFIED != 0,
+        sign_count,
+        credential,
+    })
+}
+
+/**
+ * The authenticator data from the attestation object of a registration.
+ */
+pub fn parse_attestation_object(attestation_object: &[u8]) -> Result<Vec<u8>> {
+    let value: Value =
+        ciborium::de::from_reader(attestation_object).context("Invalid attestation object")?;
+    let map = value.as_map().context("Attestation object is not a map")?;
+    map.iter()
+        .find(|(key, _)| key.as_text() == Some("authData"))
+        .and_then(|(_, value)| value.as_bytes())
+        .cloned()
+        .context("Attestation object has no authData")
+}
+
+enum PublicKey {
+    Es256(Vec<u8>),
+    EdDsa(Vec<u8>),
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+fn parse_public_key(cose: &[u8]) -> Result<PublicKey> {
+    let value: Value = ciborium::de::from_reader(cose).context("Invalid COSE key")?;
+    let map = value.as_map().context("COSE key is not a map")?;
+    let get = |label: i64| {
+        map.iter()
+            .find(|(key, _)| key.as_integer() == Some(label.into()))
+            .map(|(_, value)| value)
+    };
+    let int = |label: i64| {
+        get(label)
+            .and_then(Value::as_integer)
+            .and_then(|value| i64::try_from(value).ok())
+    };
+    let bytes = |label: i64| {
+        get(label)
+            .and_then(Value::as_bytes)
+            .cloned()
+            .with_context(|| format!("COSE key has no parameter {}", label))
+    };
+
+    // 1 is the key type, 3 the algorithm, -1 the curve (or the RSA modulus)
+    match (int(1), int(3)) {
+        (Some(2), Some(ES256)) if int(-1) == Some(1) => {
+            let (x, y) = (bytes(-2)?, bytes(-3)?);
+            if x.len() != 32 || y.len() != 32 {
+                bail!("Invalid P-256 key");
+            }
+            // Uncompressed point
+            Ok(PublicKey::Es256([&[0x04], &x[..], &y[..]].concat()))
+        }
+        (Some(1), Some(EDDSA)) if int(-1) == Some(6) => Ok(PublicKey::EdDsa(bytes(-2)?)),
+        (Some(3), Some(RS256)) => Ok(PublicKey::Rs256 {
+            n: bytes(-1)?,
+            e: bytes(-2)?,
+        }),
+        (kty, alg) => bail!("Unsupported COSE key: kty {:?}, alg {:?}", kty, alg),
+    }
+}
+
+/**
+ * Registrations only accept keys that assertions can be verified with.
+ */
+pub fn check_public_key(cose: &[u8]) -> Result<()> {
+    parse_public_key(cose).map(|_| ())
+}
+
+/**
+ * The signature of an assertion is over the authenticator data and the client data hash.
+ */
+pub fn verify_signature(
+    cose: &[u8],
+    authenticator_data: &[u8],
+    client_data_hash: &[u8; 32],
+    sig: &[u8],
+) -> Result<()> {
+    let message = [authenticator_data, &client_data_hash[..]].concat();
+    match parse_public_key(cose)? {
+        PublicKey::Es256(key) => {
+            signature::UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, key)
+                .verify(&message, sig)
+        }
+        PublicKey::EdDsa(key) => {
+            signature::UnparsedPublicKey::new(&signature::ED25519, key).verify(&message, sig)
+        }
+        PublicKey::Rs256 { n, e } => signature::RsaPublicKeyComponents { n, e }.verify(
+            &signature::RSA_PKCS1_2048_8192_SHA256,
+            &message,
+            sig,
+        ),
+    }
+    .map_err(|_| anyhow!("Invalid signature"))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, Ed25519KeyPair, KeyPair};
+
+    pub const RP_ID: &str = "localhost";
+    pub const ORIGIN: &str = "http://localhost:3000";
+    pub const CHALLENGE: &str = "challenge";
+
+    enum Key {
+        Es256(EcdsaKeyPair),
+        EdDsa(Ed25519KeyPair),
+    }
+
+    /**
+     * A software authenticator, it signs the same payloads as a platform one.
+     */
+    pub struct SoftAuthenticator {
+        key: Key,
+        pub credential_id: Vec<u8>,
+        pub rp_id: String,
+        pub flags: u8,
+        pub sign_count: u32,
+    }
+
+    impl SoftAuthenticator {
+        pub fn es256() -> Self {
+            let rng = SystemRandom::new();
+            let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+            let key = EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref()).unwrap();
+            Self::new(Key::Es256(key))
+        }
+
+        pub fn eddsa() -> Self {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+            let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+            Self::new(Key::EdDsa(key))
+        }
+
+        fn new(key: Key) -> Self {
+            SoftAuthenticator {
+                key,
+                credential_id: vec![7; 16],
+                rp_id: RP_ID.to_string(),
+                flags: FLAG_USER_PRESENT | FLAG_USER_VERIFIED,
+                sign_count: 1,
+            }
+        }
+
+        pub fn cose_key(&self) -> Vec<u8> {
+            let int = |value: i64| Value::Integer(value.into());
+            let map = match &self.key {
+                Key::Es256(key) => {
+                    let point = key.public_key().as_ref();
+                    vec![
+                        (int(1), int(2)),
+                        (int(3), int(ES256)),
+                        (int(-1), int(1)),
+                        (int(-2), Value::Bytes(point[1..33].to_vec())),
+                        (int(-3), Value::Bytes(point[33..].to_vec())),
+                    ]
+                }
+                Key::EdDsa(key) => vec![
+                    (int(1), int(1)),
+                    (int(3), int(EDDSA)),
+                    (int(-1), int(6)),
+                    (int(-2), Value::Bytes(key.public_key().as_ref().to_vec())),
+                ],
+            };
+            let mut cose = Vec::new();
+            ciborium::ser::into_writer(&Value::Map(map), &mut cose).unwrap();
+            cose
+        }
+
+        pub fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+            serde_json::json!({
+                "type": kind,
+                "challenge": challenge,
+                "origin": origin,
+                "crossOrigin": false,
+            })
+            .to_string()
+            .into_bytes()
+        }
+
+        pub fn authenticator_data(&self, attested: bool) -> Vec<u8> {
+            let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+            let flags = if attested {
+                self.flags | FLAG_ATTESTED_CREDENTIAL
+            } else {
+                self.flags
+            };
+            data.push(flags);
+            data.extend_from_slice(&self.sign_count.to_be_bytes());
+            if attested {
+                // No AAGUID, like most passkey providers without attestation
+                data.extend_from_slice(&[0; 16]);
+                data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+                data.extend_from_slice(&self.credential_id);
+                data.extend_from_slice(&self.cose_key());
+            }
+            data
+        }
+
+        pub fn attestation_object(&self) -> Vec<u8> {
+            let object = Value::Map(vec![
+                (Value::Text("fmt".into()), Value::Text("none".into())),
+                (Value::Text("attStmt".into()), Value::Map(Vec::new())),
+                (
+                    Value::Text("authData".into()),
+                    Value::Bytes(self.authenticator_data(true)),
+                ),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&object, &mut bytes).unwrap();
+            bytes
+        }
+
+        pub fn sign(&self, authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+            let message = [authenticator_data, &Sha256::digest(client_data_json)[..]].concat();
+            match &self.key {
+                Key::Es256(key) => key
+                    .sign(&SystemRandom::new(), &message)
+                    .unwrap()
+                    .as_ref()
+                    .to_vec(),
+                Key::EdDsa(key) => key.sign(&message).as_ref().to_vec(),
+            }
+        }
+    }
+
+    fn verify(
+        authenticator: &SoftAuthenticator,
+        sig: Option<Vec<u8>>,
+    ) -> Result<AuthenticatorData> {
+        let client_data = SoftAuthenticator::client_data("webauthn.get", CHALLENGE, ORIGIN);
+        let data = authenticator.authenticator_data(false);
+        let sig = sig.unwrap_or_else(|| authenticator.sign(&data, &client_data));
+        let hash = verify_client_data(&client_data, "webauthn.get", CHALLENGE, ORIGIN)?;
+        let parsed = parse_authenticator_data(&data, RP_ID)?;
+        verify_signature(&authenticator.cose_key(), &data, &hash, &sig)?;
+        Ok(parsed)
+    }
+
+    #[test]
+    fn es256_assertion_is_verified() {
+        let parsed = verify(&SoftAuthenticator::es256(), None).unwrap();
+        assert!(parsed.user_verified);
+        assert_eq!(parsed.sign_count, 1);
+        assert!(parsed.credential.is_none());
+    }
+
+    #[test]
+    fn eddsa_assertion_is_verified() {
+        assert!(verify(&SoftAuthenticator::eddsa(), None).is_ok());
+    }
+
+    #[test]
+    fn rp_id_hash_mismatch_is_rejected() {
+        let mut authenticator = SoftAuthenticator::es256();
+        authenticator.rp_id = "evil.example".to_string();
+        let err = verify(&authenticator, None).err().unwrap();
+        assert_eq!(err.to_string(), "RP ID hash mismatch");
+    }
+
+    #[test]
+    fn user_presence_is_required() {
+        let mut authenticator = SoftAuthenticator::es256();
+        authenticator.flags = FLAG_USER_VERIFIED;
+        let err = verify(&authenticator, None).err().unwrap();
+        assert_eq!(err.to_string(), "User is not present");
+    }
+
+    #[test]
+    fn user_verification_is_reported() {
+        let mut authenticator = SoftAuthenticator::es256();
+        authenticator.flags = FLAG_USER_PRESENT;
+        assert!(!verify(&authenticator, None).unwrap().user_verified);
+    }
+
+    #[test]
+    fn sign_count_is_read() {
+        let mut authenticator = SoftAuthenticator::es256();
+        authenticator.sign_count = 0x01020304;
+        assert_eq!(verify(&authenticator, None).unwrap().sign_count, 0x01020304);
+    }
+
+    #[test]
+    fn wrong_client_data_is_rejected() {
+        let check = |kind, challenge, origin| {
+            let client_data = SoftAuthenticator::client_data(kind, challenge, origin);
+            verify_client_data(&client_data, "webauthn.get", CHALLENGE, ORIGIN)
+                .err()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            check("webauthn.create", CHALLENGE, ORIGIN),
+            "Unexpected ceremony type: webauthn.create"
+        );
+        assert_eq!(check("webauthn.get", "other", ORIGIN), "Challenge mismatch");
+        assert_eq!(
+            check("webauthn.get", CHALLENGE, "https://evil.example"),
+            "Unexpected origin: https://evil.example"
+        );
+        assert!(verify_client_data(b"{}", "webauthn.get", CHALLENGE, ORIGIN).is_err());
+    }
+
+    #[test]
+    fn bad_signature_is_rejected() {
+        let authenticator = SoftAuthenticator::es256();
+        let client_data = SoftAuthenticator::client_data("webauthn.get", CHALLENGE, ORIGIN);
+        let data = authenticator.authenticator_data(false);
+
+        let mut sig = authenticator.sign(&data, &client_data);
+        let last = sig.len() - 1;
+        sig[last] ^= 0x01;
+        let err = verify(&authenticator, Some(sig)).err().unwrap();
+        assert_eq!(err.to_string(), "Invalid signature");
+
+        // Signed by another credential
+        let sig = SoftAuthenticator::es256().sign(&data, &client_data);
+        assert!(verify(&authenticator, Some(sig)).is_err());
+
+        // The counter was changed after signing
+        let sig = authenticator.sign(&data, &client_data);
+        let mut tampered = data.clone();
+        tampered[36] += 1;
+        let hash = verify_client_data(&client_data, "webauthn.get", CHALLENGE, ORIGIN).unwrap();
+        let cose = authenticator.cose_key();
+        assert!(verify_signature(&cose, &tampered, &hash, &sig).is_err());
+    }
+
+    #[test]
+    fn attested_credential_is_parsed() {
+        let authenticator = SoftAuthenticator::eddsa();
+        let object = authenticator.attestation_object();
+        let data = parse_attestation_object(&object).unwrap();
+        let parsed = parse_authenticator_data(&data, RP_ID).unwrap();
+        let credential = parsed.credential.unwrap();
+        assert_eq!(credential.id, authenticator.credential_id);
+        assert_eq!(credential.public_key, authenticator.cose_key());
+        assert!(check_public_key(&credential.public_key).is_ok());
+    }
+
+    #[test]
+    fn extensions_after_the_key_are_skipped() {
+        let authenticator = SoftAuthenticator::es256();
+        let mut data = authenticator.authenticator_data(true);
+        data[32] |= 0x80;
+        let extensions = Value::Map(vec![(Value::Text("credProtect".into()), 2.into())]);
+        ciborium::ser::into_writer(&extensions, &mut data).unwrap();
+        let parsed = parse_authenticator_data(&data, RP_ID).unwrap();
+        assert_eq!(
+            parsed.credential.unwrap().public_key,
+            authenticator.cose_key()
+        );
+    }
+
+    #[test]
+    fn truncated_authenticator_data_is_rejected() {
+        let data = SoftAuthenticator::es256().authenticator_data(true);
+        assert!(parse_authenticator_data(&data[..36], RP_ID).is_err());
+        assert!(parse_authenticator_data(&data[..60], RP_ID).is_err());
+    }
+
+    #[test]
+    fn unsupported_key_is_rejected() {
+        let int = |value: i64| Value::Integer(value.into());
+        // ES384
+        let key = Value::Map(vec![
+            (int(1), int(2)),
+            (int(3), int(-35)),
+            (int(-1), int(2)),
+        ]);
+        let mut cose = Vec::new();
+        ciborium::ser::into_writer(&key, &mut cose).unwrap();
+        assert!(check_public_key(&cose).is_err());
+    }
+}
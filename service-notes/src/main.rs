@@ -1,3 +1,4 @@
+mod health;
 mod migrations;
 mod note_db;
 mod note_service;
@@ -21,6 +22,9 @@ async fn main() -> Result<()> {
     let filter = &env.rust_log;
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
+    // Reload the JWT keys periodically, so they can be rotated without a restart
+    env.jwt_keys.spawn_reload();
+
     // Connect to database
     let pool = service_notes::connect_to_db(&env).context("Failed to connect to database")?;
     tracing::info!("Connected to database");
@@ -34,9 +38,13 @@ async fn main() -> Result<()> {
     // Run gRPC server
     let addr = format!("[::]:{}", env.port).parse()?;
     tracing::info!("gRPC server started on port: {:?}", env.port);
+    // Standard gRPC health checks, the service is ready once its dependencies answer
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health::spawn_readiness(health_reporter, pool.clone());
     let server = MyService { pool, env };
     let svc = NotesServiceServer::new(server);
     tonic::transport::Server::builder()
+        .add_service(health_service)
         .add_service(svc)
         .serve(addr)
         .await
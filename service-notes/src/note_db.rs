@@ -121,3 +121,31 @@ pub async fn delete_note_by_id(conn: &Object, id: &str, user_id: &str) -> Result
     let note = Note::try_from(res)?;
     Ok(note)
 }
+
+/**
+ * Every note of the user, for the data export.
+ */
+pub async fn select_all_notes_by_user_id(conn: &Object, user_id: &str) -> Result<Vec<Note>> {
+    let user_id = Uuid::parse_str(user_id)?;
+    let rows = conn
+        .query(
+            "select * from notes where user_id = $1 and deleted = 'infinity' order by created",
+            &[&user_id],
+        )
+        .await?;
+    rows.into_iter().map(Note::try_from).collect()
+}
+
+/**
+ * Every note of a deleted account, marked deleted first and deleted for good after the grace period.
+ */
+pub async fn delete_notes_by_user_id(conn: &Object, user_id: &str, purge: bool) -> Result<u64> {
+    let user_id = Uuid::parse_str(user_id)?;
+    let query = if purge {
+        "delete from notes where user_id = $1"
+    } else {
+        "update notes set deleted = now() where user_id = $1 and deleted = 'infinity'"
+    };
+    let count = conn.execute(query, &[&user_id]).await?;
+    Ok(count)
+}
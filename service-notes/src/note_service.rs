@@ -1,12 +1,13 @@
 use crate::{
     proto::{
-        notes_service_server::NotesService, users_service_client::UsersServiceClient, Count, Empty,
-        Id, Note, NoteResponse, Page,
+        notes_service_server::NotesService, users_service_client::UsersServiceClient, Count,
+        DataExportPart, Empty, Id, Ids, Note, NoteResponse, Page, UserDataDeletion,
     },
     MyService,
 };
 use anyhow::Result;
 use futures_util::TryStreamExt;
+use std::collections::HashMap;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
@@ -21,7 +22,7 @@ impl NotesService for MyService {
     ) -> Result<Response<Count>, Status> {
         let start = std::time::Instant::now();
         let metadata = request.metadata();
-        let user_id = service_notes::auth(metadata, &self.env.jwt_secret)?.id;
+        let user_id = service_notes::auth(metadata, &self.env.jwt_keys)?.id;
 
         let conn = self.pool.get().await.map_err(|e| {
             tracing::error!("Failed to get connection: {:?}", e);
@@ -45,7 +46,7 @@ impl NotesService for MyService {
     ) -> Result<Response<Self::GetNotesByUserIdStream>, Status> {
         let start = std::time::Instant::now();
         let metadata = request.metadata();
-        let user_id = service_notes::auth(metadata, &self.env.jwt_secret)?.id;
+        let user_id = service_notes::auth(metadata, &self.env.jwt_keys)?.id;
 
         let conn = self.pool.get().await.map_err(|e| {
             tracing::error!("Failed to get connection: {:?}", e);
@@ -61,104 +62,64 @@ impl NotesService for MyService {
                     Status::internal("Failed to get notes")
                 })?;
 
-        let jwt_token =
-            service_notes::generate_jwt_token(&self.env.jwt_secret, &user_id).map_err(|err| {
-                tracing::error!("Failed to generate jwt token: {:?}", err);
-                Status::internal("Failed to generate jwt token")
+        let notes: Vec<Note> = notes_stream
+            .map_err(anyhow::Error::from)
+            .and_then(|note| async { Note::try_from(note) })
+            .try_collect()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get notes: {:?}", e);
+                Status::internal("Failed to get notes")
             })?;
 
-        let client = match UsersServiceClient::connect(self.env.users_url.to_owned()).await {
-            Ok(client) => client,
-            Err(e) => {
-                tracing::error!("Failed to connect to users service: {:?}", e);
-                return Err(Status::internal("Failed to connect to users service"));
+        // One call for the profiles of all the authors, instead of one per note
+        let mut user_ids: Vec<String> = notes.iter().map(|note| note.user_id.clone()).collect();
+        user_ids.sort();
+        user_ids.dedup();
+        let mut profiles = HashMap::new();
+        if !user_ids.is_empty() {
+            let token = service_notes::generate_service_token(&self.env.jwt_keys).map_err(|e| {
+                tracing::error!("Failed to generate service token: {:?}", e);
+                Status::internal("Failed to generate service token")
+            })?;
+            let mut request = Request::new(Ids { ids: user_ids });
+            request.metadata_mut().insert("x-authorization", token);
+            let mut client = UsersServiceClient::connect(self.env.users_url.to_owned())
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to connect to users service: {:?}", e);
+                    Status::internal("Failed to connect to users service")
+                })?;
+            let mut stream = client
+                .get_profiles_by_user_ids(request)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to get profiles: {:?}", e);
+                    Status::internal("Failed to get profiles")
+                })?
+                .into_inner();
+            while let Some(profile) = stream.message().await.map_err(|e| {
+                tracing::error!("Failed to get profile: {:?}", e);
+                Status::internal("Failed to get profile")
+            })? {
+                profiles.insert(profile.user_id.clone(), profile);
             }
-        };
-        let (tx, rx) = mpsc::channel(128);
-
-        struct SharedData {
-            tx: mpsc::Sender<Result<NoteResponse, Status>>,
-            jwt_token: tonic::metadata::MetadataValue<tonic::metadata::Ascii>,
-            client: UsersServiceClient<tonic::transport::Channel>,
         }
-        let shared_data = std::sync::Arc::new(SharedData {
-            tx,
-            jwt_token,
-            client,
-        });
+
+        let (tx, rx) = mpsc::channel(128);
         tokio::spawn(async move {
-            futures_util::pin_mut!(notes_stream);
-            while let Ok(Some(note)) = notes_stream.try_next().await {
-                let shared_data = shared_data.clone();
-
-                // Totally overengineered way to get notes, and for each note get user profile
-                // As soon as we get note from db, we spawn async block
-                // Each note is handled in separate task, so we can get user profile for each note in parallel
-                // Notes service authorizes request to users service using jwt token
-                tokio::spawn(async move {
-                    let note: Note = match note.try_into() {
-                        Ok(note) => note,
-                        Err(e) => {
-                            tracing::error!("Failed to get note: {:?}", e);
-                            return;
-                        }
-                    };
-                    let mut request = tonic::Request::new(crate::proto::Empty {});
-                    let metadata = request.metadata_mut();
-                    metadata.insert("x-authorization", shared_data.jwt_token.to_owned());
-                    let user_profile = match shared_data
-                        .client
-                        .clone()
-                        .get_profile_by_user_id(request)
-                        .await
-                    {
-                        Ok(response) => response.into_inner(),
-                        Err(e) => {
-                            tracing::error!("Failed to get user profile: {:?}", e);
-                            return;
-                        }
-                    };
-
-                    let note_response = NoteResponse {
-                        note: Some(note),
-                        profile: Some(user_profile),
-                    };
-                    if let Err(e) = shared_data.tx.send(Ok(note_response)).await {
-                        tracing::error!("Failed to send note: {:?}", e);
-                    }
-                });
+            for note in notes {
+                // Users without a profile yet get an empty one, as before
+                let profile = profiles.get(&note.user_id).cloned().unwrap_or_default();
+                let note_response = NoteResponse {
+                    note: Some(note),
+                    profile: Some(profile),
+                };
+                if let Err(e) = tx.send(Ok(note_response)).await {
+                    tracing::error!("Failed to send note: {:?}", e);
+                    return;
+                }
             }
-
-            // Another way to get notes
-            // loop {
-            //     let note = match notes_stream.try_next().await {
-            //         Ok(Some(note)) => note,
-            //         Ok(None) => break,
-            //         Err(e) => {
-            //             tracing::error!("Failed to get note: {:?}", e);
-            //             if let Err(e) = tx.send(Err(Status::internal("Failed to get note"))).await {
-            //                 tracing::error!("Failed to send error: {:?}", e);
-            //             }
-            //             break;
-            //         }
-            //     };
-            //     let note: Note = match note.try_into() {
-            //         Ok(note) => note,
-            //         Err(e) => {
-            //             tracing::error!("Failed to convert note: {:?}", e);
-            //             if let Err(e) = tx
-            //                 .send(Err(Status::internal("Failed to convert note")))
-            //                 .await
-            //             {
-            //                 tracing::error!("Failed to send error: {:?}", e);
-            //             }
-            //             return;
-            //         }
-            //     };
-            //     if let Err(e) = tx.send(Ok(note)).await {
-            //         tracing::error!("Failed to send note: {:?}", e);
-            //     }
-            // }
             tracing::info!("get_notes_by_user_id: {:?}", start.elapsed());
         });
         Ok(Response::new(ReceiverStream::new(rx)))
@@ -167,7 +128,7 @@ impl NotesService for MyService {
     async fn get_note_by_id(&self, request: Request<Id>) -> Result<Response<Note>, Status> {
         let start = std::time::Instant::now();
         let metadata = request.metadata();
-        let user_id = service_notes::auth(metadata, &self.env.jwt_secret)?.id;
+        let user_id = service_notes::auth(metadata, &self.env.jwt_keys)?.id;
 
         let conn = self.pool.get().await.map_err(|e| {
             tracing::error!("Failed to get connection: {:?}", e);
@@ -189,7 +150,7 @@ impl NotesService for MyService {
     async fn create_note(&self, request: Request<Note>) -> Result<Response<Note>, Status> {
         let start = std::time::Instant::now();
         let metadata = request.metadata();
-        let user_id = service_notes::auth(metadata, &self.env.jwt_secret)?.id;
+        let user_id = service_notes::auth(metadata, &self.env.jwt_keys)?.id;
 
         let mut note = request.into_inner();
         crate::note_validation::Validation::validate(&note)?;
@@ -222,7 +183,7 @@ impl NotesService for MyService {
     async fn delete_note_by_id(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
         let start = std::time::Instant::now();
         let metadata = request.metadata();
-        let user_id = service_notes::auth(metadata, &self.env.jwt_secret)?.id;
+        let user_id = service_notes::auth(metadata, &self.env.jwt_keys)?.id;
 
         let conn = self.pool.get().await.map_err(|e| {
             tracing::error!("Failed to get connection: {:?}", e);
@@ -240,4 +201,80 @@ impl NotesService for MyService {
         tracing::info!("delete_note: {:?}", start.elapsed());
         return Ok(Response::new(Empty {}));
     }
+
+    async fn delete_user_data(
+        &self,
+        request: Request<UserDataDeletion>,
+    ) -> Result<Response<Empty>, Status> {
+        let start = std::time::Instant::now();
+        let metadata = request.metadata();
+        service_notes::decode_service_token(metadata, &self.env.jwt_keys, "users")?;
+
+        let conn = self.pool.get().await.map_err(|e| {
+            tracing::error!("Failed to get connection: {:?}", e);
+            Status::internal("Failed to get connection")
+        })?;
+
+        // Called again until it succeeds, so it has to be idempotent
+        let deletion = request.into_inner();
+        let count =
+            crate::note_db::delete_notes_by_user_id(&conn, &deletion.user_id, deletion.purge)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to delete notes: {:?}", e);
+                    Status::internal("Failed to delete notes")
+                })?;
+
+        tracing::info!(
+            "delete_user_data: {} notes, purge {}, {:?}",
+            count,
+            deletion.purge,
+            start.elapsed()
+        );
+        return Ok(Response::new(Empty {}));
+    }
+
+    async fn export_user_data(
+        &self,
+        request: Request<Id>,
+    ) -> Result<Response<DataExportPart>, Status> {
+        let start = std::time::Instant::now();
+        let metadata = request.metadata();
+        service_notes::decode_service_token(metadata, &self.env.jwt_keys, "users")?;
+
+        let conn = self.pool.get().await.map_err(|e| {
+            tracing::error!("Failed to get connection: {:?}", e);
+            Status::internal("Failed to get connection")
+        })?;
+
+        let user_id = request.into_inner().id;
+        let notes = crate::note_db::select_all_notes_by_user_id(&conn, &user_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get notes: {:?}", e);
+                Status::internal("Failed to get notes")
+            })?;
+        let notes: Vec<serde_json::Value> = notes
+            .into_iter()
+            .map(|note| {
+                serde_json::json!({
+                    "id": note.id,
+                    "created": note.created,
+                    "updated": note.updated,
+                    "title": note.title,
+                    "content": note.content,
+                })
+            })
+            .collect();
+        let content = serde_json::to_vec_pretty(&notes).map_err(|e| {
+            tracing::error!("Failed to serialize notes: {:?}", e);
+            Status::internal("Failed to serialize notes")
+        })?;
+
+        tracing::info!("export_user_data: {:?}", start.elapsed());
+        return Ok(Response::new(DataExportPart {
+            name: "notes.json".to_string(),
+            content,
+        }));
+    }
 }
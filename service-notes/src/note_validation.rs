@@ -5,11 +5,11 @@ pub struct Validator<'a> {
 }
 
 pub trait Validation {
-    fn validate(&self) -> Result<(), tonic::Status>;
+    fn validate(&self) -> Result<(), service_notes::RequestError>;
 }
 
 impl Validation for crate::proto::Note {
-    fn validate(&self) -> Result<(), tonic::Status> {
+    fn validate(&self) -> Result<(), service_notes::RequestError> {
         let mut validators = Vec::new();
         if self.title.is_empty() {
             validators.push(Validator {
@@ -40,10 +40,12 @@ impl Validation for crate::proto::Note {
         } else {
             let json = serde_json::to_string(&validators);
             match json {
-                Ok(json) => Err(tonic::Status::invalid_argument(json)),
+                Ok(json) => Err(service_notes::RequestError::InvalidArgument(json)),
                 Err(e) => {
                     tracing::error!("Failed to serialize validators: {:?}", e);
-                    Err(tonic::Status::internal("Failed to serialize validators"))
+                    Err(service_notes::RequestError::Internal(
+                        "Failed to serialize validators",
+                    ))
                 }
             }
         }
@@ -0,0 +1,356 @@
+use service_users::RequestError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::proto::{AuditEventType, Permission, User, UserDetails, UserFilter, UserRoleChange};
+
+/**
+ * Every RPC here needs `MANAGE_USERS`. Returns the id of the admin.
+ */
+async fn authorize(
+    env: &service_users::Env,
+    conn: &deadpool_postgres::Object,
+    metadata: &tonic::metadata::MetadataMap,
+) -> Result<String, Status> {
+    let claims = crate::authz::authorize(env, conn, metadata, Permission::ManageUsers).await?;
+    Ok(claims.id)
+}
+
+fn parse_user_id(user_id: &str) -> Result<Uuid, RequestError> {
+    Uuid::parse_str(user_id).map_err(|_| RequestError::InvalidArgument("Invalid user id".into()))
+}
+
+/**
+ * Admins don't change their own role or delete themselves, so there is always one left.
+ */
+fn reject_self(admin_id: &str, user_id: &Uuid) -> Result<(), RequestError> {
+    if admin_id == user_id.to_string() {
+        return Err(RequestError::FailedPrecondition(
+            "You can't do this to your own account",
+        ));
+    }
+    Ok(())
+}
+
+pub async fn count_users(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<UserFilter>,
+) -> Result<Response<crate::proto::Count>, Status> {
+    let start = std::time::Instant::now();
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    authorize(env, &conn, request.metadata()).await?;
+
+    let filter = request.into_inner();
+    let count = crate::user_db::count_users(&conn, filter.search.trim(), filter.deleted)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to count users: {:?}", e);
+            Status::internal("Failed to count users")
+        })?;
+
+    tracing::info!("count_users: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Count { count }))
+}
+
+pub async fn get_users(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<UserFilter>,
+) -> Result<Response<ReceiverStream<Result<User, Status>>>, Status> {
+    let start = std::time::Instant::now();
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    authorize(env, &conn, request.metadata()).await?;
+
+    let filter = request.into_inner();
+    if filter.offset < 0 || filter.limit < 1 || filter.limit > 100 {
+        return Err(Status::invalid_argument("Invalid page"));
+    }
+
+    let users = crate::user_db::select_users(
+        &conn,
+        filter.search.trim(),
+        filter.deleted,
+        filter.offset,
+        filter.limit,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to get users: {:?}", e);
+        Status::internal("Failed to get users")
+    })?;
+
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for user in users {
+            if let Err(e) = tx.send(Ok(user)).await {
+                tracing::error!("Failed to send user: {:?}", e);
+                return;
+            }
+        }
+        tracing::info!("get_users: {:?}", start.elapsed());
+    });
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+/**
+ * The user, deleted or not, with the current subscription and two-factor state.
+ */
+pub async fn get_user_details(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<UserDetails>, Status> {
+    let start = std::time::Instant::now();
+    let user_id = parse_user_id(&request.get_ref().id)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    authorize(env, &conn, request.metadata()).await?;
+
+    let mut user = crate::user_db::select_any_user_by_id(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user: {:?}", e);
+            Status::internal("Failed to get user")
+        })?
+        .ok_or_else(|| Status::not_found("User not found"))?;
+
+    user.subscription_active = crate::stripe_service::check_subscription(env, &conn, &user)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update subscription: {:?}", e);
+            Status::internal("Failed to update subscription")
+        })?;
+    let (enabled, required) =
+        crate::two_factor_db::select_two_factor_status(&conn, &user.id, user.role)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get two-factor status: {:?}", e);
+                Status::internal("Failed to get two-factor status")
+            })?;
+    user.two_factor_enabled = enabled;
+    user.two_factor_required = required;
+
+    let profile = crate::profile_db::select_profile_by_user_id(&conn, &user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get profile: {:?}", e);
+            Status::internal("Failed to get profile")
+        })?;
+    let identities = crate::identity_db::select_identities_by_user_id(&conn, &user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get identities: {:?}", e);
+            Status::internal("Failed to get identities")
+        })?;
+    let sessions = crate::token_db::select_active_tokens_by_user_id(&conn, &user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get sessions: {:?}", e);
+            Status::internal("Failed to get sessions")
+        })?;
+
+    tracing::info!("get_user_details: {:?}", start.elapsed());
+    Ok(Response::new(UserDetails {
+        user: Some(user),
+        profile,
+        identities,
+        active_sessions: sessions.len() as i64,
+    }))
+}
+
+/**
+ * The user is signed out everywhere, so no session keeps the permissions of the old role.
+ */
+pub async fn set_user_role(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<UserRoleChange>,
+) -> Result<Response<User>, Status> {
+    let start = std::time::Instant::now();
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    let admin_id = authorize(env, &conn, request.metadata()).await?;
+
+    let (metadata, _, change) = request.into_parts();
+    let user_id = parse_user_id(&change.user_id)?;
+    reject_self(&admin_id, &user_id)?;
+    if matches!(
+        crate::proto::UserRole::from_i32(change.role),
+        None | Some(crate::proto::UserRole::Unset)
+    ) {
+        return Err(Status::invalid_argument("Invalid role"));
+    }
+
+    let user = crate::user_db::update_user_role(&conn, &user_id, change.role)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update role: {:?}", e);
+            Status::internal("Failed to update role")
+        })?
+        .ok_or_else(|| Status::not_found("User not found"))?;
+    crate::token_db::revoke_user_token_families(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke sessions: {:?}", e);
+            Status::internal("Failed to revoke sessions")
+        })?;
+    crate::audit_service::record(
+        &conn,
+        &metadata,
+        AuditEventType::UserRoleChanged,
+        Some(user_id),
+        "",
+        &format!("role {} by admin {}", change.role, admin_id),
+    )
+    .await;
+
+    tracing::info!("set_user_role: {:?}", start.elapsed());
+    Ok(Response::new(user))
+}
+
+/**
+ * Soft delete, and sign the user out. Their data is kept, so they can be restored.
+ */
+pub async fn delete_user(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let user_id = parse_user_id(&request.get_ref().id)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    let admin_id = authorize(env, &conn, request.metadata()).await?;
+    reject_self(&admin_id, &user_id)?;
+
+    let deleted = crate::user_db::soft_delete_user(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete user: {:?}", e);
+            Status::internal("Failed to delete user")
+        })?;
+    if !deleted {
+        return Err(Status::not_found("User not found"));
+    }
+    crate::token_db::revoke_user_token_families(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke sessions: {:?}", e);
+            Status::internal("Failed to revoke sessions")
+        })?;
+    crate::audit_service::record(
+        &conn,
+        request.metadata(),
+        AuditEventType::UserDeleted,
+        Some(user_id),
+        "",
+        &format!("admin {}", admin_id),
+    )
+    .await;
+
+    tracing::info!("delete_user: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+pub async fn restore_user(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let user_id = parse_user_id(&request.get_ref().id)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    let admin_id = authorize(env, &conn, request.metadata()).await?;
+
+    // The data of the user is already being deleted in the other services
+    let self_deleted = crate::deletion_db::account_deletion_exists(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get account deletion: {:?}", e);
+            Status::internal("Failed to get account deletion")
+        })?;
+    if self_deleted {
+        return Err(Status::failed_precondition(
+            "The user deleted their account",
+        ));
+    }
+    let restored = crate::user_db::restore_user(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to restore user: {:?}", e);
+            Status::internal("Failed to restore user")
+        })?;
+    if !restored {
+        return Err(Status::not_found("Deleted user not found"));
+    }
+    crate::audit_service::record(
+        &conn,
+        request.metadata(),
+        AuditEventType::UserRestored,
+        Some(user_id),
+        "",
+        &format!("admin {}", admin_id),
+    )
+    .await;
+
+    tracing::info!("restore_user: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * Their access tokens stop working on the next `Auth`.
+ */
+pub async fn revoke_user_sessions(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let user_id = parse_user_id(&request.get_ref().id)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    let admin_id = authorize(env, &conn, request.metadata()).await?;
+
+    crate::token_db::revoke_user_token_families(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke sessions: {:?}", e);
+            Status::internal("Failed to revoke sessions")
+        })?;
+    crate::audit_service::record(
+        &conn,
+        request.metadata(),
+        AuditEventType::SessionsRevoked,
+        Some(user_id),
+        "",
+        &format!("admin {}", admin_id),
+    )
+    .await;
+
+    tracing::info!("revoke_user_sessions: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
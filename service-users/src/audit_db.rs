@@ -0,0 +1,78 @@
+use anyhow::Result;
+use deadpool_postgres::Object;
+use time::format_description::well_known::Iso8601;
+use uuid::Uuid;
+
+use crate::proto::{AuditEvent, AuditEventType};
+
+impl TryFrom<tokio_postgres::Row> for AuditEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let id: Uuid = value.try_get("id")?;
+        let created: time::OffsetDateTime = value.try_get("created")?;
+        let created: String = created.format(&Iso8601::DEFAULT)?.to_string();
+        let user_id: Option<Uuid> = value.try_get("user_id")?;
+        let r#type: i32 = value.try_get("type")?;
+        let provider: String = value.try_get("provider")?;
+        let ip: String = value.try_get("ip")?;
+        let user_agent: String = value.try_get("user_agent")?;
+        let detail: String = value.try_get("detail")?;
+
+        Ok(AuditEvent {
+            id: id.to_string(),
+            created,
+            user_id: user_id.map(|id| id.to_string()).unwrap_or_default(),
+            r#type,
+            provider,
+            ip,
+            user_agent,
+            detail,
+        })
+    }
+}
+
+pub struct NewAuditEvent<'a> {
+    pub user_id: Option<Uuid>,
+    pub r#type: AuditEventType,
+    pub provider: &'a str,
+    pub ip: &'a str,
+    pub user_agent: &'a str,
+    pub detail: &'a str,
+}
+
+pub async fn insert_audit_event(conn: &Object, event: &NewAuditEvent<'_>) -> Result<()> {
+    conn.execute(
+        "insert into audit_events (id, user_id, type, provider, ip, user_agent, detail) values ($1, $2, $3, $4, $5, $6, $7)",
+        &[
+            &Uuid::now_v7(),
+            &event.user_id,
+            &(event.r#type as i32),
+            &event.provider,
+            &event.ip,
+            &event.user_agent,
+            &event.detail,
+        ],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * Newest first, `user_id` and `type` are only filtered on when set.
+ */
+pub async fn select_audit_events(
+    conn: &Object,
+    user_id: Option<Uuid>,
+    r#type: Option<i32>,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<AuditEvent>> {
+    let rows = conn
+        .query(
+            "select * from audit_events where ($1::uuid is null or user_id = $1) and ($2::int is null or type = $2) order by created desc offset $3 limit $4",
+            &[&user_id, &r#type, &offset, &limit],
+        )
+        .await?;
+    rows.into_iter().map(AuditEvent::try_from).collect()
+}
@@ -0,0 +1,127 @@
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::audit_db::NewAuditEvent;
+use crate::proto::{AuditEvent, AuditEventFilter, AuditEventType, Permission};
+
+/**
+ * Append an event to the audit log, with the IP and user agent forwarded in the metadata.
+ * A failure is only logged, it never fails the request being audited.
+ */
+pub async fn record(
+    conn: &deadpool_postgres::Object,
+    metadata: &tonic::metadata::MetadataMap,
+    r#type: AuditEventType,
+    user_id: Option<Uuid>,
+    provider: &str,
+    detail: &str,
+) {
+    let ip = crate::user_service::metadata_value(metadata, "x-client-ip", 64);
+    let user_agent = crate::user_service::metadata_value(metadata, "x-user-agent", 512);
+    let event = NewAuditEvent {
+        user_id,
+        r#type,
+        provider,
+        ip: &ip,
+        user_agent: &user_agent,
+        detail,
+    };
+    if let Err(e) = crate::audit_db::insert_audit_event(conn, &event).await {
+        tracing::error!("Failed to record audit event {:?}: {:?}", r#type, e);
+    }
+}
+
+/**
+ * A login that failed in service-auth, before reaching `CreateUser`.
+ */
+pub async fn create_audit_event(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let claims = service_users::decode_audit_token(request.metadata(), &env.jwt_keys)?;
+    // Everything else is recorded here, where it happens
+    if claims.r#type != AuditEventType::LoginFailure as i32 {
+        return Err(Status::invalid_argument("Invalid audit event type"));
+    }
+    let user_id = match claims.user_id.as_str() {
+        "" => None,
+        user_id => Some(
+            Uuid::parse_str(user_id).map_err(|_| Status::invalid_argument("Invalid user id"))?,
+        ),
+    };
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let event = NewAuditEvent {
+        user_id,
+        r#type: AuditEventType::LoginFailure,
+        provider: &claims.provider,
+        ip: &claims.ip,
+        user_agent: &claims.user_agent,
+        detail: &claims.detail,
+    };
+    crate::audit_db::insert_audit_event(&conn, &event)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to insert audit event: {:?}", e);
+            Status::internal("Failed to insert audit event")
+        })?;
+
+    tracing::info!("create_audit_event: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+pub async fn get_audit_events(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<AuditEventFilter>,
+) -> Result<Response<ReceiverStream<Result<AuditEvent, Status>>>, Status> {
+    let start = std::time::Instant::now();
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    crate::authz::authorize(env, &conn, request.metadata(), Permission::ReadAuditLog).await?;
+
+    let filter = request.into_inner();
+    let user_id = match filter.user_id.as_str() {
+        "" => None,
+        user_id => Some(
+            Uuid::parse_str(user_id).map_err(|_| Status::invalid_argument("Invalid user id"))?,
+        ),
+    };
+    let r#type = match filter.r#type {
+        0 => None,
+        r#type => Some(r#type),
+    };
+    if filter.offset < 0 || filter.limit < 1 || filter.limit > 100 {
+        return Err(Status::invalid_argument("Invalid page"));
+    }
+
+    let events =
+        crate::audit_db::select_audit_events(&conn, user_id, r#type, filter.offset, filter.limit)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get audit events: {:?}", e);
+                Status::internal("Failed to get audit events")
+            })?;
+
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for event in events {
+            if let Err(e) = tx.send(Ok(event)).await {
+                tracing::error!("Failed to send audit event: {:?}", e);
+                return;
+            }
+        }
+        tracing::info!("get_audit_events: {:?}", start.elapsed());
+    });
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
@@ -0,0 +1,84 @@
+use crate::proto::{Permission, User, UserRole};
+use crate::user_db::StringOrUuid;
+use service_users::AccessClaims;
+use tonic::Status;
+
+/**
+ * What each role may do. The permissions are put in the access token, and forwarded by the client
+ * to the other services, which check them with `Claims::require`.
+ * A change here applies to a session on its next refresh, at most 15 minutes later, except for
+ * the RPCs of this service, see `authorize`.
+ */
+pub fn role_permissions(role: i32) -> Vec<i32> {
+    match UserRole::from_i32(role) {
+        Some(UserRole::Admin) => vec![
+            Permission::ReadAuditLog as i32,
+            Permission::ImpersonateUsers as i32,
+            Permission::ManageRoles as i32,
+            Permission::ManageUsers as i32,
+        ],
+        Some(UserRole::User) | Some(UserRole::Unset) | None => Vec::new(),
+    }
+}
+
+/**
+ * The permissions put in the access token of the user. A role that requires two-factor grants
+ * nothing until it's set up, whatever the client does with the setup prompt.
+ */
+pub fn user_permissions(user: &User) -> Vec<i32> {
+    if user.two_factor_required && !user.two_factor_enabled {
+        tracing::warn!("Two-factor is required but not set up: {:?}", user.id);
+        return Vec::new();
+    }
+    role_permissions(user.role)
+}
+
+/**
+ * The permission check of the RPCs of this service. The access token can be 15 minutes old, so
+ * its session must still be active, and the current role of the user must still grant it.
+ */
+pub async fn authorize(
+    env: &service_users::Env,
+    conn: &deadpool_postgres::Object,
+    metadata: &tonic::metadata::MetadataMap,
+    permission: Permission,
+) -> Result<AccessClaims, Status> {
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    claims.require(permission as i32)?;
+
+    let active = crate::token_db::touch_token_family(conn, &claims.sid)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to auth session: {:?}", e);
+            Status::unauthenticated("Failed to auth session")
+        })?;
+    if !active {
+        tracing::error!("Session has been revoked: {:?}", claims.sid);
+        return Err(Status::unauthenticated("Unauthenticated"));
+    }
+
+    let mut user = crate::user_db::select_user_by_id(conn, StringOrUuid::String(claims.id.clone()))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to auth user: {:?}", e);
+            Status::unauthenticated("Failed to auth user")
+        })?;
+    let (enabled, required) =
+        crate::two_factor_db::select_two_factor_status(conn, &user.id, user.role)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get two-factor status: {:?}", e);
+                Status::internal("Failed to get two-factor status")
+            })?;
+    user.two_factor_enabled = enabled;
+    user.two_factor_required = required;
+    if !user_permissions(&user).contains(&(permission as i32)) {
+        tracing::error!(
+            "Permission {:?} no longer granted: {:?}",
+            permission,
+            user.id
+        );
+        return Err(Status::permission_denied("Permission denied"));
+    }
+    Ok(claims)
+}
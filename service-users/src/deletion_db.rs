@@ -0,0 +1,127 @@
+use anyhow::Result;
+use deadpool_postgres::Object;
+use uuid::Uuid;
+
+/**
+ * An account deleted by its user, waiting for the other services or for the end of the grace period.
+ */
+pub struct AccountDeletion {
+    pub user_id: Uuid,
+    pub propagated: bool,
+    pub purge_due: bool,
+}
+
+/**
+ * Soft delete the user and the profile, revoke the sessions and queue the deletion in the other
+ * services, all or nothing. Returns false if the user doesn't exist or is already deleted.
+ */
+pub async fn delete_account(conn: &mut Object, user_id: &Uuid) -> Result<bool> {
+    let tr = conn.transaction().await?;
+    let updated = tr
+        .execute(
+            "update users set deleted = now() where id = $1 and deleted = 'infinity'",
+            &[user_id],
+        )
+        .await?;
+    if updated == 0 {
+        return Ok(false);
+    }
+    tr.execute(
+        "update profiles set deleted = now() where user_id = $1 and deleted = 'infinity'",
+        &[user_id],
+    )
+    .await?;
+    tr.execute(
+        "update tokens set revoked = now() where user_id = $1 and revoked = 'infinity'",
+        &[user_id],
+    )
+    .await?;
+    tr.execute(
+        "insert into account_deletions (user_id, purge_after) values ($1, now() + interval '30 days')",
+        &[user_id],
+    )
+    .await?;
+    tr.commit().await?;
+    Ok(true)
+}
+
+pub async fn account_deletion_exists(conn: &Object, user_id: &Uuid) -> Result<bool> {
+    let row = conn
+        .query_opt(
+            "select 1 from account_deletions where user_id = $1",
+            &[user_id],
+        )
+        .await?;
+    Ok(row.is_some())
+}
+
+/**
+ * Claim the deletions with work left for 5 minutes. A failed one is retried once the claim expires.
+ */
+pub async fn claim_pending_deletions(conn: &Object) -> Result<Vec<AccountDeletion>> {
+    let rows = conn
+        .query(
+            "update account_deletions set locked_until = now() + interval '5 minutes', attempts = attempts + 1
+            where user_id in (
+                select user_id from account_deletions
+                where purged = 'infinity' and locked_until < now()
+                and (propagated = 'infinity' or purge_after < now())
+                order by created limit 10
+                for update skip locked
+            )
+            returning user_id, propagated != 'infinity' as propagated, purge_after < now() as purge_due",
+            &[],
+        )
+        .await?;
+    let mut deletions = Vec::new();
+    for row in rows {
+        deletions.push(AccountDeletion {
+            user_id: row.try_get("user_id")?,
+            propagated: row.try_get("propagated")?,
+            purge_due: row.try_get("purge_due")?,
+        });
+    }
+    Ok(deletions)
+}
+
+pub async fn update_deletion_propagated(conn: &Object, user_id: &Uuid) -> Result<()> {
+    conn.execute(
+        "update account_deletions set propagated = now(), locked_until = '-infinity', last_error = '' where user_id = $1",
+        &[user_id],
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn update_deletion_error(conn: &Object, user_id: &Uuid, error: &str) -> Result<()> {
+    conn.execute(
+        "update account_deletions set last_error = $2 where user_id = $1",
+        &[user_id, &error],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * Delete the user for good, the identities, passkeys and profile go with it.
+ * The audit events are kept, without the ip and user agent of the client.
+ */
+pub async fn purge_user(conn: &mut Object, user_id: &Uuid) -> Result<()> {
+    let tr = conn.transaction().await?;
+    tr.execute("delete from users where id = $1", &[user_id])
+        .await?;
+    tr.execute("delete from tokens where user_id = $1", &[user_id])
+        .await?;
+    tr.execute(
+        "update audit_events set ip = '', user_agent = '' where user_id = $1 and (ip != '' or user_agent != '')",
+        &[user_id],
+    )
+    .await?;
+    tr.execute(
+        "update account_deletions set purged = now(), locked_until = '-infinity', last_error = '' where user_id = $1",
+        &[user_id],
+    )
+    .await?;
+    tr.commit().await?;
+    Ok(())
+}
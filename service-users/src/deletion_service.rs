@@ -0,0 +1,169 @@
+use anyhow::Result;
+use std::time::Duration;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::deletion_db::AccountDeletion;
+use crate::proto::{
+    notes_service_client::NotesServiceClient, utils_service_client::UtilsServiceClient,
+    AuditEventType, UserDataDeletion,
+};
+
+/**
+ * The user deletes their own account. It's soft deleted right away, the other services are told
+ * by `spawn_worker`, and everything is deleted for good after 30 days.
+ */
+pub async fn delete_account(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+
+    let mut conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    let user =
+        crate::user_db::select_user_by_id(&conn, crate::user_db::StringOrUuid::String(claims.id))
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to auth user: {:?}", e);
+                Status::unauthenticated("Failed to auth user")
+            })?;
+    let user_id = Uuid::parse_str(&user.id).map_err(|e| {
+        tracing::error!("Failed to parse user id: {:?}", e);
+        Status::internal("Failed to parse user id")
+    })?;
+
+    // Before anything is deleted, so the user can try again if Stripe fails
+    crate::stripe_service::cancel_subscriptions(env, &user)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to cancel subscription: {:?}", e);
+            Status::internal("Failed to cancel subscription")
+        })?;
+
+    let deleted = crate::deletion_db::delete_account(&mut conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete account: {:?}", e);
+            Status::internal("Failed to delete account")
+        })?;
+    if !deleted {
+        return Err(Status::not_found("User not found"));
+    }
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::AccountDeleted,
+        Some(user_id),
+        "",
+        "",
+    )
+    .await;
+
+    tracing::info!("delete_account: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * Runs the queued account deletions every minute, until every service has confirmed them.
+ * Only the ids are sent, so the other services don't need to know anything else about users.
+ */
+pub fn spawn_worker(env: service_users::Env, pool: deadpool_postgres::Pool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let mut conn = match pool.get().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::error!("Failed to get connection: {:?}", e);
+                    continue;
+                }
+            };
+            let deletions = match crate::deletion_db::claim_pending_deletions(&conn).await {
+                Ok(deletions) => deletions,
+                Err(e) => {
+                    tracing::error!("Failed to claim account deletions: {:?}", e);
+                    continue;
+                }
+            };
+            for deletion in deletions {
+                if let Err(e) = run_deletion(&env, &mut conn, &deletion).await {
+                    tracing::error!("Failed to delete account {}: {:?}", deletion.user_id, e);
+                    let error = format!("{:#}", e);
+                    if let Err(e) =
+                        crate::deletion_db::update_deletion_error(&conn, &deletion.user_id, &error)
+                            .await
+                    {
+                        tracing::error!("Failed to update account deletion: {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_deletion(
+    env: &service_users::Env,
+    conn: &mut deadpool_postgres::Object,
+    deletion: &AccountDeletion,
+) -> Result<()> {
+    if !deletion.propagated {
+        delete_user_data(env, &deletion.user_id, false).await?;
+        crate::deletion_db::update_deletion_propagated(conn, &deletion.user_id).await?;
+        tracing::info!("Account deletion propagated: {}", deletion.user_id);
+    }
+    if deletion.purge_due {
+        delete_user_data(env, &deletion.user_id, true).await?;
+        crate::deletion_db::purge_user(conn, &deletion.user_id).await?;
+        // Run in the background, so there is no client to record
+        crate::audit_service::record(
+            conn,
+            &tonic::metadata::MetadataMap::new(),
+            AuditEventType::AccountPurged,
+            Some(deletion.user_id),
+            "",
+            "",
+        )
+        .await;
+        tracing::info!("Account purged: {}", deletion.user_id);
+    }
+    Ok(())
+}
+
+/**
+ * Tell the notes and utils services, both calls are idempotent.
+ */
+async fn delete_user_data(env: &service_users::Env, user_id: &Uuid, purge: bool) -> Result<()> {
+    let deletion = UserDataDeletion {
+        user_id: user_id.to_string(),
+        purge,
+    };
+
+    let mut request = Request::new(deletion.clone());
+    request.metadata_mut().insert(
+        "x-authorization",
+        service_users::generate_service_token(&env.jwt_keys, "service-notes")?,
+    );
+    NotesServiceClient::connect(env.notes_url.to_owned())
+        .await?
+        .delete_user_data(request)
+        .await?;
+
+    let mut request = Request::new(deletion);
+    request.metadata_mut().insert(
+        "x-authorization",
+        service_users::generate_service_token(&env.jwt_keys, "service-utils")?,
+    );
+    UtilsServiceClient::connect(env.utils_url.to_owned())
+        .await?
+        .delete_user_data(request)
+        .await?;
+    Ok(())
+}
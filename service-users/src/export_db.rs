@@ -0,0 +1,77 @@
+use anyhow::Result;
+use deadpool_postgres::Object;
+use uuid::Uuid;
+
+/**
+ * An archive of the data of the user, built by `spawn_worker`.
+ */
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+}
+
+/**
+ * Returns false if an export of the user is already pending.
+ */
+pub async fn insert_data_export(conn: &Object, user_id: &Uuid) -> Result<bool> {
+    let inserted = conn
+        .execute(
+            "insert into data_exports (id, user_id) values ($1, $2)
+            on conflict (user_id) where finished = 'infinity' do nothing",
+            &[&Uuid::now_v7(), user_id],
+        )
+        .await?;
+    Ok(inserted > 0)
+}
+
+/**
+ * Claim the pending exports for 5 minutes, with the users still there. A failed one is retried
+ * once the claim expires.
+ */
+pub async fn claim_pending_exports(conn: &Object) -> Result<Vec<DataExport>> {
+    let rows = conn
+        .query(
+            "update data_exports set locked_until = now() + interval '5 minutes', attempts = attempts + 1
+            where id in (
+                select data_exports.id from data_exports
+                join users on users.id = data_exports.user_id and users.deleted = 'infinity'
+                where finished = 'infinity' and locked_until < now()
+                order by data_exports.created limit 10
+                for update of data_exports skip locked
+            )
+            returning id, user_id",
+            &[],
+        )
+        .await?;
+    let mut exports = Vec::new();
+    for row in rows {
+        exports.push(DataExport {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+        });
+    }
+    Ok(exports)
+}
+
+pub async fn update_export_finished(conn: &Object, id: &Uuid, file_id: &str) -> Result<()> {
+    conn.execute(
+        "update data_exports set finished = now(), file_id = $2, locked_until = '-infinity', last_error = '' where id = $1",
+        &[id, &file_id],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * The 10th failure gives up, finished without a file, so the user can ask again.
+ */
+pub async fn update_export_error(conn: &Object, id: &Uuid, error: &str) -> Result<()> {
+    conn.execute(
+        "update data_exports set last_error = $2,
+        finished = case when attempts >= 10 then now() else finished end
+        where id = $1",
+        &[id, &error],
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use anyhow::Result;
+use std::time::Duration;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::export_db::DataExport;
+use crate::proto::{
+    notes_service_client::NotesServiceClient, utils_service_client::UtilsServiceClient,
+    AuditEventType, DataExportPart, DataExportRequest, UserRole,
+};
+
+/**
+ * Queue an archive of everything stored about the user, built by `spawn_worker`.
+ * The user gets an email once it's ready to download from the files page.
+ */
+pub async fn create_data_export(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = Uuid::parse_str(&claims.id).map_err(|e| {
+        tracing::error!("Failed to parse user id: {:?}", e);
+        Status::unauthenticated("Invalid access token")
+    })?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let inserted = crate::export_db::insert_data_export(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create data export: {:?}", e);
+            Status::internal("Failed to create data export")
+        })?;
+    if !inserted {
+        return Err(Status::already_exists("An export is already in progress"));
+    }
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::DataExportRequested,
+        Some(user_id),
+        "",
+        "",
+    )
+    .await;
+
+    tracing::info!("create_data_export: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * Builds the queued exports every minute. Each service exports its own data,
+ * service-utils adds the emails and files and stores the archive.
+ */
+pub fn spawn_worker(env: service_users::Env, pool: deadpool_postgres::Pool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let conn = match pool.get().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::error!("Failed to get connection: {:?}", e);
+                    continue;
+                }
+            };
+            let exports = match crate::export_db::claim_pending_exports(&conn).await {
+                Ok(exports) => exports,
+                Err(e) => {
+                    tracing::error!("Failed to claim data exports: {:?}", e);
+                    continue;
+                }
+            };
+            for export in exports {
+                if let Err(e) = run_export(&env, &conn, &export).await {
+                    tracing::error!("Failed to export data {}: {:?}", export.id, e);
+                    let error = format!("{:#}", e);
+                    if let Err(e) =
+                        crate::export_db::update_export_error(&conn, &export.id, &error).await
+                    {
+                        tracing::error!("Failed to update data export: {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_export(
+    env: &service_users::Env,
+    conn: &deadpool_postgres::Object,
+    export: &DataExport,
+) -> Result<()> {
+    let user =
+        crate::user_db::select_user_by_id(conn, crate::user_db::StringOrUuid::Uuid(export.user_id))
+            .await?;
+    let user_part = export_user(conn, &user).await?;
+
+    let mut request = Request::new(crate::proto::Id {
+        id: export.user_id.to_string(),
+    });
+    request.metadata_mut().insert(
+        "x-authorization",
+        service_users::generate_service_token(&env.jwt_keys, "service-notes")?,
+    );
+    let notes_part = NotesServiceClient::connect(env.notes_url.to_owned())
+        .await?
+        .export_user_data(request)
+        .await?
+        .into_inner();
+
+    let mut request = Request::new(DataExportRequest {
+        user_id: export.user_id.to_string(),
+        parts: vec![user_part, notes_part],
+    });
+    request.metadata_mut().insert(
+        "x-authorization",
+        service_users::generate_service_token(&env.jwt_keys, "service-utils")?,
+    );
+    let file = UtilsServiceClient::connect(env.utils_url.to_owned())
+        .await?
+        .create_data_export(request)
+        .await?
+        .into_inner();
+
+    crate::export_db::update_export_finished(conn, &export.id, &file.id).await?;
+    tracing::info!("Data export finished: {}", export.id);
+
+    // The archive is already on the files page, a failed email doesn't run the export again
+    if let Err(e) = send_export_email(env, &user).await {
+        tracing::error!("Failed to send data export email {}: {:?}", export.id, e);
+    }
+    Ok(())
+}
+
+/**
+ * Sent to the address of the account, never one from a request.
+ */
+async fn send_export_email(env: &service_users::Env, user: &crate::proto::User) -> Result<()> {
+    let url = format!("{}/files", env.client_url);
+    let mut request = Request::new(crate::proto::Email {
+        target_id: user.id.to_owned(),
+        email_to: user.email.to_owned(),
+        email_from: env.email_from.to_owned(),
+        email_from_name: "Rusve".to_string(),
+        email_subject: "Your data export is ready".to_string(),
+        email_body: format!(
+            r#"
+            <p>The archive of Your data is ready, You can download it from Your files.</p>
+            <p><a href="{url}">Download</a></p>
+            <p>If You didn't request it, sign in and check Your sessions in the settings.</p>
+            "#
+        ),
+        ..Default::default()
+    });
+    request.metadata_mut().insert(
+        "x-authorization",
+        service_users::generate_service_token(&env.jwt_keys, "service-utils")?,
+    );
+    UtilsServiceClient::connect(env.utils_url.to_owned())
+        .await?
+        .send_email(request)
+        .await?;
+    Ok(())
+}
+
+/**
+ * `user.json`, the account, the profile and the linked identities.
+ */
+async fn export_user(
+    conn: &deadpool_postgres::Object,
+    user: &crate::proto::User,
+) -> Result<DataExportPart> {
+    let profile = crate::profile_db::select_profile_by_user_id(conn, &user.id).await?;
+    let identities = crate::identity_db::select_identities_by_user_id(conn, &user.id).await?;
+
+    let role = UserRole::from_i32(user.role).unwrap_or_default();
+    let content = serde_json::json!({
+        "user": {
+            "id": user.id,
+            "created": user.created,
+            "email": user.email,
+            "role": role.as_str_name(),
+            "avatar": user.avatar,
+            "subscription_end": user.subscription_end,
+        },
+        "profile": profile.map(|profile| serde_json::json!({
+            "created": profile.created,
+            "updated": profile.updated,
+            "name": profile.name,
+            "about": profile.about,
+            "avatar_url": profile.avatar_url,
+            "cover_url": profile.cover_url,
+        })),
+        "identities": identities.iter().map(|identity| serde_json::json!({
+            "created": identity.created,
+            "provider": identity.provider,
+            "email": identity.email,
+        })).collect::<Vec<_>>(),
+    });
+    Ok(DataExportPart {
+        name: "user.json".to_string(),
+        content: serde_json::to_vec_pretty(&content)?,
+    })
+}
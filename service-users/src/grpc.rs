@@ -1,15 +1,26 @@
 use crate::proto::users_service_server::UsersService;
-use crate::proto::{AuthResponse, Empty, Id, Profile};
+use crate::proto::{
+    AuthResponse, Empty, Id, LoginResponse, Passkey, Profile, RecoveryCodes, RoleTwoFactor,
+    TwoFactorCode, TwoFactorSetup,
+};
 use crate::MyService;
 use anyhow::Result;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 
 #[tonic::async_trait]
 impl UsersService for MyService {
+    type GetSessionsStream = ReceiverStream<Result<crate::proto::Session, Status>>;
+    type GetIdentitiesStream = ReceiverStream<Result<crate::proto::Identity, Status>>;
+    type GetPasskeysStream = ReceiverStream<Result<Passkey, Status>>;
+    type GetAuditEventsStream = ReceiverStream<Result<crate::proto::AuditEvent, Status>>;
+    type GetUsersStream = ReceiverStream<Result<crate::proto::User, Status>>;
+    type GetProfilesByUserIdsStream = ReceiverStream<Result<Profile, Status>>;
+
     async fn create_user(
         &self,
         request: Request<crate::proto::Empty>,
-    ) -> Result<Response<Id>, Status> {
+    ) -> Result<Response<LoginResponse>, Status> {
         crate::user_service::create_user(&self.env, &self.pool, request).await
     }
 
@@ -17,6 +28,115 @@ impl UsersService for MyService {
         crate::user_service::auth(&self.env, &self.pool, request).await
     }
 
+    async fn refresh_token(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<AuthResponse>, Status> {
+        crate::user_service::refresh_token(&self.env, &self.pool, request).await
+    }
+
+    async fn logout(&self, request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        crate::session_service::logout(&self.env, &self.pool, request).await
+    }
+
+    async fn get_sessions(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::GetSessionsStream>, Status> {
+        crate::session_service::get_sessions(&self.env, &self.pool, request).await
+    }
+
+    async fn revoke_session(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::session_service::revoke_session(&self.env, &self.pool, request).await
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Empty>, Status> {
+        crate::session_service::revoke_other_sessions(&self.env, &self.pool, request).await
+    }
+
+    async fn get_identities(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::GetIdentitiesStream>, Status> {
+        crate::identity_service::get_identities(&self.env, &self.pool, request).await
+    }
+
+    async fn create_link_code(&self, request: Request<Empty>) -> Result<Response<Id>, Status> {
+        crate::identity_service::create_link_code(&self.env, &self.pool, request).await
+    }
+
+    async fn link_identity(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::identity_service::link_identity(&self.env, &self.pool, request).await
+    }
+
+    async fn unlink_identity(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::identity_service::unlink_identity(&self.env, &self.pool, request).await
+    }
+
+    async fn create_passkey(&self, request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        crate::passkey_service::create_passkey(&self.env, &self.pool, request).await
+    }
+
+    async fn get_passkey(&self, request: Request<Empty>) -> Result<Response<Passkey>, Status> {
+        crate::passkey_service::get_passkey(&self.env, &self.pool, request).await
+    }
+
+    async fn passkey_login(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<LoginResponse>, Status> {
+        crate::passkey_service::passkey_login(&self.env, &self.pool, request).await
+    }
+
+    async fn get_passkeys(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::GetPasskeysStream>, Status> {
+        crate::passkey_service::get_passkeys(&self.env, &self.pool, request).await
+    }
+
+    async fn delete_passkey(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::passkey_service::delete_passkey(&self.env, &self.pool, request).await
+    }
+
+    async fn setup_two_factor(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<TwoFactorSetup>, Status> {
+        crate::two_factor_service::setup_two_factor(&self.env, &self.pool, request).await
+    }
+
+    async fn enable_two_factor(
+        &self,
+        request: Request<TwoFactorCode>,
+    ) -> Result<Response<RecoveryCodes>, Status> {
+        crate::two_factor_service::enable_two_factor(&self.env, &self.pool, request).await
+    }
+
+    async fn disable_two_factor(
+        &self,
+        request: Request<TwoFactorCode>,
+    ) -> Result<Response<Empty>, Status> {
+        crate::two_factor_service::disable_two_factor(&self.env, &self.pool, request).await
+    }
+
+    async fn verify_two_factor(
+        &self,
+        request: Request<TwoFactorCode>,
+    ) -> Result<Response<Id>, Status> {
+        crate::two_factor_service::verify_two_factor(&self.env, &self.pool, request).await
+    }
+
+    async fn set_role_two_factor(
+        &self,
+        request: Request<RoleTwoFactor>,
+    ) -> Result<Response<Empty>, Status> {
+        crate::two_factor_service::set_role_two_factor(&self.env, &self.pool, request).await
+    }
+
     async fn get_profile_by_user_id(
         &self,
         request: Request<crate::proto::Empty>,
@@ -24,6 +144,13 @@ impl UsersService for MyService {
         crate::profile_service::get_profile_by_user_id(&self.env, &self.pool, request).await
     }
 
+    async fn get_profiles_by_user_ids(
+        &self,
+        request: Request<crate::proto::Ids>,
+    ) -> Result<Response<Self::GetProfilesByUserIdsStream>, Status> {
+        crate::profile_service::get_profiles_by_user_ids(&self.env, &self.pool, request).await
+    }
+
     async fn create_profile(&self, request: Request<Profile>) -> Result<Response<Profile>, Status> {
         crate::profile_service::create_profile(&self.env, &self.pool, request).await
     }
@@ -41,4 +168,70 @@ impl UsersService for MyService {
     ) -> Result<Response<crate::proto::StripeUrlResponse>, Status> {
         crate::stripe_service::create_stripe_portal(&self.env, &self.pool, request).await
     }
+
+    async fn delete_account(&self, request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        crate::deletion_service::delete_account(&self.env, &self.pool, request).await
+    }
+
+    async fn create_data_export(&self, request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        crate::export_service::create_data_export(&self.env, &self.pool, request).await
+    }
+
+    async fn create_audit_event(&self, request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        crate::audit_service::create_audit_event(&self.env, &self.pool, request).await
+    }
+
+    async fn get_audit_events(
+        &self,
+        request: Request<crate::proto::AuditEventFilter>,
+    ) -> Result<Response<Self::GetAuditEventsStream>, Status> {
+        crate::audit_service::get_audit_events(&self.env, &self.pool, request).await
+    }
+
+    async fn impersonate_user(
+        &self,
+        request: Request<crate::proto::Id>,
+    ) -> Result<Response<AuthResponse>, Status> {
+        crate::session_service::impersonate_user(&self.env, &self.pool, request).await
+    }
+
+    async fn count_users(
+        &self,
+        request: Request<crate::proto::UserFilter>,
+    ) -> Result<Response<crate::proto::Count>, Status> {
+        crate::admin_service::count_users(&self.env, &self.pool, request).await
+    }
+
+    async fn get_users(
+        &self,
+        request: Request<crate::proto::UserFilter>,
+    ) -> Result<Response<Self::GetUsersStream>, Status> {
+        crate::admin_service::get_users(&self.env, &self.pool, request).await
+    }
+
+    async fn get_user_details(
+        &self,
+        request: Request<Id>,
+    ) -> Result<Response<crate::proto::UserDetails>, Status> {
+        crate::admin_service::get_user_details(&self.env, &self.pool, request).await
+    }
+
+    async fn set_user_role(
+        &self,
+        request: Request<crate::proto::UserRoleChange>,
+    ) -> Result<Response<crate::proto::User>, Status> {
+        crate::admin_service::set_user_role(&self.env, &self.pool, request).await
+    }
+
+    async fn delete_user(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::admin_service::delete_user(&self.env, &self.pool, request).await
+    }
+
+    async fn restore_user(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::admin_service::restore_user(&self.env, &self.pool, request).await
+    }
+
+    async fn revoke_user_sessions(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
+        crate::admin_service::revoke_user_sessions(&self.env, &self.pool, request).await
+    }
 }
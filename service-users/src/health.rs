@@ -0,0 +1,50 @@
+use anyhow::Result;
+use std::time::Duration;
+use tonic_health::server::HealthReporter;
+
+use crate::proto::users_service_server::UsersServiceServer;
+use crate::MyService;
+
+/**
+ * Readiness of `proto.UsersService` in `grpc.health.v1`, checked every 10 seconds.
+ * The overall status, the empty service name, is always serving and acts as the liveness check.
+ */
+pub fn spawn_readiness(mut reporter: HealthReporter, pool: deadpool_postgres::Pool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(10));
+        let mut ready = None;
+        loop {
+            interval.tick().await;
+            let result = check(&pool).await;
+            if ready != Some(result.is_ok()) {
+                match &result {
+                    Ok(()) => tracing::info!("Service is ready"),
+                    Err(err) => tracing::error!("Service is not ready: {:?}", err),
+                }
+                ready = Some(result.is_ok());
+            }
+            match result {
+                Ok(()) => {
+                    reporter
+                        .set_serving::<UsersServiceServer<MyService>>()
+                        .await
+                }
+                Err(_) => {
+                    reporter
+                        .set_not_serving::<UsersServiceServer<MyService>>()
+                        .await
+                }
+            }
+        }
+    });
+}
+
+async fn check(pool: &deadpool_postgres::Pool) -> Result<()> {
+    // A pool without free connections waits, don't let it hang the check
+    tokio::time::timeout(Duration::from_secs(5), async {
+        let conn = pool.get().await?;
+        conn.simple_query("select 1").await?;
+        Ok(())
+    })
+    .await?
+}
@@ -0,0 +1,124 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use deadpool_postgres::Object;
+use time::format_description::well_known::Iso8601;
+use uuid::Uuid;
+
+use crate::proto::Identity;
+
+impl TryFrom<tokio_postgres::Row> for Identity {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let id: Uuid = value.try_get("id")?;
+        let created: time::OffsetDateTime = value.try_get("created")?;
+        let created: String = created.format(&Iso8601::DEFAULT)?.to_string();
+        let provider: String = value.try_get("provider")?;
+        let email: String = value.try_get("email")?;
+
+        Ok(Identity {
+            id: id.to_string(),
+            created,
+            provider,
+            email,
+        })
+    }
+}
+
+pub async fn select_user_id_by_identity(
+    conn: &Object,
+    provider: &str,
+    sub: &str,
+) -> Result<Option<Uuid>> {
+    let row = conn
+        .query_opt(
+            "select user_id from identities where provider = $1 and sub = $2",
+            &[&provider, &sub],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(row.try_get("user_id")?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn select_identities_by_user_id(conn: &Object, user_id: &str) -> Result<Vec<Identity>> {
+    let rows = conn
+        .query(
+            "select * from identities where user_id = $1 order by created",
+            &[&Uuid::from_str(user_id)?],
+        )
+        .await?;
+    rows.into_iter().map(Identity::try_from).collect()
+}
+
+pub async fn insert_identity(
+    conn: &Object,
+    user_id: &Uuid,
+    provider: &str,
+    sub: &str,
+    email: &str,
+) -> Result<Identity> {
+    let id = Uuid::now_v7();
+    let row = conn
+        .query_one(
+            "insert into identities (id, user_id, provider, sub, email) values ($1, $2, $3, $4, $5) returning *",
+            &[&id, user_id, &provider, &sub, &email],
+        )
+        .await?;
+    Identity::try_from(row)
+}
+
+/**
+ * Delete the identity, unless it's the last one of the user, so the account can still be signed in.
+ * Returns false if nothing was deleted.
+ */
+pub async fn delete_identity(conn: &Object, user_id: &str, identity_id: &str) -> Result<bool> {
+    let user_id = Uuid::from_str(user_id)?;
+    let deleted = conn
+        .execute(
+            "
+            delete from identities
+            where id = $1
+            and user_id = $2
+            and (select count(*) from identities where user_id = $2) > 1
+            ",
+            &[&Uuid::from_str(identity_id)?, &user_id],
+        )
+        .await?;
+    Ok(deleted > 0)
+}
+
+pub async fn insert_link_code(conn: &Object, user_id: &str) -> Result<Uuid> {
+    let id = Uuid::now_v7();
+    conn.execute(
+        "insert into link_codes (id, user_id) values ($1, $2)",
+        &[&id, &Uuid::from_str(user_id)?],
+    )
+    .await?;
+    Ok(id)
+}
+
+// Codes are single use and valid for 5 minutes, the same as the OAuth verifiers.
+pub async fn delete_link_code(conn: &Object, code: &str) -> Result<Option<Uuid>> {
+    let row = conn
+        .query_opt(
+            "delete from link_codes where id = $1 and created > now() - interval '5 minutes' returning user_id",
+            &[&Uuid::from_str(code)?],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(row.try_get("user_id")?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn delete_old_link_codes(conn: &Object) -> Result<()> {
+    conn.execute(
+        "delete from link_codes where created < now() - interval '5 minutes'",
+        &[],
+    )
+    .await?;
+    Ok(())
+}
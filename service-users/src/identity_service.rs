@@ -0,0 +1,203 @@
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::proto::{AuditEventType, Identity, LoginError};
+use crate::user_service::{invalid_link_code, login_error};
+
+pub async fn get_identities(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<ReceiverStream<Result<Identity, Status>>>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let user_id = service_users::decode_access_token(metadata, &env.jwt_keys)?.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let identities = crate::identity_db::select_identities_by_user_id(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get identities: {:?}", e);
+            Status::internal("Failed to get identities")
+        })?;
+
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for identity in identities {
+            if let Err(e) = tx.send(Ok(identity)).await {
+                tracing::error!("Failed to send identity: {:?}", e);
+                return;
+            }
+        }
+        tracing::info!("get_identities: {:?}", start.elapsed());
+    });
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+/**
+ * The user is signed in, so the code proves which account the next OAuth login should be linked to.
+ * `LinkIdentity` also checks that it comes back to the same user.
+ */
+pub async fn create_link_code(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Id>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let code = crate::identity_db::insert_link_code(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to insert link code: {:?}", e);
+            Status::internal("Failed to insert link code")
+        })?;
+
+    // Delete old link codes. If this fails, it's not a big deal.
+    tokio::spawn(async move {
+        if let Err(err) = crate::identity_db::delete_old_link_codes(&conn).await {
+            tracing::error!("Failed to delete old link codes: {:?}", err);
+        }
+    });
+
+    tracing::info!("create_link_code: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Id {
+        id: code.to_string(),
+    }))
+}
+
+/**
+ * Link the identity verified by service-auth to the signed in user. The link code has to be
+ * created by the same user, so a login started by someone else can't be finished in this session.
+ * An identity that already belongs to another user is never moved.
+ */
+pub async fn link_identity(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+    let claims = env
+        .jwt_keys
+        .decode::<service_users::OAuthClaims>(&request.get_ref().id, &service_users::OAUTH_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode identity token: {:?}", e);
+            Status::unauthenticated("Invalid identity token")
+        })?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    // Consumed even if it belongs to another user, so it can't be tried again.
+    let code_user_id = crate::identity_db::delete_link_code(&conn, &claims.link_code)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get link code: {:?}", e);
+            invalid_link_code()
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Link code not found or expired");
+            invalid_link_code()
+        })?;
+    if code_user_id.to_string() != user_id {
+        tracing::error!("Link code belongs to another user: {:?}", code_user_id);
+        return Err(invalid_link_code());
+    }
+
+    let linked_user_id =
+        crate::identity_db::select_user_id_by_identity(&conn, &claims.provider, &claims.sub)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get identity: {:?}", e);
+                Status::internal("Failed to get identity")
+            })?;
+    match linked_user_id {
+        Some(linked_user_id) if linked_user_id == code_user_id => {}
+        Some(_) => {
+            tracing::error!("Identity is linked to another user");
+            return Err(login_error(
+                tonic::Code::AlreadyExists,
+                "Identity is linked to another account",
+                LoginError::AccountExists,
+            ));
+        }
+        None => {
+            crate::identity_db::insert_identity(
+                &conn,
+                &code_user_id,
+                &claims.provider,
+                &claims.sub,
+                &claims.email,
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to insert identity: {:?}", e);
+                Status::internal("Failed to insert identity")
+            })?;
+            crate::audit_service::record(
+                &conn,
+                metadata,
+                AuditEventType::IdentityLinked,
+                Some(code_user_id),
+                &claims.provider,
+                "",
+            )
+            .await;
+        }
+    }
+
+    tracing::info!("link_identity: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+pub async fn unlink_identity(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let identity_id = request.into_inner().id;
+    let deleted = crate::identity_db::delete_identity(&conn, &user_id, &identity_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete identity: {:?}", e);
+            Status::internal("Failed to delete identity")
+        })?;
+    if !deleted {
+        return Err(Status::failed_precondition(
+            "Identity not found, or it's the last one of the account",
+        ));
+    }
+
+    tracing::info!("unlink_identity: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
@@ -1,9 +1,14 @@
 use anyhow::{Context, Result};
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rustls::RootCertStore;
 use rustls_native_certs::load_native_certs;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 use tokio_postgres_rustls::MakeRustlsConnect;
+use tonic::metadata::{Ascii, MetadataValue};
 mod proto;
 
 #[derive(Clone)]
@@ -12,9 +17,12 @@ pub struct Env {
     pub rust_log: String,
     pub database_url: String,
     pub client_url: String,
+    pub notes_url: String,
+    pub utils_url: String,
+    pub email_from: String,
     pub stripe_api_key: String,
     pub stripe_price_id: String,
-    pub jwt_secret: String,
+    pub jwt_keys: JwtKeys,
 }
 
 pub fn init_envs() -> Result<Env> {
@@ -23,12 +31,350 @@ pub fn init_envs() -> Result<Env> {
         rust_log: std::env::var("RUST_LOG").context("RUST_LOG is not set")?,
         database_url: std::env::var("DATABASE_URL").context("DATABASE_URL is not set")?,
         client_url: std::env::var("CLIENT_URL").context("CLIENT_URL is not set")?,
+        notes_url: std::env::var("NOTES_URL").context("NOTES_URL is not set")?,
+        utils_url: std::env::var("UTILS_URL").context("UTILS_URL is not set")?,
+        email_from: std::env::var("EMAIL_FROM").context("EMAIL_FROM is not set")?,
         stripe_api_key: std::env::var("STRIPE_API_KEY").context("STRIPE_API_KEY is not set")?,
         stripe_price_id: std::env::var("STRIPE_PRICE_ID").context("STRIPE_PRICE_ID is not set")?,
-        jwt_secret: std::env::var("JWT_SECRET").context("JWT_SECRET is not set")?,
+        jwt_keys: init_jwt_keys()?,
     })
 }
 
+/**
+ * Keys used to verify the tokens signed by the client and the other services, and to sign
+ * the short-lived access tokens. Only public keys are accepted, except for the legacy HS256 mode
+ * with shared secrets.
+ * The key set is shared by every clone and swapped on reload, see `spawn_reload`.
+ */
+#[derive(Clone)]
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    key_set: Arc<RwLock<KeySet>>,
+}
+
+struct KeySet {
+    // Active signing key
+    kid: Option<String>,
+    encoding_key: EncodingKey,
+    // kid -> key, every key that is still valid
+    decoding_keys: HashMap<String, DecodingKey>,
+}
+
+impl JwtKeys {
+    pub fn encode<T: serde::Serialize>(&self, claims: &T, kind: &TokenKind) -> Result<String> {
+        let key_set = self.key_set.read().expect("JWT key set lock poisoned");
+        let kid = key_set.kid.as_deref().unwrap_or_default();
+        if !self.may_sign(kid, kind) {
+            return Err(anyhow::anyhow!(
+                "JWT_KID {} can't sign {} tokens",
+                kid,
+                kind.typ
+            ));
+        }
+        let mut header = Header::new(self.algorithm);
+        header.kid = key_set.kid.clone();
+        header.typ = Some(kind.typ.to_string());
+        Ok(jsonwebtoken::encode(
+            &header,
+            &WithAudience {
+                claims,
+                aud: kind.audience,
+            },
+            &key_set.encoding_key,
+        )?)
+    }
+
+    pub fn decode<T: serde::de::DeserializeOwned>(
+        &self,
+        token: &str,
+        kind: &TokenKind,
+    ) -> Result<T> {
+        let header = jsonwebtoken::decode_header(token)?;
+        // The algorithm comes from our config, never from the token itself.
+        if header.alg != self.algorithm {
+            return Err(anyhow::anyhow!(
+                "Unexpected token algorithm: {:?}",
+                header.alg
+            ));
+        }
+        if header.typ.as_deref() != Some(kind.typ) {
+            return Err(anyhow::anyhow!("Unexpected token type: {:?}", header.typ));
+        }
+        let kid = header.kid.as_deref().unwrap_or_default();
+        if !self.may_sign(kid, kind) {
+            return Err(anyhow::anyhow!(
+                "Token kid {:?} can't sign {} tokens",
+                kid,
+                kind.typ
+            ));
+        }
+        let key_set = self.key_set.read().expect("JWT key set lock poisoned");
+        let key = key_set
+            .decoding_keys
+            .get(kid)
+            .ok_or_else(|| anyhow::anyhow!("Unknown token kid: {:?}", header.kid))?;
+        let mut validation = Validation::new(self.algorithm);
+        if let Some(audience) = kind.audience {
+            validation.set_audience(&[audience]);
+            validation.set_required_spec_claims(&["exp", "aud"]);
+        }
+        let token = jsonwebtoken::decode::<T>(token, key, &validation)?;
+        Ok(token.claims)
+    }
+
+    /**
+     * The shared secrets of the legacy HS256 mode are known to every service and the client,
+     * so they can't tell the signers apart. Only the public keys are bound to their signer.
+     */
+    fn may_sign(&self, kid: &str, kind: &TokenKind) -> bool {
+        self.algorithm == Algorithm::HS256 || kind.signers.contains(&key_signer(kid))
+    }
+
+    /**
+     * Reload the key set every minute, so keys can be rotated without a restart.
+     * If the new keys are invalid, the current ones are kept.
+     */
+    pub fn spawn_reload(&self) {
+        let jwt_keys = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            // The first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match load_key_set(jwt_keys.algorithm) {
+                    Ok(key_set) => {
+                        *jwt_keys.key_set.write().expect("JWT key set lock poisoned") = key_set
+                    }
+                    Err(e) => tracing::error!("Failed to reload JWT keys: {:?}", e),
+                }
+            }
+        });
+    }
+}
+
+/**
+ * What a token is for: its `typ` header, the signers allowed to sign it and its `aud` claim.
+ * A token of another kind, signed by another service or for another service is rejected,
+ * so one compromised key can't mint the tokens of the others.
+ */
+pub struct TokenKind<'a> {
+    pub typ: &'a str,
+    pub signers: &'a [&'a str],
+    pub audience: Option<&'a str>,
+}
+
+// Signed by the client for the signed in user
+pub const CLIENT_TOKEN: TokenKind = TokenKind {
+    typ: "JWT",
+    signers: &["client"],
+    audience: None,
+};
+pub const OAUTH_TOKEN: TokenKind = TokenKind {
+    typ: "oauth+jwt",
+    signers: &["auth"],
+    audience: Some("service-users"),
+};
+pub const PASSKEY_TOKEN: TokenKind = TokenKind {
+    typ: "passkey+jwt",
+    signers: &["auth"],
+    audience: Some("service-users"),
+};
+// The credential of an assertion, looked up by service-auth before it verifies it
+pub const PASSKEY_LOOKUP_TOKEN: TokenKind = TokenKind {
+    typ: "passkey-lookup+jwt",
+    signers: &["auth"],
+    audience: Some("service-users"),
+};
+pub const AUDIT_TOKEN: TokenKind = TokenKind {
+    typ: "audit+jwt",
+    signers: &["auth"],
+    audience: Some("service-users"),
+};
+// RFC 9068, only service-users issues sessions
+pub const ACCESS_TOKEN: TokenKind = TokenKind {
+    typ: "at+jwt",
+    signers: &["users"],
+    audience: Some("service-users"),
+};
+// A call between services, signed by the caller for the receiving service
+pub const SERVICE_TOKEN_TYP: &str = "service+jwt";
+
+/**
+ * The signer of a key is its kid, up to the first `-`, e.g. `auth` for `auth` or `auth-2024-06`.
+ */
+fn key_signer(kid: &str) -> &str {
+    kid.split('-').next().unwrap_or_default()
+}
+
+#[derive(serde::Serialize)]
+struct WithAudience<'a, T> {
+    #[serde(flatten)]
+    claims: &'a T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aud: Option<&'a str>,
+}
+
+/**
+ * Every JWT_* variable can also be read from a file, with its path in JWT_*_FILE.
+ * Files are read again on each reload, this is how keys are rotated without a restart.
+ */
+fn jwt_var(name: &str) -> Result<Option<String>> {
+    let value = match std::env::var(format!("{}_FILE", name)) {
+        Ok(path) => std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}_FILE", name))?,
+        Err(_) => std::env::var(name).unwrap_or_default(),
+    };
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/**
+ * JWT_ALGORITHM is HS256 (legacy, default), RS256 or EdDSA.
+ */
+fn init_jwt_keys() -> Result<JwtKeys> {
+    let algorithm = match jwt_var("JWT_ALGORITHM")?.as_deref() {
+        None | Some("HS256") => Algorithm::HS256,
+        Some("RS256") => Algorithm::RS256,
+        Some("EdDSA") => Algorithm::EdDSA,
+        Some(other) => return Err(anyhow::anyhow!("Unsupported JWT_ALGORITHM: {}", other)),
+    };
+    Ok(JwtKeys {
+        algorithm,
+        key_set: Arc::new(RwLock::new(load_key_set(algorithm)?)),
+    })
+}
+
+fn load_key_set(algorithm: Algorithm) -> Result<KeySet> {
+    let secrets = load_secrets(algorithm)?;
+    let (kid, encoding_key) = load_signing_key(algorithm, &secrets)?;
+    let decoding_keys = load_decoding_keys(algorithm, &secrets)?;
+    if !decoding_keys.contains_key(kid.as_deref().unwrap_or_default()) {
+        return Err(anyhow::anyhow!(
+            "JWT_PUBLIC_KEYS has no key for JWT_KID {:?}",
+            kid
+        ));
+    }
+    Ok(KeySet {
+        kid,
+        encoding_key,
+        decoding_keys,
+    })
+}
+
+/**
+ * JWT_SECRETS is a JSON object of kid -> secret, the legacy JWT_SECRET is used for tokens without kid.
+ */
+fn load_secrets(algorithm: Algorithm) -> Result<HashMap<String, String>> {
+    // Shared secrets are only used by the legacy HS256 mode
+    if algorithm != Algorithm::HS256 {
+        return Ok(HashMap::new());
+    }
+    let mut secrets: HashMap<String, String> = match jwt_var("JWT_SECRETS")? {
+        Some(secrets) => {
+            serde_json::from_str(&secrets).context("JWT_SECRETS is not a valid JSON object")?
+        }
+        None => HashMap::new(),
+    };
+    if secrets.contains_key("") {
+        return Err(anyhow::anyhow!("JWT_SECRETS contains a secret without kid"));
+    }
+    if let Some(secret) = jwt_var("JWT_SECRET")? {
+        secrets.insert(String::new(), secret);
+    }
+    Ok(secrets)
+}
+
+/**
+ * The active signing key. With HS256 it's the secret under JWT_KID, or JWT_SECRET without kid
+ * or without JWT_SECRETS.
+ * With RS256 and EdDSA it's the JWT_PRIVATE_KEY pem under JWT_KID.
+ */
+fn load_signing_key(
+    algorithm: Algorithm,
+    secrets: &HashMap<String, String>,
+) -> Result<(Option<String>, EncodingKey)> {
+    let kid = jwt_var("JWT_KID")?;
+    if algorithm == Algorithm::HS256 {
+        // Without JWT_SECRETS there is only JWT_SECRET, which has no kid, like the client signs it
+        let kid = kid.filter(|_| secrets.keys().any(|kid| !kid.is_empty()));
+        let secret = secrets
+            .get(kid.as_deref().unwrap_or_default())
+            .with_context(|| match &kid {
+                Some(kid) => format!("JWT_SECRETS has no secret for JWT_KID {}", kid),
+                None => "JWT_SECRET is not set".to_string(),
+            })?;
+        return Ok((kid, EncodingKey::from_secret(secret.as_bytes())));
+    }
+
+    let kid = kid.context("JWT_KID is not set")?;
+    let private_key = jwt_var("JWT_PRIVATE_KEY")?.context("JWT_PRIVATE_KEY is not set")?;
+    let encoding_key = match algorithm {
+        Algorithm::RS256 => EncodingKey::from_rsa_pem(private_key.as_bytes()),
+        _ => EncodingKey::from_ed_pem(private_key.as_bytes()),
+    }
+    .context("JWT_PRIVATE_KEY is not a valid private key")?;
+    Ok((Some(kid), encoding_key))
+}
+
+/**
+ * JWT_PUBLIC_KEYS is a JWKS document, the same one served by service-auth.
+ * Every key needs a kid, and only public keys for the configured algorithm are accepted.
+ */
+fn load_public_keys(algorithm: Algorithm) -> Result<JwkSet> {
+    let jwks = jwt_var("JWT_PUBLIC_KEYS")?.context("JWT_PUBLIC_KEYS is not set")?;
+    // Private parameters (`d`, `p`, `q`...) are dropped while parsing.
+    let jwks: JwkSet =
+        serde_json::from_str(&jwks).context("JWT_PUBLIC_KEYS is not a valid JWKS")?;
+    for jwk in &jwks.keys {
+        let kid = jwk
+            .common
+            .key_id
+            .as_deref()
+            .filter(|kid| !kid.is_empty())
+            .context("JWT_PUBLIC_KEYS contains a key without kid")?;
+        match (&jwk.algorithm, algorithm) {
+            (AlgorithmParameters::RSA(_), Algorithm::RS256) => {}
+            (AlgorithmParameters::OctetKeyPair(_), Algorithm::EdDSA) => {}
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "JWT_PUBLIC_KEYS key {} is not a {:?} public key",
+                    kid,
+                    algorithm
+                ))
+            }
+        }
+    }
+    if jwks.keys.is_empty() {
+        return Err(anyhow::anyhow!("JWT_PUBLIC_KEYS has no keys"));
+    }
+    Ok(jwks)
+}
+
+/**
+ * Every key still valid for verification, by kid. The legacy JWT_SECRET is stored under an empty kid.
+ */
+fn load_decoding_keys(
+    algorithm: Algorithm,
+    secrets: &HashMap<String, String>,
+) -> Result<HashMap<String, DecodingKey>> {
+    let mut decoding_keys = HashMap::new();
+    if algorithm == Algorithm::HS256 {
+        for (kid, secret) in secrets {
+            decoding_keys.insert(kid.clone(), DecodingKey::from_secret(secret.as_bytes()));
+        }
+        if decoding_keys.is_empty() {
+            return Err(anyhow::anyhow!("JWT_SECRET is not set"));
+        }
+        return Ok(decoding_keys);
+    }
+    for jwk in load_public_keys(algorithm)?.keys {
+        let kid = jwk.common.key_id.clone().unwrap_or_default();
+        decoding_keys.insert(kid, DecodingKey::from_jwk(&jwk)?);
+    }
+    Ok(decoding_keys)
+}
+
 pub fn connect_to_db(env: &Env) -> Result<deadpool_postgres::Pool> {
     let tokio_config = tokio_postgres::Config::from_str(&env.database_url)?;
     let mgr_config = ManagerConfig {
@@ -49,68 +395,416 @@ pub fn connect_to_db(env: &Env) -> Result<deadpool_postgres::Pool> {
     Ok(pool)
 }
 
-pub fn extract_token(metadata: &tonic::metadata::MetadataMap) -> Result<&str, tonic::Status> {
+/**
+ * Why a request was rejected. Handlers return it as a `tonic::Status` with `?`, which is too
+ * large to be the error of every check.
+ */
+#[derive(Debug)]
+pub enum RequestError {
+    Unauthenticated(&'static str),
+    PermissionDenied(&'static str),
+    InvalidArgument(String),
+    FailedPrecondition(&'static str),
+    Internal(&'static str),
+}
+
+impl From<RequestError> for tonic::Status {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::Unauthenticated(message) => tonic::Status::unauthenticated(message),
+            RequestError::PermissionDenied(message) => tonic::Status::permission_denied(message),
+            RequestError::InvalidArgument(message) => tonic::Status::invalid_argument(message),
+            RequestError::FailedPrecondition(message) => {
+                tonic::Status::failed_precondition(message)
+            }
+            RequestError::Internal(message) => tonic::Status::internal(message),
+        }
+    }
+}
+
+pub fn extract_token(metadata: &tonic::metadata::MetadataMap) -> Result<&str, RequestError> {
     let token = match metadata.get("x-authorization") {
         Some(token) => token,
         None => {
             tracing::error!("Missing authorization token");
-            return Err(tonic::Status::unauthenticated(
-                "Missing authorization token",
-            ));
+            return Err(RequestError::Unauthenticated("Missing authorization token"));
         }
     };
     let token = token
         .to_str()
         .map_err(|e| {
             tracing::error!("Failed to parse authorization token: {:?}", e);
-            tonic::Status::unauthenticated("Invalid authorization token")
+            RequestError::Unauthenticated("Invalid authorization token")
         })?
         .strip_prefix("bearer ")
         .ok_or_else(|| {
             tracing::error!("Failed to parse authorization token");
-            tonic::Status::unauthenticated("Invalid authorization token")
+            RequestError::Unauthenticated("Invalid authorization token")
         })?;
     Ok(token)
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct OAuthClaims {
+    pub provider: String,
     pub email: String,
+    pub email_verified: bool,
     pub sub: String,
     pub avatar: String,
+    // Set when a signed in user links the provider to their account
+    pub link_code: String,
 }
 pub fn decode_oauth_token(
     metadata: &tonic::metadata::MetadataMap,
-    jwt_secret: &str,
-) -> Result<OAuthClaims, tonic::Status> {
+    jwt_keys: &JwtKeys,
+) -> Result<OAuthClaims, RequestError> {
     let token = extract_token(metadata)?;
-    let token_message = jsonwebtoken::decode::<OAuthClaims>(
-        token,
-        &jsonwebtoken::DecodingKey::from_secret(jwt_secret.as_ref()),
-        &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256),
-    )
-    .map_err(|e| {
-        tracing::error!("Failed to decode authorization token: {:?}", e);
-        tonic::Status::unauthenticated("Invalid authorization token")
-    })?;
-    Ok(token_message.claims)
+    let claims = jwt_keys
+        .decode::<OAuthClaims>(token, &OAUTH_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode authorization token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    Ok(claims)
+}
+
+/**
+ * WebAuthn credential verified by service-auth, registered with a link code or used to sign in.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct PasskeyClaims {
+    pub credential_id: String,
+    // base64url COSE key, only set on registration
+    pub public_key: String,
+    pub sign_count: i64,
+    pub user_verified: bool,
+    pub name: String,
+    // The signed in user the passkey is registered to
+    pub link_code: String,
+}
+pub fn decode_passkey_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+) -> Result<PasskeyClaims, RequestError> {
+    let token = extract_token(metadata)?;
+    let claims = jwt_keys
+        .decode::<PasskeyClaims>(token, &PASSKEY_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode authorization token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    Ok(claims)
+}
+
+/**
+ * The credential id sent by service-auth to `GetPasskey`. It only reads the public key, so it
+ * can't be used to sign in like a `PasskeyClaims`.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct PasskeyLookupClaims {
+    pub credential_id: String,
+}
+pub fn decode_passkey_lookup_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+) -> Result<PasskeyLookupClaims, RequestError> {
+    let token = extract_token(metadata)?;
+    let claims = jwt_keys
+        .decode::<PasskeyLookupClaims>(token, &PASSKEY_LOOKUP_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode authorization token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    Ok(claims)
+}
+
+/**
+ * Event recorded by service-auth, for the logins that fail before reaching `CreateUser`.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct AuditClaims {
+    pub r#type: i32,
+    pub user_id: String,
+    pub provider: String,
+    pub ip: String,
+    pub user_agent: String,
+    pub detail: String,
+}
+pub fn decode_audit_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+) -> Result<AuditClaims, RequestError> {
+    let token = extract_token(metadata)?;
+    let claims = jwt_keys
+        .decode::<AuditClaims>(token, &AUDIT_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode authorization token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    Ok(claims)
+}
+
+/**
+ * Call between services, with no user behind it, see `DeleteUserData`, `CreateDataExport`
+ * and `GetProfilesByUserIds`.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ServiceClaims {
+    pub service: String,
+    pub exp: i64,
+}
+/**
+ * `audience` is the receiving service, e.g. `service-notes`.
+ */
+pub fn generate_service_token(jwt_keys: &JwtKeys, audience: &str) -> Result<MetadataValue<Ascii>> {
+    let token = jwt_keys.encode(
+        &ServiceClaims {
+            service: "service-users".to_string(),
+            // 5 minutes
+            exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 5,
+        },
+        &TokenKind {
+            typ: SERVICE_TOKEN_TYP,
+            signers: &["users"],
+            audience: Some(audience),
+        },
+    )?;
+    Ok(format!("bearer {}", token).parse()?)
+}
+
+/**
+ * Only accepts a token signed by the key of `caller`, e.g. `users`, for this service.
+ */
+pub fn decode_service_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+    caller: &str,
+) -> Result<ServiceClaims, RequestError> {
+    let token = extract_token(metadata)?;
+    let kind = TokenKind {
+        typ: SERVICE_TOKEN_TYP,
+        signers: &[caller],
+        audience: Some("service-users"),
+    };
+    let claims = jwt_keys
+        .decode::<ServiceClaims>(token, &kind)
+        .map_err(|e| {
+            tracing::error!("Failed to decode service token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    if claims.service != format!("service-{}", caller) {
+        tracing::error!("Unexpected service: {:?}", claims.service);
+        return Err(RequestError::PermissionDenied("Permission denied"));
+    }
+    Ok(claims)
+}
+
+/**
+ * The admin acting as the user, the `act` claim of RFC 8693, see `ImpersonateUser`.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Actor {
+    pub sub: String,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Claims {
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
 }
-pub fn decode_token(metadata: &tonic::metadata::MetadataMap, jwt_secret: &str) -> Result<Claims, tonic::Status> {
+pub fn decode_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+) -> Result<Claims, RequestError> {
     let token = extract_token(metadata)?;
-    let token_message = jsonwebtoken::decode::<Claims>(
-        token,
-        &jsonwebtoken::DecodingKey::from_secret(jwt_secret.as_ref()),
-        &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256),
+    let claims = jwt_keys
+        .decode::<Claims>(token, &CLIENT_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode authorization token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    if let Some(act) = &claims.act {
+        tracing::info!(act = %act.sub, "Impersonated request");
+    }
+
+    Ok(claims)
+}
+
+/**
+ * Short-lived token returned by `RefreshToken`, `sid` is the refresh token family.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct AccessClaims {
+    pub id: String,
+    pub sid: String,
+    pub exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    // `UserRole` and the `Permission`s it grants, when the token was issued
+    #[serde(default)]
+    pub role: i32,
+    #[serde(default)]
+    pub permissions: Vec<i32>,
+}
+
+impl AccessClaims {
+    /**
+     * The shared authorization check, fails unless the role of the user grants the permission.
+     */
+    pub fn require(&self, permission: i32) -> Result<(), RequestError> {
+        if !self.permissions.contains(&permission) {
+            tracing::error!("Missing permission {:?}: {:?}", permission, self.id);
+            return Err(RequestError::PermissionDenied("Permission denied"));
+        }
+        Ok(())
+    }
+}
+
+pub fn generate_access_token(
+    jwt_keys: &JwtKeys,
+    user_id: &str,
+    family_id: &str,
+    role: i32,
+    permissions: Vec<i32>,
+) -> Result<String> {
+    jwt_keys.encode(
+        &AccessClaims {
+            id: user_id.to_string(),
+            sid: family_id.to_string(),
+            // 15 minutes
+            exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 15,
+            act: None,
+            role,
+            permissions,
+        },
+        &ACCESS_TOKEN,
+    )
+}
+pub fn generate_impersonation_token(
+    jwt_keys: &JwtKeys,
+    user_id: &str,
+    family_id: &str,
+    role: i32,
+    permissions: Vec<i32>,
+    impersonator_id: &str,
+) -> Result<String> {
+    jwt_keys.encode(
+        &AccessClaims {
+            id: user_id.to_string(),
+            sid: family_id.to_string(),
+            // 15 minutes, the session can't be refreshed
+            exp: time::OffsetDateTime::now_utc().unix_timestamp() + 60 * 15,
+            act: Some(Actor {
+                sub: impersonator_id.to_string(),
+            }),
+            role,
+            permissions,
+        },
+        &ACCESS_TOKEN,
     )
-    .map_err(|e| {
-        tracing::error!("Failed to decode authorization token: {:?}", e);
-        tonic::Status::unauthenticated("Invalid authorization token")
-    })?;
+}
+pub fn decode_access_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+) -> Result<AccessClaims, RequestError> {
+    // The client forwards the access token in its own signed token
+    let access_token = decode_token(metadata, jwt_keys)?.id;
+    let claims = jwt_keys
+        .decode::<AccessClaims>(&access_token, &ACCESS_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode access token: {:?}", e);
+            RequestError::Unauthenticated("Invalid access token")
+        })?;
+    if let Some(act) = &claims.act {
+        tracing::info!(act = %act.sub, "Impersonated request");
+    }
+    Ok(claims)
+}
+
+/**
+ * Billing, account deletion, sign-in methods and sessions are left to the user, an admin
+ * impersonating them can't use them.
+ */
+pub fn reject_impersonation(claims: &AccessClaims) -> Result<(), RequestError> {
+    if let Some(act) = &claims.act {
+        tracing::error!(act = %act.sub, "Impersonated session is not allowed");
+        return Err(RequestError::PermissionDenied(
+            "Not allowed while impersonating",
+        ));
+    }
+    Ok(())
+}
 
-    Ok(token_message.claims)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt_keys() -> JwtKeys {
+        let secret = b"secret";
+        JwtKeys {
+            algorithm: Algorithm::HS256,
+            key_set: Arc::new(RwLock::new(KeySet {
+                kid: Some("users".to_string()),
+                encoding_key: EncodingKey::from_secret(secret),
+                decoding_keys: HashMap::from([(
+                    "users".to_string(),
+                    DecodingKey::from_secret(secret),
+                )]),
+            })),
+        }
+    }
+
+    fn metadata(jwt_keys: &JwtKeys, act: Option<Actor>) -> tonic::metadata::MetadataMap {
+        let exp = time::OffsetDateTime::now_utc().unix_timestamp() + 60;
+        let access_token = jwt_keys
+            .encode(
+                &AccessClaims {
+                    id: uuid::Uuid::now_v7().to_string(),
+                    sid: uuid::Uuid::now_v7().to_string(),
+                    exp,
+                    act,
+                    role: 0,
+                    permissions: vec![],
+                },
+                &ACCESS_TOKEN,
+            )
+            .unwrap();
+        let token = jwt_keys
+            .encode(
+                &serde_json::json!({ "id": access_token, "exp": exp }),
+                &CLIENT_TOKEN,
+            )
+            .unwrap();
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        metadata.insert(
+            "x-authorization",
+            format!("bearer {}", token).parse().unwrap(),
+        );
+        metadata
+    }
+
+    #[test]
+    fn impersonated_token_is_refused() {
+        let jwt_keys = jwt_keys();
+        let metadata = metadata(
+            &jwt_keys,
+            Some(Actor {
+                sub: uuid::Uuid::now_v7().to_string(),
+            }),
+        );
+        let claims = decode_access_token(&metadata, &jwt_keys).unwrap();
+        assert!(matches!(
+            reject_impersonation(&claims),
+            Err(RequestError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn own_token_is_accepted() {
+        let jwt_keys = jwt_keys();
+        let metadata = metadata(&jwt_keys, None);
+        let claims = decode_access_token(&metadata, &jwt_keys).unwrap();
+        assert!(reject_impersonation(&claims).is_ok());
+    }
 }
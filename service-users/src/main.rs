@@ -1,12 +1,29 @@
 mod proto;
 mod migrations;
 mod grpc;
+mod health;
+mod admin_service;
+mod audit_db;
+mod audit_service;
+mod authz;
+mod deletion_db;
+mod deletion_service;
+mod export_db;
+mod export_service;
+mod identity_db;
+mod identity_service;
+mod passkey_db;
+mod passkey_service;
 mod profile_service;
 mod profile_validation;
 mod profile_db;
+mod session_service;
 mod stripe_service;
 mod stripe_db;
 mod token_db;
+mod totp;
+mod two_factor_db;
+mod two_factor_service;
 mod user_service;
 mod user_db;
 
@@ -27,6 +44,9 @@ async fn main() -> Result<()> {
     let filter = &env.rust_log;
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
+    // Reload the JWT keys periodically, so they can be rotated without a restart
+    env.jwt_keys.spawn_reload();
+
     // Connect to database
     let pool = service_users::connect_to_db(&env).context("Failed to connect to database")?;
     tracing::info!("Connected to database");
@@ -37,12 +57,21 @@ async fn main() -> Result<()> {
         .context("Failed to run migrations")?;
     tracing::info!("Migrations complete");
 
+    // Tell the other services about the deleted accounts, and purge them after the grace period
+    deletion_service::spawn_worker(env.clone(), pool.clone());
+    // Build the queued data exports
+    export_service::spawn_worker(env.clone(), pool.clone());
+
     // Run gRPC server
     let addr = format!("[::]:{}", env.port).parse()?;
     tracing::info!("gRPC server started on port: {:?}", env.port);
+    // Standard gRPC health checks, the service is ready once its dependencies answer
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health::spawn_readiness(health_reporter, pool.clone());
     let server = MyService { env, pool };
     let svc = UsersServiceServer::new(server);
     tonic::transport::Server::builder()
+        .add_service(health_service)
         .add_service(svc)
         .serve(addr)
         .await
@@ -18,8 +18,27 @@ pub async fn run_migrations(pool: &deadpool_postgres::Pool) -> Result<()> {
                 id uuid primary key,
                 created timestamptz not null default now(),
                 updated timestamptz not null default now(),
-                user_id uuid not null
+                user_id uuid not null,
+                family_id uuid not null,
+                rotated timestamptz not null default 'infinity',
+                replaced_by uuid,
+                revoked timestamptz not null default 'infinity',
+                user_agent text not null default '',
+                ip text not null default '',
+                last_used timestamptz not null default now()
             );
+            alter table tokens add column if not exists family_id uuid;
+            update tokens set family_id = id where family_id is null;
+            alter table tokens alter column family_id set not null;
+            alter table tokens add column if not exists rotated timestamptz not null default 'infinity';
+            alter table tokens add column if not exists replaced_by uuid;
+            alter table tokens add column if not exists revoked timestamptz not null default 'infinity';
+            alter table tokens add column if not exists user_agent text not null default '';
+            alter table tokens add column if not exists ip text not null default '';
+            alter table tokens add column if not exists last_used timestamptz not null default now();
+            alter table tokens add column if not exists impersonator_id uuid;
+            create index if not exists tokens_family_id_idx on tokens (family_id);
+            create index if not exists tokens_user_id_idx on tokens (user_id);
             drop trigger if exists set_timestamp on tokens;
             create trigger set_timestamp before update on tokens for each row execute procedure trigger_set_timestamp();
 
@@ -29,17 +48,117 @@ pub async fn run_migrations(pool: &deadpool_postgres::Pool) -> Result<()> {
                 updated timestamptz not null default current_timestamp,
                 deleted timestamptz not null default 'infinity',
                 email text unique not null,
-                sub text unique not null,
+                sub text not null,
                 role int not null,
                 avatar text not null default '',
                 subscription_id text not null default '',
                 subscription_end timestamptz not null default '-infinity',
-                subscription_check timestamptz not null default '-infinity',
-                unique (email, sub)
+                subscription_check timestamptz not null default '-infinity'
             );
+            alter table users drop constraint if exists users_sub_key;
+            alter table users drop constraint if exists users_email_sub_key;
             drop trigger if exists set_timestamp on users;
             create trigger set_timestamp before update on users for each row execute procedure trigger_set_timestamp();
 
+            create table if not exists identities (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                updated timestamptz not null default now(),
+                user_id uuid not null references users(id) on delete cascade,
+                provider text not null,
+                sub text not null,
+                email text not null,
+                unique (provider, sub)
+            );
+            create index if not exists identities_user_id_idx on identities (user_id);
+            drop trigger if exists set_timestamp on identities;
+            create trigger set_timestamp before update on identities for each row execute procedure trigger_set_timestamp();
+
+            create table if not exists totp (
+                user_id uuid primary key references users(id) on delete cascade,
+                created timestamptz not null default now(),
+                updated timestamptz not null default now(),
+                secret text not null,
+                confirmed timestamptz not null default 'infinity',
+                last_step bigint not null default 0
+            );
+            drop trigger if exists set_timestamp on totp;
+            create trigger set_timestamp before update on totp for each row execute procedure trigger_set_timestamp();
+
+            create table if not exists recovery_codes (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                user_id uuid not null references users(id) on delete cascade,
+                code_hash text not null,
+                used timestamptz not null default 'infinity'
+            );
+            create index if not exists recovery_codes_user_id_idx on recovery_codes (user_id);
+
+            create table if not exists two_factor_challenges (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                user_id uuid not null references users(id) on delete cascade,
+                attempts int not null default 0
+            );
+
+            create table if not exists role_settings (
+                role int primary key,
+                require_two_factor boolean not null default false
+            );
+
+            create table if not exists link_codes (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                user_id uuid not null references users(id) on delete cascade
+            );
+
+            create table if not exists passkeys (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                last_used timestamptz not null default now(),
+                user_id uuid not null references users(id) on delete cascade,
+                credential_id text not null unique,
+                public_key bytea not null,
+                sign_count bigint not null default 0,
+                name text not null default ''
+            );
+            create index if not exists passkeys_user_id_idx on passkeys (user_id);
+
+            create table if not exists audit_events (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                -- No foreign key, the events outlive the user
+                user_id uuid,
+                type int not null,
+                provider text not null default '',
+                ip text not null default '',
+                user_agent text not null default '',
+                detail text not null default ''
+            );
+            create index if not exists audit_events_user_id_idx on audit_events (user_id, created);
+            create index if not exists audit_events_type_idx on audit_events (type, created);
+            create or replace function audit_events_append_only ()
+            returns trigger
+            as $$
+            begin
+                -- The one change allowed, clearing the ip and user agent of a purged user
+                if tg_op = 'UPDATE'
+                    and new.id = old.id
+                    and new.created = old.created
+                    and new.user_id is not distinct from old.user_id
+                    and new.type = old.type
+                    and new.provider = old.provider
+                    and new.detail = old.detail
+                    and new.ip = ''
+                    and new.user_agent = '' then
+                    return new;
+                end if;
+                raise exception 'audit_events is append-only';
+            end;
+            $$ language plpgsql;
+            drop trigger if exists append_only on audit_events;
+            create trigger append_only before update or delete on audit_events for each row execute procedure audit_events_append_only();
+
             create table if not exists profiles (
                 id uuid primary key,
                 created timestamptz not null default current_timestamp,
@@ -57,9 +176,95 @@ pub async fn run_migrations(pool: &deadpool_postgres::Pool) -> Result<()> {
             );
             drop trigger if exists set_timestamp on profiles;
             create trigger set_timestamp before update on profiles for each row execute procedure trigger_set_timestamp();
+
+            create table if not exists account_deletions (
+                -- No foreign key, the row is kept as a record once the user is purged
+                user_id uuid primary key,
+                created timestamptz not null default now(),
+                updated timestamptz not null default now(),
+                purge_after timestamptz not null,
+                -- The other services confirmed the soft delete, then everything was deleted for good
+                propagated timestamptz not null default 'infinity',
+                purged timestamptz not null default 'infinity',
+                -- Claimed by a worker until then, so the replicas don't run the same deletion
+                locked_until timestamptz not null default '-infinity',
+                attempts int not null default 0,
+                last_error text not null default ''
+            );
+            create index if not exists account_deletions_pending_idx on account_deletions (locked_until) where purged = 'infinity';
+            drop trigger if exists set_timestamp on account_deletions;
+            create trigger set_timestamp before update on account_deletions for each row execute procedure trigger_set_timestamp();
+
+            create table if not exists data_exports (
+                id uuid primary key,
+                created timestamptz not null default now(),
+                updated timestamptz not null default now(),
+                user_id uuid not null references users(id) on delete cascade,
+                -- The archive, a file of the user in service-utils
+                file_id text not null default '',
+                finished timestamptz not null default 'infinity',
+                -- Claimed by a worker until then, so the replicas don't build the same export
+                locked_until timestamptz not null default '-infinity',
+                attempts int not null default 0,
+                last_error text not null default ''
+            );
+            create unique index if not exists data_exports_pending_idx on data_exports (user_id) where finished = 'infinity';
+            drop trigger if exists set_timestamp on data_exports;
+            create trigger set_timestamp before update on data_exports for each row execute procedure trigger_set_timestamp();
     "#,
         )
         .await?;
 
+    backfill_identities(&client).await?;
+
+    Ok(())
+}
+
+/**
+ * Users created before identities could only sign in with Google or GitHub, and their provider
+ * was never stored, the users table only has the sub. The provider is guessed from its format:
+ * GitHub ids are numbers of up to 18 digits, Google subs are 21 digit numbers. A user of another
+ * provider with such short numeric subs would be filed under GitHub, and get a new account on
+ * their next login. The users of each branch are logged, so a wrong guess can be found and fixed.
+ */
+async fn backfill_identities(client: &deadpool_postgres::Object) -> Result<()> {
+    let rows = client
+        .query(
+            r#"
+            insert into identities (id, user_id, provider, sub, email)
+            select id, id, case when sub ~ '^[0-9]{1,18}$' then 'github' else 'google' end, sub, email from users
+            where not exists (select 1 from identities where identities.user_id = users.id)
+            returning user_id, provider
+            "#,
+            &[],
+        )
+        .await?;
+    let updated = client
+        .query(
+            r#"
+            update identities set provider = case when sub ~ '^[0-9]{1,18}$' then 'github' else 'google' end
+            where provider = ''
+            returning user_id, provider
+            "#,
+            &[],
+        )
+        .await?;
+
+    for provider in ["github", "google"] {
+        let user_ids = rows
+            .iter()
+            .chain(updated.iter())
+            .filter(|row| row.get::<_, &str>("provider") == provider)
+            .map(|row| row.get::<_, uuid::Uuid>("user_id"))
+            .collect::<Vec<_>>();
+        if !user_ids.is_empty() {
+            tracing::warn!(
+                provider,
+                count = user_ids.len(),
+                user_ids = ?user_ids,
+                "Backfilled identities with a provider guessed from the sub"
+            );
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use deadpool_postgres::Object;
+use time::format_description::well_known::Iso8601;
+use uuid::Uuid;
+
+use crate::proto::Passkey;
+
+impl TryFrom<tokio_postgres::Row> for Passkey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let id: Uuid = value.try_get("id")?;
+        let created: time::OffsetDateTime = value.try_get("created")?;
+        let created: String = created.format(&Iso8601::DEFAULT)?.to_string();
+        let last_used: time::OffsetDateTime = value.try_get("last_used")?;
+        let last_used: String = last_used.format(&Iso8601::DEFAULT)?.to_string();
+        let user_id: Uuid = value.try_get("user_id")?;
+        let credential_id: String = value.try_get("credential_id")?;
+        let public_key: Vec<u8> = value.try_get("public_key")?;
+        let sign_count: i64 = value.try_get("sign_count")?;
+        let name: String = value.try_get("name")?;
+
+        Ok(Passkey {
+            id: id.to_string(),
+            created,
+            last_used,
+            user_id: user_id.to_string(),
+            credential_id,
+            public_key,
+            sign_count,
+            name,
+        })
+    }
+}
+
+pub async fn select_passkey_by_credential_id(
+    conn: &Object,
+    credential_id: &str,
+) -> Result<Option<Passkey>> {
+    let row = conn
+        .query_opt(
+            "select * from passkeys where credential_id = $1",
+            &[&credential_id],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(Passkey::try_from(row)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn select_passkeys_by_user_id(conn: &Object, user_id: &str) -> Result<Vec<Passkey>> {
+    let rows = conn
+        .query(
+            "select * from passkeys where user_id = $1 order by created",
+            &[&Uuid::from_str(user_id)?],
+        )
+        .await?;
+    rows.into_iter().map(Passkey::try_from).collect()
+}
+
+pub async fn insert_passkey(
+    conn: &Object,
+    user_id: &Uuid,
+    credential_id: &str,
+    public_key: &[u8],
+    sign_count: i64,
+    name: &str,
+) -> Result<()> {
+    conn.execute(
+        "insert into passkeys (id, user_id, credential_id, public_key, sign_count, name) values ($1, $2, $3, $4, $5, $6)",
+        &[&Uuid::now_v7(), user_id, &credential_id, &public_key, &sign_count, &name],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * The signature counter of an assertion has to grow, or the credential may have been cloned.
+ * Synced passkeys (iCloud Keychain, Google Password Manager) share the credential between
+ * devices, so they can't keep a counter and always send 0. The spec lets them, and rejecting
+ * them would lock out most passkey users, so 0 is accepted as long as it has always been 0.
+ * A credential that counted once and then sends 0 is rejected like any other regression.
+ */
+pub fn sign_count_is_valid(stored: i64, sign_count: i64) -> bool {
+    sign_count > stored || (stored == 0 && sign_count == 0)
+}
+
+/**
+ * Save the counter of the assertion, see `sign_count_is_valid`.
+ * The row is locked, so concurrent logins with the same counter can't both pass.
+ * Returns the user of the passkey, or None if the counter is not valid.
+ */
+pub async fn use_passkey(
+    conn: &mut Object,
+    credential_id: &str,
+    sign_count: i64,
+) -> Result<Option<Uuid>> {
+    let tr = conn.transaction().await?;
+    let row = tr
+        .query_opt(
+            "select user_id, sign_count from passkeys where credential_id = $1 for update",
+            &[&credential_id],
+        )
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let user_id: Uuid = row.try_get("user_id")?;
+    let stored: i64 = row.try_get("sign_count")?;
+    if !sign_count_is_valid(stored, sign_count) {
+        return Ok(None);
+    }
+    tr.execute(
+        "update passkeys set sign_count = $2, last_used = now() where credential_id = $1",
+        &[&credential_id, &sign_count],
+    )
+    .await?;
+    tr.commit().await?;
+    Ok(Some(user_id))
+}
+
+pub async fn delete_passkey(conn: &Object, user_id: &str, passkey_id: &str) -> Result<bool> {
+    let deleted = conn
+        .execute(
+            "delete from passkeys where id = $1 and user_id = $2",
+            &[&Uuid::from_str(passkey_id)?, &Uuid::from_str(user_id)?],
+        )
+        .await?;
+    Ok(deleted > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sign_count_is_valid;
+
+    #[test]
+    fn sign_count_has_to_grow() {
+        assert!(sign_count_is_valid(1, 2));
+        assert!(sign_count_is_valid(1, 100));
+        assert!(!sign_count_is_valid(5, 5));
+        assert!(!sign_count_is_valid(5, 4));
+    }
+
+    #[test]
+    fn sign_count_of_zero_is_accepted_only_without_a_counter() {
+        assert!(sign_count_is_valid(0, 0));
+        assert!(sign_count_is_valid(0, 1));
+        assert!(!sign_count_is_valid(1, 0));
+    }
+}
@@ -0,0 +1,263 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::proto::{AuditEventType, Passkey};
+use crate::user_db::StringOrUuid;
+use crate::user_service::invalid_link_code;
+
+/**
+ * Save the credential of a finished registration, to the user of the link code.
+ */
+pub async fn create_passkey(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_passkey_token(metadata, &env.jwt_keys)?;
+
+    let public_key = URL_SAFE_NO_PAD.decode(&claims.public_key).map_err(|e| {
+        tracing::error!("Failed to decode public key: {:?}", e);
+        Status::invalid_argument("Invalid public key")
+    })?;
+    if claims.credential_id.is_empty() || public_key.is_empty() {
+        return Err(Status::invalid_argument("Invalid credential"));
+    }
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let user_id = crate::identity_db::delete_link_code(&conn, &claims.link_code)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to use link code: {:?}", e);
+            invalid_link_code()
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Link code expired or already used");
+            invalid_link_code()
+        })?;
+
+    let existing = crate::passkey_db::select_passkey_by_credential_id(&conn, &claims.credential_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get passkey: {:?}", e);
+            Status::internal("Failed to get passkey")
+        })?;
+    if existing.is_some() {
+        return Err(Status::already_exists("Passkey is already registered"));
+    }
+
+    crate::passkey_db::insert_passkey(
+        &conn,
+        &user_id,
+        &claims.credential_id,
+        &public_key,
+        claims.sign_count,
+        &claims.name,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to insert passkey: {:?}", e);
+        Status::internal("Failed to insert passkey")
+    })?;
+
+    tracing::info!("create_passkey: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * The public key and counter, so service-auth can verify an assertion.
+ */
+pub async fn get_passkey(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<Passkey>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let credential_id =
+        service_users::decode_passkey_lookup_token(metadata, &env.jwt_keys)?.credential_id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let passkey = crate::passkey_db::select_passkey_by_credential_id(&conn, &credential_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get passkey: {:?}", e);
+            Status::internal("Failed to get passkey")
+        })?
+        .ok_or_else(|| Status::not_found("Passkey not found"))?;
+
+    tracing::info!("get_passkey: {:?}", start.elapsed());
+    Ok(Response::new(passkey))
+}
+
+/**
+ * Sign in with an assertion verified by service-auth. Returns the same response as `CreateUser`,
+ * the two-factor challenge is skipped when the authenticator verified the user.
+ */
+pub async fn passkey_login(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::LoginResponse>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_passkey_token(metadata, &env.jwt_keys)?;
+
+    let mut conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let user_id =
+        crate::passkey_db::use_passkey(&mut conn, &claims.credential_id, claims.sign_count)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to use passkey: {:?}", e);
+                Status::internal("Failed to use passkey")
+            })?;
+    let Some(user_id) = user_id else {
+        tracing::error!("Passkey not found, or its counter didn't grow");
+        crate::audit_service::record(
+            &conn,
+            metadata,
+            AuditEventType::LoginFailure,
+            None,
+            "passkey",
+            &format!("invalid passkey {}", claims.credential_id),
+        )
+        .await;
+        return Err(Status::unauthenticated("Invalid passkey"));
+    };
+
+    let user = crate::user_db::select_user_by_id(&conn, StringOrUuid::Uuid(user_id))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user: {:?}", e);
+            Status::internal("Failed to get user")
+        })?;
+    if user.deleted != "infinity" {
+        tracing::error!("User is deleted");
+        return Err(Status::unauthenticated("Unauthenticated"));
+    }
+
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::LoginSuccess,
+        Some(user_id),
+        "passkey",
+        "",
+    )
+    .await;
+
+    if !claims.user_verified {
+        if let Some(challenge_id) =
+            crate::two_factor_service::start_challenge(&conn, &user_id).await?
+        {
+            tracing::info!("passkey_login: {:?}", start.elapsed());
+            return Ok(Response::new(crate::proto::LoginResponse {
+                token: String::new(),
+                challenge_id: challenge_id.to_string(),
+            }));
+        }
+    }
+
+    let token = crate::token_db::insert_token(&conn, &user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to insert token: {:?}", e);
+            Status::internal("Failed to insert token")
+        })?;
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::TokenIssued,
+        Some(user_id),
+        "passkey",
+        "",
+    )
+    .await;
+
+    tracing::info!("passkey_login: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::LoginResponse {
+        token: token.to_string(),
+        challenge_id: String::new(),
+    }))
+}
+
+pub async fn get_passkeys(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<ReceiverStream<Result<Passkey, Status>>>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let user_id = service_users::decode_access_token(metadata, &env.jwt_keys)?.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let passkeys = crate::passkey_db::select_passkeys_by_user_id(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get passkeys: {:?}", e);
+            Status::internal("Failed to get passkeys")
+        })?;
+
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for passkey in passkeys {
+            if let Err(e) = tx.send(Ok(passkey)).await {
+                tracing::error!("Failed to send passkey: {:?}", e);
+                return;
+            }
+        }
+        tracing::info!("get_passkeys: {:?}", start.elapsed());
+    });
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+pub async fn delete_passkey(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let passkey_id = request.into_inner().id;
+    let deleted = crate::passkey_db::delete_passkey(&conn, &user_id, &passkey_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete passkey: {:?}", e);
+            Status::internal("Failed to delete passkey")
+        })?;
+    if !deleted {
+        return Err(Status::not_found("Passkey not found"));
+    }
+
+    tracing::info!("delete_passkey: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
@@ -69,6 +69,20 @@ pub async fn select_profile_by_user_id(conn: &Object, user_id: &str) -> Result<O
     Ok(profile)
 }
 
+pub async fn select_profiles_by_user_ids(conn: &Object, user_ids: &[Uuid]) -> Result<Vec<Profile>> {
+    let rows = conn
+        .query(
+            "select * from profiles where user_id = any($1) and deleted = 'infinity'",
+            &[&user_ids],
+        )
+        .await?;
+    let mut profiles = Vec::new();
+    for row in rows {
+        profiles.push(Profile::try_from(row)?);
+    }
+    Ok(profiles)
+}
+
 pub async fn insert_profile(conn: &Object, user_id: &str, profile: &Profile) -> Result<Profile> {
     let user_id: Uuid = Uuid::from_str(user_id)?;
     let profile: tokio_postgres::Row = conn.query_one(
@@ -1,4 +1,10 @@
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::proto::{AuditEventType, Ids, Profile};
 
 pub async fn get_profile_by_user_id(
     env: &service_users::Env,
@@ -7,7 +13,7 @@ pub async fn get_profile_by_user_id(
 ) -> Result<Response<crate::proto::Profile>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let user_id = service_users::decode_token(metadata, &env.jwt_secret)?.id;
+    let user_id = service_users::decode_token(metadata, &env.jwt_keys)?.id;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
@@ -25,6 +31,59 @@ pub async fn get_profile_by_user_id(
     Ok(Response::new(profile.unwrap_or_default()))
 }
 
+/**
+ * The profiles of many users in one query, for service-notes to attach the authors of the notes.
+ * Users without a profile are left out.
+ */
+pub async fn get_profiles_by_user_ids(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<Ids>,
+) -> Result<Response<ReceiverStream<Result<Profile, Status>>>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    service_users::decode_service_token(metadata, &env.jwt_keys, "notes")?;
+
+    let ids = request.into_inner().ids;
+    if ids.len() > 1000 {
+        return Err(Status::invalid_argument("Too many ids"));
+    }
+    let user_ids = ids
+        .iter()
+        .map(|id| Uuid::parse_str(id))
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| {
+            tracing::error!("Failed to parse user id: {:?}", e);
+            Status::invalid_argument("Invalid user id")
+        })?
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let profiles = crate::profile_db::select_profiles_by_user_ids(&conn, &user_ids)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get profiles: {:?}", e);
+            Status::internal("Failed to get profiles")
+        })?;
+
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for profile in profiles {
+            if let Err(e) = tx.send(Ok(profile)).await {
+                tracing::error!("Failed to send profile: {:?}", e);
+                return;
+            }
+        }
+        tracing::info!("get_profiles_by_user_ids: {:?}", start.elapsed());
+    });
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
 pub async fn create_profile(
     env: &service_users::Env,
     pool: &deadpool_postgres::Pool,
@@ -32,15 +91,20 @@ pub async fn create_profile(
 ) -> Result<Response<crate::proto::Profile>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let user_id = service_users::decode_token(metadata, &env.jwt_secret)?.id;
+    let user_id = service_users::decode_token(metadata, &env.jwt_keys)?.id;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
         Status::internal("Failed to get connection")
     })?;
 
-    let mut profile = request.into_inner();
+    let (metadata, _, mut profile) = request.into_parts();
     crate::profile_validation::Validation::validate(&profile)?;
+    let detail = if profile.id.is_empty() {
+        "created"
+    } else {
+        "updated"
+    };
 
     if profile.id.is_empty() {
         profile = crate::profile_db::insert_profile(&conn, &user_id, &profile)
@@ -58,6 +122,16 @@ pub async fn create_profile(
             })?;
     }
 
+    crate::audit_service::record(
+        &conn,
+        &metadata,
+        AuditEventType::ProfileUpdated,
+        uuid::Uuid::parse_str(&user_id).ok(),
+        "",
+        detail,
+    )
+    .await;
+
     tracing::info!("update_user: {:?}", start.elapsed());
     Ok(Response::new(profile))
 }
@@ -1,5 +1,3 @@
-use tonic::Status;
-
 use crate::proto::Profile;
 
 #[derive(serde::Serialize)]
@@ -9,11 +7,11 @@ pub struct Validator<'a> {
 }
 
 pub trait Validation {
-    fn validate(&self) -> Result<(), Status>;
+    fn validate(&self) -> Result<(), service_users::RequestError>;
 }
 
 impl Validation for Profile {
-    fn validate(&self) -> Result<(), Status> {
+    fn validate(&self) -> Result<(), service_users::RequestError> {
         let mut validators = Vec::new();
 
         // name is required and max length is 1000
@@ -46,10 +44,12 @@ impl Validation for Profile {
         } else {
             let json = serde_json::to_string(&validators);
             match json {
-                Ok(json) => Err(Status::invalid_argument(json)),
+                Ok(json) => Err(service_users::RequestError::InvalidArgument(json)),
                 Err(e) => {
                     tracing::error!("Failed to serialize validators: {:?}", e);
-                    Err(Status::internal("Failed to serialize validators"))
+                    Err(service_users::RequestError::Internal(
+                        "Failed to serialize validators",
+                    ))
                 }
             }
         }
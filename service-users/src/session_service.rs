@@ -0,0 +1,238 @@
+use time::format_description::well_known::Iso8601;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::proto::{AuditEventType, Permission, Session};
+use crate::user_db::StringOrUuid;
+
+impl TryFrom<crate::token_db::Token> for Session {
+    type Error = anyhow::Error;
+
+    fn try_from(value: crate::token_db::Token) -> std::result::Result<Self, Self::Error> {
+        Ok(Session {
+            id: value.family_id.to_string(),
+            created: value.created.format(&Iso8601::DEFAULT)?,
+            last_used: value.last_used.format(&Iso8601::DEFAULT)?,
+            user_agent: value.user_agent,
+            ip: value.ip,
+            current: false,
+            impersonated: value.impersonator_id.is_some(),
+        })
+    }
+}
+
+/**
+ * Revoke the session of the refresh token, its access tokens stop working on the next `Auth`.
+ */
+pub async fn logout(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let token_id = service_users::decode_token(metadata, &env.jwt_keys)?.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let token = crate::token_db::select_token_by_id(&conn, &token_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get token: {:?}", e);
+            Status::unauthenticated("Failed to get token")
+        })?;
+
+    crate::token_db::revoke_token_family(&conn, &token.family_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke session: {:?}", e);
+            Status::internal("Failed to revoke session")
+        })?;
+
+    tracing::info!("logout: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+pub async fn get_sessions(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<ReceiverStream<Result<Session, Status>>>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let tokens = crate::token_db::select_active_tokens_by_user_id(&conn, &claims.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get sessions: {:?}", e);
+            Status::internal("Failed to get sessions")
+        })?;
+
+    let (tx, rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for token in tokens {
+            let session = Session::try_from(token)
+                .map(|mut session| {
+                    session.current = session.id == claims.sid;
+                    session
+                })
+                .map_err(|e| {
+                    tracing::error!("Failed to convert session: {:?}", e);
+                    Status::internal("Failed to convert session")
+                });
+            if let Err(e) = tx.send(session).await {
+                tracing::error!("Failed to send session: {:?}", e);
+                return;
+            }
+        }
+        tracing::info!("get_sessions: {:?}", start.elapsed());
+    });
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+pub async fn revoke_session(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let session_id = request.into_inner().id;
+    let revoked = crate::token_db::revoke_user_token_family(&conn, &claims.id, &session_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke session: {:?}", e);
+            Status::internal("Failed to revoke session")
+        })?;
+    if !revoked {
+        return Err(Status::not_found("Session not found"));
+    }
+
+    tracing::info!("revoke_session: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+pub async fn revoke_other_sessions(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    crate::token_db::revoke_other_token_families(&conn, &claims.id, &claims.sid)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke sessions: {:?}", e);
+            Status::internal("Failed to revoke sessions")
+        })?;
+
+    tracing::info!("revoke_other_sessions: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * Sign in as another user, for support. Needs `IMPERSONATE_USERS`, and users with any permission
+ * can't be impersonated.
+ * The session has no refresh token, it ends with its access token after 15 minutes.
+ * The access token names the admin in its `act` claim, and the user sees the session in their list.
+ */
+pub async fn impersonate_user(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Id>,
+) -> Result<Response<crate::proto::AuthResponse>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let user_id = uuid::Uuid::parse_str(&request.get_ref().id)
+        .map_err(|_| Status::invalid_argument("Invalid user id"))?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    let claims =
+        crate::authz::authorize(env, &conn, metadata, Permission::ImpersonateUsers).await?;
+    service_users::reject_impersonation(&claims)?;
+
+    let admin_id = uuid::Uuid::parse_str(&claims.id).map_err(|e| {
+        tracing::error!("Failed to parse user id: {:?}", e);
+        Status::internal("Failed to parse user id")
+    })?;
+
+    let user =
+        crate::user_service::select_active_user(env, &conn, StringOrUuid::Uuid(user_id)).await?;
+    // Impersonating them would grant their permissions
+    let permissions = crate::authz::role_permissions(user.role);
+    if !permissions.is_empty() {
+        return Err(Status::permission_denied(
+            "Users with permissions can't be impersonated",
+        ));
+    }
+
+    let user_agent = crate::user_service::metadata_value(metadata, "x-user-agent", 512);
+    let ip = crate::user_service::metadata_value(metadata, "x-client-ip", 64);
+    let family_id =
+        crate::token_db::insert_impersonation_token(&conn, &user_id, &admin_id, &user_agent, &ip)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to insert token: {:?}", e);
+                Status::internal("Failed to insert token")
+            })?;
+    let access_token = service_users::generate_impersonation_token(
+        &env.jwt_keys,
+        &user.id,
+        &family_id.to_string(),
+        user.role,
+        permissions.clone(),
+        &claims.id,
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to generate access token: {:?}", e);
+        Status::internal("Failed to generate access token")
+    })?;
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::ImpersonationStarted,
+        Some(user_id),
+        "",
+        &format!("admin {}", claims.id),
+    )
+    .await;
+    tracing::warn!(act = %claims.id, "Impersonating user: {:?}", user.id);
+
+    tracing::info!("impersonate_user: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::AuthResponse {
+        user: Some(user),
+        token: String::new(),
+        access_token,
+        impersonator_id: claims.id,
+        permissions,
+    }))
+}
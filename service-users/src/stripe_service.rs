@@ -1,11 +1,13 @@
 use anyhow::Result;
 use std::str::FromStr;
 use stripe::{
-    Client, CreateCustomer, Customer, CustomerId, ListSubscriptions, Subscription,
-    SubscriptionStatus,
+    CancelSubscription, Client, CreateCustomer, Customer, CustomerId, ListSubscriptions,
+    Subscription, SubscriptionStatus,
 };
 use tonic::{Request, Response, Status};
 
+use crate::proto::AuditEventType;
+
 pub async fn check_subscription(
     env: &service_users::Env,
     conn: &deadpool_postgres::Object,
@@ -54,12 +56,48 @@ pub async fn check_subscription(
                 subscription.current_period_end,
             )
             .await?;
+            // Checked in the background of `Auth`, so there is no client to record
+            crate::audit_service::record(
+                conn,
+                &tonic::metadata::MetadataMap::new(),
+                AuditEventType::SubscriptionUpdated,
+                uuid::Uuid::parse_str(&user.id).ok(),
+                "stripe",
+                &format!("active until {}", subscription.current_period_end),
+            )
+            .await;
             return Ok(true);
         }
     }
     Ok(false)
 }
 
+/**
+ * Cancel every subscription of the user right away, when the account is deleted.
+ * Canceled subscriptions are not listed, so this can be called again after a failure.
+ */
+pub async fn cancel_subscriptions(
+    env: &service_users::Env,
+    user: &crate::proto::User,
+) -> Result<()> {
+    if user.subscription_id.is_empty() {
+        return Ok(());
+    }
+
+    let secret_key = env.stripe_api_key.clone();
+    let client = Client::new(secret_key);
+
+    let customer_id: CustomerId = CustomerId::from_str(&user.subscription_id)?;
+    let mut params: ListSubscriptions<'_> = ListSubscriptions::new();
+    params.customer = Some(customer_id);
+
+    let subscriptions = Subscription::list(&client, &params).await?.data;
+    for subscription in subscriptions {
+        Subscription::cancel(&client, &subscription.id, CancelSubscription::new()).await?;
+    }
+    Ok(())
+}
+
 pub async fn create_stripe_checkout(
     env: &service_users::Env,
     pool: &deadpool_postgres::Pool,
@@ -67,26 +105,35 @@ pub async fn create_stripe_checkout(
 ) -> Result<Response<crate::proto::StripeUrlResponse>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let user_id = service_users::decode_token(metadata, &env.jwt_secret)?.id;
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
         Status::internal("Failed to get connection")
     })?;
     let user =
-        crate::user_db::select_user_by_id(&conn, crate::user_db::StringOrUuid::String(user_id))
+        crate::user_db::select_user_by_id(&conn, crate::user_db::StringOrUuid::String(claims.id))
             .await
             .map_err(|e| {
                 tracing::error!("Failed to auth user: {:?}", e);
                 Status::unauthenticated("Failed to auth user")
             })?;
 
-    let url = create_checkout(env, conn, user)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to create checkout session: {:?}", e);
-            Status::internal("Failed to create checkout session")
-        })?;
+    let user_uuid = uuid::Uuid::parse_str(&user.id).ok();
+    let url = create_checkout(env, &conn, user).await.map_err(|e| {
+        tracing::error!("Failed to create checkout session: {:?}", e);
+        Status::internal("Failed to create checkout session")
+    })?;
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::SubscriptionCheckout,
+        user_uuid,
+        "",
+        "",
+    )
+    .await;
 
     tracing::info!("create_stripe_checkout: {:?}", start.elapsed());
     Ok(Response::new(crate::proto::StripeUrlResponse { url }))
@@ -99,24 +146,35 @@ pub async fn create_stripe_portal(
 ) -> Result<Response<crate::proto::StripeUrlResponse>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let user_id = service_users::decode_token(metadata, &env.jwt_secret)?.id;
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
         Status::internal("Failed to get connection")
     })?;
     let user =
-        crate::user_db::select_user_by_id(&conn, crate::user_db::StringOrUuid::String(user_id))
+        crate::user_db::select_user_by_id(&conn, crate::user_db::StringOrUuid::String(claims.id))
             .await
             .map_err(|e| {
                 tracing::error!("Failed to auth user: {:?}", e);
                 Status::unauthenticated("Failed to auth user")
             })?;
 
+    let user_uuid = uuid::Uuid::parse_str(&user.id).ok();
     let url = create_portal(env, &conn, user).await.map_err(|e| {
         tracing::error!("Failed to create portal session: {:?}", e);
         Status::internal("Failed to create portal session")
     })?;
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::SubscriptionPortal,
+        user_uuid,
+        "",
+        "",
+    )
+    .await;
 
     tracing::info!("create_stripe_portal: {:?}", start.elapsed());
     Ok(Response::new(crate::proto::StripeUrlResponse { url }))
@@ -124,7 +182,7 @@ pub async fn create_stripe_portal(
 
 async fn create_checkout(
     env: &service_users::Env,
-    conn: deadpool_postgres::Object,
+    conn: &deadpool_postgres::Object,
     user: crate::proto::User,
 ) -> Result<String> {
     let secret_key = env.stripe_api_key.clone();
@@ -135,8 +193,7 @@ async fn create_checkout(
     let mut customer_id = user.subscription_id;
     if customer_id.is_empty() {
         customer_id = create_customer(&client, &user.email).await?;
-        crate::stripe_db::update_user_subscription_id(&conn, &user.id, &customer_id.clone())
-            .await?;
+        crate::stripe_db::update_user_subscription_id(conn, &user.id, &customer_id.clone()).await?;
     }
 
     let success_url = format!("{}/subscription?success", client_url);
@@ -160,7 +217,7 @@ async fn create_checkout(
         .url
         .ok_or_else(|| anyhow::anyhow!("Missing session url"))?;
 
-    let _ = crate::stripe_db::remove_user_subscription_check(&conn, &user.id).await?;
+    let _ = crate::stripe_db::remove_user_subscription_check(conn, &user.id).await?;
 
     Ok(session_url)
 }
@@ -1,13 +1,34 @@
 use anyhow::Result;
 use deadpool_postgres::Object;
 use std::str::FromStr;
+use tokio_postgres::types::Timestamp;
 use uuid::Uuid;
 
+/**
+ * Opaque refresh token. Every login starts a new family, and each refresh replaces the token
+ * with a new one from the same family. A family is what the user sees as a session.
+ */
+#[derive(Clone)]
 pub struct Token {
     pub id: Uuid,
     pub created: time::OffsetDateTime,
-    pub updated: time::OffsetDateTime,
     pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub rotated: Option<time::OffsetDateTime>,
+    pub replaced_by: Option<Uuid>,
+    pub revoked: Option<time::OffsetDateTime>,
+    pub user_agent: String,
+    pub ip: String,
+    pub last_used: time::OffsetDateTime,
+    // The admin that started the session, see `ImpersonateUser`
+    pub impersonator_id: Option<Uuid>,
+}
+
+fn finite(timestamp: Timestamp<time::OffsetDateTime>) -> Option<time::OffsetDateTime> {
+    match timestamp {
+        Timestamp::Value(date) => Some(date),
+        _ => None,
+    }
 }
 
 impl TryFrom<tokio_postgres::Row> for Token {
@@ -16,8 +37,15 @@ impl TryFrom<tokio_postgres::Row> for Token {
         Ok(Token {
             id: value.try_get("id")?,
             created: value.try_get("created")?,
-            updated: value.try_get("updated")?,
             user_id: value.try_get("user_id")?,
+            family_id: value.try_get("family_id")?,
+            rotated: finite(value.try_get("rotated")?),
+            replaced_by: value.try_get("replaced_by")?,
+            revoked: finite(value.try_get("revoked")?),
+            user_agent: value.try_get("user_agent")?,
+            ip: value.try_get("ip")?,
+            last_used: value.try_get("last_used")?,
+            impersonator_id: value.try_get("impersonator_id")?,
         })
     }
 }
@@ -33,17 +61,170 @@ pub async fn select_token_by_id(conn: &Object, token_id: &str) -> Result<Token>
     Ok(token)
 }
 
+/**
+ * Insert the first token of a new family, on login.
+ */
 pub async fn insert_token(conn: &Object, user_id: &str) -> Result<Uuid> {
     let id: Uuid = Uuid::now_v7();
     let user_id = Uuid::from_str(user_id)?;
     conn.execute(
-        "insert into tokens (id, user_id) values ($1, $2)",
+        "insert into tokens (id, user_id, family_id) values ($1, $2, $1)",
         &[&id, &user_id],
     )
     .await?;
     Ok(id)
 }
 
+/**
+ * Start a session of the user for an admin. The token is never returned, so the session
+ * can't be refreshed and ends with its access token.
+ */
+pub async fn insert_impersonation_token(
+    conn: &Object,
+    user_id: &Uuid,
+    impersonator_id: &Uuid,
+    user_agent: &str,
+    ip: &str,
+) -> Result<Uuid> {
+    let id: Uuid = Uuid::now_v7();
+    conn.execute(
+        "insert into tokens (id, user_id, family_id, user_agent, ip, impersonator_id) values ($1, $2, $1, $3, $4, $5)",
+        &[&id, user_id, &user_agent, &ip, impersonator_id],
+    )
+    .await?;
+    Ok(id)
+}
+
+/**
+ * Replace a valid token with a new one from the same family, in a single statement.
+ * Returns None if the token does not exist, expired, was revoked or was already rotated.
+ * Empty user agent and ip are taken from the old token.
+ */
+pub async fn rotate_token(
+    conn: &Object,
+    token_id: &str,
+    user_agent: &str,
+    ip: &str,
+) -> Result<Option<Token>> {
+    let id: Uuid = Uuid::now_v7();
+    let token = conn
+        .query_opt(
+            "
+            with old as (
+                update tokens set rotated = now(), replaced_by = $2
+                where id = $1
+                and rotated = 'infinity'
+                and revoked = 'infinity'
+                and impersonator_id is null
+                and created > now() - interval '7 days'
+                returning user_id, family_id, user_agent, ip
+            )
+            insert into tokens (id, user_id, family_id, user_agent, ip)
+            select $2, user_id, family_id, coalesce(nullif($3, ''), user_agent), coalesce(nullif($4, ''), ip) from old
+            returning *
+            ",
+            &[&Uuid::from_str(token_id)?, &id, &user_agent, &ip],
+        )
+        .await?;
+    token.map(Token::try_from).transpose()
+}
+
+/**
+ * Mark the session as used. Returns false if it was revoked or expired.
+ */
+pub async fn touch_token_family(conn: &Object, family_id: &str) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "
+            update tokens set last_used = now()
+            where family_id = $1
+            and rotated = 'infinity'
+            and revoked = 'infinity'
+            and created > now() - interval '7 days'
+            ",
+            &[&Uuid::from_str(family_id)?],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+/**
+ * Active sessions of the user, one token per family.
+ */
+pub async fn select_active_tokens_by_user_id(conn: &Object, user_id: &str) -> Result<Vec<Token>> {
+    let tokens = conn
+        .query(
+            "
+            select * from tokens
+            where user_id = $1
+            and rotated = 'infinity'
+            and revoked = 'infinity'
+            and created > now() - interval '7 days'
+            order by last_used desc
+            ",
+            &[&Uuid::from_str(user_id)?],
+        )
+        .await?;
+    tokens.into_iter().map(Token::try_from).collect()
+}
+
+/**
+ * Revoke every token of the family, used when a rotated token is presented again.
+ */
+pub async fn revoke_token_family(conn: &Object, family_id: &Uuid) -> Result<()> {
+    conn.execute(
+        "update tokens set revoked = now() where family_id = $1 and revoked = 'infinity'",
+        &[family_id],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * Revoke one session of the user. Returns false if the user has no such session.
+ */
+pub async fn revoke_user_token_family(
+    conn: &Object,
+    user_id: &str,
+    family_id: &str,
+) -> Result<bool> {
+    let revoked = conn
+        .execute(
+            "update tokens set revoked = now() where user_id = $1 and family_id = $2 and revoked = 'infinity'",
+            &[&Uuid::from_str(user_id)?, &Uuid::from_str(family_id)?],
+        )
+        .await?;
+    Ok(revoked > 0)
+}
+
+/**
+ * Revoke every session of the user, except the current one.
+ */
+pub async fn revoke_other_token_families(
+    conn: &Object,
+    user_id: &str,
+    family_id: &str,
+) -> Result<()> {
+    conn.execute(
+        "update tokens set revoked = now() where user_id = $1 and family_id != $2 and revoked = 'infinity'",
+        &[&Uuid::from_str(user_id)?, &Uuid::from_str(family_id)?],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * Revoke every session of the user, when an admin signs them out or deletes them.
+ */
+pub async fn revoke_user_token_families(conn: &Object, user_id: &Uuid) -> Result<()> {
+    conn.execute(
+        "update tokens set revoked = now() where user_id = $1 and revoked = 'infinity'",
+        &[user_id],
+    )
+    .await?;
+    Ok(())
+}
+
 // 7 days
 pub async fn delete_old_tokens(client: &Object) -> Result<()> {
     client
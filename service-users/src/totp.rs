@@ -0,0 +1,239 @@
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/**
+ * Time-based one-time passwords (RFC 6238), with the defaults every authenticator app supports:
+ * HMAC-SHA1, 6 digits and 30 second steps.
+ */
+const STEP: i64 = 30;
+const DIGITS: u32 = 6;
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// Without similar looking characters
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/**
+ * New random secret, base32 encoded as expected by the otpauth uri.
+ */
+pub fn generate_secret() -> String {
+    let bytes: [u8; 20] = rand::random();
+    base32_encode(&bytes)
+}
+
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        STEP
+    )
+}
+
+/**
+ * Check the code against the current step, and one step before and after for clock drift.
+ * Steps up to `last_step` were already used, so their codes are rejected.
+ * Returns the matched step, the caller saves it only if it's still after the last one.
+ */
+pub fn verify(secret: &str, code: &str, now: i64, last_step: i64) -> Option<i64> {
+    let secret = base32_decode(secret)?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let current = now / STEP;
+    (current - 1..=current + 1)
+        .filter(|step| *step > last_step)
+        .find(|step| hotp(&secret, *step) == Some(code))
+}
+
+fn hotp(secret: &[u8], step: i64) -> Option<u32> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).ok()?;
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    Some(binary % 10u32.pow(DIGITS))
+}
+
+/**
+ * Recovery codes are only shown once, the database keeps their hashes.
+ */
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| {
+            let code: String = (0..10)
+                .map(|_| RECOVERY_ALPHABET[rng.gen_range(0..RECOVERY_ALPHABET.len())] as char)
+                .collect();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect()
+}
+
+pub fn hash_recovery_code(code: &str) -> String {
+    let code = code.trim().to_lowercase().replace('-', "");
+    format!("{:x}", Sha256::digest(code.as_bytes()))
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    output
+}
+
+fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in data.trim_end_matches('=').bytes() {
+        let value = BASE32.iter().position(|b| *b == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "12345678901234567890", the secret of the RFC 4226 and RFC 6238 test vectors
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    fn code(step: i64) -> String {
+        let secret = base32_decode(SECRET).unwrap();
+        format!("{:06}", hotp(&secret, step).unwrap())
+    }
+
+    #[test]
+    fn base32_round_trips() {
+        assert_eq!(base32_encode(b"12345678901234567890"), SECRET);
+        assert_eq!(base32_decode(SECRET).unwrap(), b"12345678901234567890");
+        assert_eq!(
+            base32_decode(&SECRET.to_lowercase()).unwrap(),
+            b"12345678901234567890"
+        );
+        assert!(base32_decode("GEZ1").is_none());
+    }
+
+    #[test]
+    fn hotp_matches_rfc_4226() {
+        // Appendix D
+        let expected = [
+            755224, 287082, 359152, 969429, 338314, 254676, 287922, 162583, 399871, 520489,
+        ];
+        let secret = base32_decode(SECRET).unwrap();
+        for (counter, value) in expected.iter().enumerate() {
+            assert_eq!(hotp(&secret, counter as i64), Some(*value));
+        }
+    }
+
+    #[test]
+    fn totp_matches_rfc_6238() {
+        // Appendix B, SHA1, the last 6 of the 8 digits
+        let expected = [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+            (20000000000, "353130"),
+        ];
+        for (time, value) in expected {
+            assert_eq!(verify(SECRET, value, time, 0), Some(time / STEP));
+        }
+    }
+
+    #[test]
+    fn one_step_of_drift_is_accepted() {
+        let now = 1111111111;
+        let current = now / STEP;
+        assert_eq!(
+            verify(SECRET, &code(current - 1), now, 0),
+            Some(current - 1)
+        );
+        assert_eq!(verify(SECRET, &code(current), now, 0), Some(current));
+        assert_eq!(
+            verify(SECRET, &code(current + 1), now, 0),
+            Some(current + 1)
+        );
+        assert_eq!(verify(SECRET, &code(current - 2), now, 0), None);
+        assert_eq!(verify(SECRET, &code(current + 2), now, 0), None);
+    }
+
+    #[test]
+    fn used_steps_are_rejected() {
+        let now = 1111111111;
+        let current = now / STEP;
+        // The same code again
+        assert_eq!(verify(SECRET, &code(current), now, current), None);
+        // An older code after a newer one
+        assert_eq!(verify(SECRET, &code(current - 1), now, current), None);
+        // The next code is still accepted
+        assert_eq!(
+            verify(SECRET, &code(current + 1), now, current),
+            Some(current + 1)
+        );
+    }
+
+    #[test]
+    fn malformed_codes_are_rejected() {
+        let now = 59;
+        assert_eq!(verify(SECRET, " 287082 ", now, 0), Some(1));
+        assert_eq!(verify(SECRET, "28708", now, 0), None);
+        assert_eq!(verify(SECRET, "0287082", now, 0), None);
+        assert_eq!(verify(SECRET, "+87082", now, 0), None);
+        assert_eq!(verify(SECRET, "abcdef", now, 0), None);
+        assert_eq!(verify("not base32!", "287082", now, 0), None);
+    }
+
+    #[test]
+    fn recovery_codes_are_hashed_without_formatting() {
+        let codes = generate_recovery_codes(10);
+        assert_eq!(codes.len(), 10);
+        let code = &codes[0];
+        assert_eq!(code.len(), 11);
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&format!(" {} ", code.to_uppercase().replace('-', "")))
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use deadpool_postgres::Object;
+use tokio_postgres::types::Timestamp;
+use uuid::Uuid;
+
+pub struct Totp {
+    pub secret: String,
+    pub confirmed: bool,
+    // The step of the last accepted code
+    pub last_step: i64,
+}
+
+impl TryFrom<tokio_postgres::Row> for Totp {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tokio_postgres::Row) -> std::result::Result<Self, Self::Error> {
+        let secret: String = value.try_get("secret")?;
+        let confirmed: Timestamp<time::OffsetDateTime> = value.try_get("confirmed")?;
+        let last_step: i64 = value.try_get("last_step")?;
+
+        Ok(Totp {
+            secret,
+            confirmed: matches!(confirmed, Timestamp::Value(_)),
+            last_step,
+        })
+    }
+}
+
+pub async fn select_totp(conn: &Object, user_id: &str) -> Result<Option<Totp>> {
+    let row = conn
+        .query_opt(
+            "select * from totp where user_id = $1",
+            &[&Uuid::from_str(user_id)?],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(Totp::try_from(row)?)),
+        None => Ok(None),
+    }
+}
+
+/**
+ * Start the enrolment with a new secret. Returns false if two-factor is already enabled.
+ */
+pub async fn upsert_totp(conn: &Object, user_id: &str, secret: &str) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "
+            insert into totp (user_id, secret) values ($1, $2)
+            on conflict (user_id) do update set secret = $2, last_step = 0
+            where totp.confirmed = 'infinity'
+            ",
+            &[&Uuid::from_str(user_id)?, &secret],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+pub async fn confirm_totp(conn: &Object, user_id: &str, step: i64) -> Result<()> {
+    conn.execute(
+        "update totp set confirmed = now(), last_step = $2 where user_id = $1",
+        &[&Uuid::from_str(user_id)?, &step],
+    )
+    .await?;
+    Ok(())
+}
+
+/**
+ * Every code can be used once, so only steps after the last used one are accepted.
+ */
+pub async fn use_totp_step(conn: &Object, user_id: &str, step: i64) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "update totp set last_step = $2 where user_id = $1 and last_step < $2",
+            &[&Uuid::from_str(user_id)?, &step],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+pub async fn delete_totp(conn: &Object, user_id: &str) -> Result<()> {
+    let user_id = Uuid::from_str(user_id)?;
+    conn.execute("delete from totp where user_id = $1", &[&user_id])
+        .await?;
+    conn.execute("delete from recovery_codes where user_id = $1", &[&user_id])
+        .await?;
+    Ok(())
+}
+
+/**
+ * Replace the recovery codes of the user.
+ */
+pub async fn insert_recovery_codes(conn: &Object, user_id: &str, hashes: &[String]) -> Result<()> {
+    let user_id = Uuid::from_str(user_id)?;
+    conn.execute("delete from recovery_codes where user_id = $1", &[&user_id])
+        .await?;
+    for hash in hashes {
+        conn.execute(
+            "insert into recovery_codes (id, user_id, code_hash) values ($1, $2, $3)",
+            &[&Uuid::now_v7(), &user_id, hash],
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn use_recovery_code(conn: &Object, user_id: &str, hash: &str) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "update recovery_codes set used = now() where user_id = $1 and code_hash = $2 and used = 'infinity'",
+            &[&Uuid::from_str(user_id)?, &hash],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+/**
+ * Whether the user has two-factor enabled, and whether their role requires it.
+ */
+pub async fn select_two_factor_status(
+    conn: &Object,
+    user_id: &str,
+    role: i32,
+) -> Result<(bool, bool)> {
+    let row = conn
+        .query_one(
+            "
+            select
+                exists (select 1 from totp where user_id = $1 and confirmed != 'infinity') as enabled,
+                coalesce((select require_two_factor from role_settings where role = $2), false) as required
+            ",
+            &[&Uuid::from_str(user_id)?, &role],
+        )
+        .await?;
+    Ok((row.try_get("enabled")?, row.try_get("required")?))
+}
+
+pub async fn upsert_role_two_factor(conn: &Object, role: i32, required: bool) -> Result<()> {
+    conn.execute(
+        "
+        insert into role_settings (role, require_two_factor) values ($1, $2)
+        on conflict (role) do update set require_two_factor = $2
+        ",
+        &[&role, &required],
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_challenge(conn: &Object, user_id: &Uuid) -> Result<Uuid> {
+    let id = Uuid::now_v7();
+    conn.execute(
+        "insert into two_factor_challenges (id, user_id) values ($1, $2)",
+        &[&id, user_id],
+    )
+    .await?;
+    Ok(id)
+}
+
+/**
+ * Count an attempt at the challenge, 5 attempts in 5 minutes are allowed.
+ * Returns the user of the challenge, or None if it expired or ran out of attempts.
+ */
+pub async fn attempt_challenge(conn: &Object, challenge_id: &str) -> Result<Option<Uuid>> {
+    let row = conn
+        .query_opt(
+            "
+            update two_factor_challenges set attempts = attempts + 1
+            where id = $1
+            and attempts < 5
+            and created > now() - interval '5 minutes'
+            returning user_id
+            ",
+            &[&Uuid::from_str(challenge_id)?],
+        )
+        .await?;
+    match row {
+        Some(row) => Ok(Some(row.try_get("user_id")?)),
+        None => Ok(None),
+    }
+}
+
+/**
+ * A challenge issues one session, so it's consumed in the same statement that checks it.
+ * Returns false if a concurrent request already consumed it.
+ */
+pub async fn delete_challenge(conn: &Object, challenge_id: &str) -> Result<bool> {
+    let row = conn
+        .query_opt(
+            "delete from two_factor_challenges where id = $1 returning user_id",
+            &[&Uuid::from_str(challenge_id)?],
+        )
+        .await?;
+    Ok(row.is_some())
+}
+
+// 5 minutes
+pub async fn delete_old_challenges(conn: &Object) -> Result<()> {
+    conn.execute(
+        "delete from two_factor_challenges where created < now() - interval '5 minutes'",
+        &[],
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,333 @@
+use tonic::{Request, Response, Status};
+
+use crate::proto::{
+    AuditEventType, Permission, RecoveryCodes, RoleTwoFactor, TwoFactorCode, TwoFactorSetup,
+    UserRole,
+};
+use crate::user_db::StringOrUuid;
+
+const ISSUER: &str = "Rusve";
+const RECOVERY_CODES: usize = 10;
+
+/**
+ * Generate a new secret, the enrolment is confirmed with `enable_two_factor`.
+ */
+pub async fn setup_two_factor(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<TwoFactorSetup>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let user = crate::user_db::select_user_by_id(&conn, StringOrUuid::String(user_id))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user: {:?}", e);
+            Status::internal("Failed to get user")
+        })?;
+
+    let secret = crate::totp::generate_secret();
+    let created = crate::two_factor_db::upsert_totp(&conn, &user.id, &secret)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save two-factor secret: {:?}", e);
+            Status::internal("Failed to save two-factor secret")
+        })?;
+    if !created {
+        return Err(Status::failed_precondition("Two-factor is already enabled"));
+    }
+
+    tracing::info!("setup_two_factor: {:?}", start.elapsed());
+    Ok(Response::new(TwoFactorSetup {
+        uri: crate::totp::otpauth_uri(ISSUER, &user.email, &secret),
+        secret,
+    }))
+}
+
+/**
+ * Confirm the enrolment with the first code, and return the recovery codes. They are only shown once.
+ */
+pub async fn enable_two_factor(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<TwoFactorCode>,
+) -> Result<Response<RecoveryCodes>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let totp = crate::two_factor_db::select_totp(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get two-factor secret: {:?}", e);
+            Status::internal("Failed to get two-factor secret")
+        })?
+        .ok_or_else(|| Status::failed_precondition("Two-factor setup is not started"))?;
+    if totp.confirmed {
+        return Err(Status::failed_precondition("Two-factor is already enabled"));
+    }
+
+    let code = request.into_inner().code;
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let step = crate::totp::verify(&totp.secret, &code, now, totp.last_step)
+        .ok_or_else(|| Status::invalid_argument("Invalid code"))?;
+
+    crate::two_factor_db::confirm_totp(&conn, &user_id, step)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to enable two-factor: {:?}", e);
+            Status::internal("Failed to enable two-factor")
+        })?;
+
+    let codes = crate::totp::generate_recovery_codes(RECOVERY_CODES);
+    let hashes: Vec<String> = codes
+        .iter()
+        .map(|code| crate::totp::hash_recovery_code(code))
+        .collect();
+    crate::two_factor_db::insert_recovery_codes(&conn, &user_id, &hashes)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save recovery codes: {:?}", e);
+            Status::internal("Failed to save recovery codes")
+        })?;
+
+    tracing::info!("enable_two_factor: {:?}", start.elapsed());
+    Ok(Response::new(RecoveryCodes { codes }))
+}
+
+pub async fn disable_two_factor(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<TwoFactorCode>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+    service_users::reject_impersonation(&claims)?;
+    let user_id = claims.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let user = crate::user_db::select_user_by_id(&conn, StringOrUuid::String(user_id))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user: {:?}", e);
+            Status::internal("Failed to get user")
+        })?;
+    let (_, required) = crate::two_factor_db::select_two_factor_status(&conn, &user.id, user.role)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get two-factor status: {:?}", e);
+            Status::internal("Failed to get two-factor status")
+        })?;
+    if required {
+        return Err(Status::failed_precondition(
+            "Two-factor is required for your role",
+        ));
+    }
+
+    let code = request.into_inner().code;
+    if !verify_code(&conn, &user.id, &code).await? {
+        return Err(Status::invalid_argument("Invalid code"));
+    }
+
+    crate::two_factor_db::delete_totp(&conn, &user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to disable two-factor: {:?}", e);
+            Status::internal("Failed to disable two-factor")
+        })?;
+
+    tracing::info!("disable_two_factor: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * Start a challenge if the user has two-factor enabled, the session is only issued
+ * once it's completed with `verify_two_factor`.
+ */
+pub async fn start_challenge(
+    conn: &deadpool_postgres::Object,
+    user_id: &uuid::Uuid,
+) -> Result<Option<uuid::Uuid>, Status> {
+    let totp = crate::two_factor_db::select_totp(conn, &user_id.to_string())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get two-factor secret: {:?}", e);
+            Status::internal("Failed to get two-factor secret")
+        })?;
+    if !totp.is_some_and(|totp| totp.confirmed) {
+        return Ok(None);
+    }
+
+    let challenge_id = crate::two_factor_db::insert_challenge(conn, user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create two-factor challenge: {:?}", e);
+            Status::internal("Failed to create two-factor challenge")
+        })?;
+    Ok(Some(challenge_id))
+}
+
+/**
+ * The client forwards the challenge id in its own signed token, the same as the refresh token.
+ */
+pub async fn verify_two_factor(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<TwoFactorCode>,
+) -> Result<Response<crate::proto::Id>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let challenge_id = service_users::decode_token(metadata, &env.jwt_keys)?.id;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    let user_id = crate::two_factor_db::attempt_challenge(&conn, &challenge_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get two-factor challenge: {:?}", e);
+            Status::unauthenticated("Invalid challenge")
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Two-factor challenge expired or out of attempts");
+            Status::unauthenticated("Invalid challenge")
+        })?
+        .to_string();
+
+    let (metadata, _, code) = request.into_parts();
+    let user_uuid = uuid::Uuid::parse_str(&user_id).ok();
+    if !verify_code(&conn, &user_id, &code.code).await? {
+        crate::audit_service::record(
+            &conn,
+            &metadata,
+            AuditEventType::TwoFactorFailure,
+            user_uuid,
+            "",
+            "",
+        )
+        .await;
+        return Err(Status::unauthenticated("Invalid code"));
+    }
+
+    let consumed = crate::two_factor_db::delete_challenge(&conn, &challenge_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete two-factor challenge: {:?}", e);
+            Status::internal("Failed to delete two-factor challenge")
+        })?;
+    if !consumed {
+        tracing::error!("Two-factor challenge already used");
+        return Err(Status::unauthenticated("Invalid challenge"));
+    }
+
+    let token = crate::token_db::insert_token(&conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to insert token: {:?}", e);
+            Status::internal("Failed to insert token")
+        })?;
+    crate::audit_service::record(
+        &conn,
+        &metadata,
+        AuditEventType::TokenIssued,
+        user_uuid,
+        "",
+        "after two-factor",
+    )
+    .await;
+
+    // Delete old challenges. If this fails, it's not a big deal.
+    tokio::spawn(async move {
+        if let Err(err) = crate::two_factor_db::delete_old_challenges(&conn).await {
+            tracing::error!("Failed to delete old challenges: {:?}", err);
+        }
+    });
+
+    tracing::info!("verify_two_factor: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Id {
+        id: token.to_string(),
+    }))
+}
+
+pub async fn set_role_two_factor(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<RoleTwoFactor>,
+) -> Result<Response<crate::proto::Empty>, Status> {
+    let start = std::time::Instant::now();
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+    crate::authz::authorize(env, &conn, request.metadata(), Permission::ManageRoles).await?;
+
+    let setting = request.into_inner();
+    if setting.role == UserRole::Unset as i32 {
+        return Err(Status::invalid_argument("Invalid role"));
+    }
+    crate::two_factor_db::upsert_role_two_factor(&conn, setting.role, setting.required)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save role setting: {:?}", e);
+            Status::internal("Failed to save role setting")
+        })?;
+
+    tracing::info!("set_role_two_factor: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::Empty {}))
+}
+
+/**
+ * Accept a code from the authenticator app, or an unused recovery code.
+ */
+async fn verify_code(
+    conn: &deadpool_postgres::Object,
+    user_id: &str,
+    code: &str,
+) -> Result<bool, Status> {
+    let totp = crate::two_factor_db::select_totp(conn, user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get two-factor secret: {:?}", e);
+            Status::internal("Failed to get two-factor secret")
+        })?;
+    let Some(totp) = totp.filter(|totp| totp.confirmed) else {
+        return Ok(false);
+    };
+
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let valid = match crate::totp::verify(&totp.secret, code, now, totp.last_step) {
+        Some(step) => crate::two_factor_db::use_totp_step(conn, user_id, step).await,
+        None => {
+            let hash = crate::totp::hash_recovery_code(code);
+            crate::two_factor_db::use_recovery_code(conn, user_id, &hash).await
+        }
+    }
+    .map_err(|e| {
+        tracing::error!("Failed to verify two-factor code: {:?}", e);
+        Status::internal("Failed to verify two-factor code")
+    })?;
+    Ok(valid)
+}
@@ -6,7 +6,7 @@ use time::format_description::well_known::Iso8601;
 use tokio_postgres::types::Timestamp;
 use uuid::Uuid;
 
-use crate::proto::User;
+use crate::proto::{User, UserRole};
 
 pub enum StringOrUuid {
     String(String),
@@ -68,6 +68,9 @@ impl TryFrom<tokio_postgres::Row> for User {
             subscription_end,
             subscription_check,
             subscription_active,
+            // Filled in by the service, they live in the two-factor tables
+            two_factor_enabled: false,
+            two_factor_required: false,
         })
     }
 }
@@ -87,31 +90,124 @@ pub async fn select_user_by_id(conn: &Object, user_id: StringOrUuid) -> Result<U
     Ok(user)
 }
 
-pub async fn create_user(conn: &Object, email: &str, sub: &str, avatar: &str) -> Result<User> {
+pub async fn select_user_by_email(conn: &Object, email: &str) -> Result<Option<User>> {
     let row = conn
-        .query_opt(
-            "select * from users where email = $1 and sub = $2 and deleted = 'infinity'",
-            &[&email, &sub],
+        .query_opt("select * from users where email = $1", &[&email])
+        .await?;
+    match row {
+        Some(row) => Ok(Some(User::try_from(row)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn insert_user(conn: &Object, email: &str, sub: &str, avatar: &str) -> Result<User> {
+    let id = Uuid::now_v7();
+    // Admins are promoted in the database, see `authz::role_permissions`
+    let role = UserRole::User as i32;
+    let user = conn
+        .query_one(
+            "insert into users (id, email, sub, role, avatar) values ($1, $2, $3, $4, $5) returning *",
+            &[&id, &email, &sub, &role, &avatar],
         )
         .await?;
-    let user = match row {
-        Some(_) => {
-            conn.query_one(
-                "update users set updated = now() where email = $1 and sub = $2 returning *",
-                &[&email, &sub],
-            )
-            .await
-        }
-        None => {
-            let id = Uuid::now_v7();
-            let role: i32 = 1;
-            conn.query_one(
-                "insert into users (id, email, sub, role, avatar) values ($1, $2, $3, $4, $5) returning *",
-                &[&id, &email, &sub, &role, &avatar],
-            )
-            .await
-        }
-    }?;
     let user = User::try_from(user)?;
     Ok(user)
 }
+
+/**
+ * Including the soft-deleted users, for the admins.
+ */
+pub async fn select_any_user_by_id(conn: &Object, user_id: &Uuid) -> Result<Option<User>> {
+    let row = conn
+        .query_opt("select * from users where id = $1", &[user_id])
+        .await?;
+    row.map(User::try_from).transpose()
+}
+
+/**
+ * `%` and `_` in the search are matched literally.
+ */
+fn like_pattern(search: &str) -> String {
+    let escaped = search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+pub async fn count_users(conn: &Object, search: &str, deleted: bool) -> Result<i64> {
+    let count = conn
+        .query_one(
+            "
+            select count(*) from users
+            where ($1 = '' or email ilike $2 or id::text = $1)
+            and (deleted = 'infinity') != $3
+            ",
+            &[&search, &like_pattern(search), &deleted],
+        )
+        .await?;
+    Ok(count.try_get(0)?)
+}
+
+/**
+ * Newest first, see `UserFilter`.
+ */
+pub async fn select_users(
+    conn: &Object,
+    search: &str,
+    deleted: bool,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<User>> {
+    let rows = conn
+        .query(
+            "
+            select * from users
+            where ($1 = '' or email ilike $2 or id::text = $1)
+            and (deleted = 'infinity') != $3
+            order by created desc offset $4 limit $5
+            ",
+            &[&search, &like_pattern(search), &deleted, &offset, &limit],
+        )
+        .await?;
+    rows.into_iter().map(User::try_from).collect()
+}
+
+/**
+ * Returns None if the user doesn't exist or is deleted.
+ */
+pub async fn update_user_role(conn: &Object, user_id: &Uuid, role: i32) -> Result<Option<User>> {
+    let row = conn
+        .query_opt(
+            "update users set role = $2 where id = $1 and deleted = 'infinity' returning *",
+            &[user_id, &role],
+        )
+        .await?;
+    row.map(User::try_from).transpose()
+}
+
+/**
+ * Returns false if the user doesn't exist or is already deleted.
+ */
+pub async fn soft_delete_user(conn: &Object, user_id: &Uuid) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "update users set deleted = now() where id = $1 and deleted = 'infinity'",
+            &[user_id],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+/**
+ * Returns false if the user doesn't exist or isn't deleted.
+ */
+pub async fn restore_user(conn: &Object, user_id: &Uuid) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "update users set deleted = 'infinity' where id = $1 and deleted != 'infinity'",
+            &[user_id],
+        )
+        .await?;
+    Ok(updated > 0)
+}
@@ -1,36 +1,86 @@
+use prost::Message;
 use tonic::{Request, Response, Status};
 
+use crate::proto::{AuditEventType, LoginError, LoginFailure};
 use crate::user_db::StringOrUuid;
 
 pub async fn create_user(
     env: &service_users::Env,
     pool: &deadpool_postgres::Pool,
     request: Request<crate::proto::Empty>,
-) -> Result<Response<crate::proto::Id>, tonic::Status> {
+) -> Result<Response<crate::proto::LoginResponse>, tonic::Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let user = service_users::decode_oauth_token(metadata, &env.jwt_secret)?;
+    let claims = service_users::decode_oauth_token(metadata, &env.jwt_keys)?;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
         Status::internal("Failed to get connection")
     })?;
 
-    // Create a new user if one doesn't exist, otherwise update the existing user.
-    let user = crate::user_db::create_user(&conn, &user.email, &user.sub, &user.avatar)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to create user: {:?}", e);
-            Status::internal("Failed to create user")
-        })?;
+    // Linking needs the session of the user, see `LinkIdentity`.
+    if !claims.link_code.is_empty() {
+        return Err(Status::invalid_argument(
+            "Identities are linked with LinkIdentity",
+        ));
+    }
+
+    // Find the user of the identity, or create a new user.
+    let user_id = match sign_in_identity(&conn, &claims).await {
+        Ok(user_id) => reject_deleted(&conn, user_id).await,
+        status => status,
+    };
+    let user_id = match user_id {
+        Ok(user_id) => user_id,
+        Err(status) => {
+            let detail = format!("{:?}: {}", status.code(), status.message());
+            crate::audit_service::record(
+                &conn,
+                metadata,
+                AuditEventType::LoginFailure,
+                None,
+                &claims.provider,
+                &detail,
+            )
+            .await;
+            return Err(status);
+        }
+    };
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::LoginSuccess,
+        Some(user_id),
+        &claims.provider,
+        "",
+    )
+    .await;
+
+    // With two-factor enabled, the token is only issued after the challenge.
+    if let Some(challenge_id) = crate::two_factor_service::start_challenge(&conn, &user_id).await? {
+        tracing::info!("create_user: {:?}", start.elapsed());
+        return Ok(Response::new(crate::proto::LoginResponse {
+            token: String::new(),
+            challenge_id: challenge_id.to_string(),
+        }));
+    }
 
     // Create a new token.
-    let token = crate::token_db::insert_token(&conn, &user.id)
+    let token = crate::token_db::insert_token(&conn, &user_id.to_string())
         .await
         .map_err(|e| {
             tracing::error!("Failed to insert token: {:?}", e);
             Status::internal("Failed to insert token")
         })?;
+    crate::audit_service::record(
+        &conn,
+        metadata,
+        AuditEventType::TokenIssued,
+        Some(user_id),
+        &claims.provider,
+        "",
+    )
+    .await;
 
     // Delete old tokens. If this fails, it's not a big deal.
     tokio::spawn(async move {
@@ -40,11 +90,123 @@ pub async fn create_user(
     });
 
     tracing::info!("create_user: {:?}", start.elapsed());
-    Ok(Response::new(crate::proto::Id {
-        id: token.to_string(),
+    Ok(Response::new(crate::proto::LoginResponse {
+        token: token.to_string(),
+        challenge_id: String::new(),
     }))
 }
 
+/**
+ * An error of a login the user can act on, service-auth reads the reason from the details.
+ */
+pub fn login_error(code: tonic::Code, message: &str, reason: LoginError) -> Status {
+    let failure = LoginFailure {
+        reason: reason as i32,
+    };
+    Status::with_details(code, message, failure.encode_to_vec().into())
+}
+
+/**
+ * The link code of `LinkIdentity` or `CreatePasskey` is unknown, expired or of another user.
+ */
+pub fn invalid_link_code() -> Status {
+    login_error(
+        tonic::Code::Unauthenticated,
+        "Invalid link code",
+        LoginError::InvalidLinkCode,
+    )
+}
+
+/**
+ * A user deleted by an admin can't sign in until restored.
+ */
+pub async fn reject_deleted(
+    conn: &deadpool_postgres::Object,
+    user_id: uuid::Uuid,
+) -> Result<uuid::Uuid, tonic::Status> {
+    let user = crate::user_db::select_any_user_by_id(conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user: {:?}", e);
+            Status::internal("Failed to get user")
+        })?
+        .ok_or_else(|| Status::not_found("User not found"))?;
+    if user.deleted != "infinity" {
+        tracing::error!("User is deleted: {:?}", user_id);
+        return Err(login_error(
+            tonic::Code::PermissionDenied,
+            "Account is disabled",
+            LoginError::AccountDisabled,
+        ));
+    }
+    Ok(user_id)
+}
+
+/**
+ * Sign in with an identity. An unknown identity is never linked to the account with the same
+ * email, the user has to sign in and link it from the settings, otherwise a new user is created.
+ */
+async fn sign_in_identity(
+    conn: &deadpool_postgres::Object,
+    claims: &service_users::OAuthClaims,
+) -> Result<uuid::Uuid, tonic::Status> {
+    let user_id =
+        crate::identity_db::select_user_id_by_identity(conn, &claims.provider, &claims.sub)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get identity: {:?}", e);
+                Status::internal("Failed to get identity")
+            })?;
+    if let Some(user_id) = user_id {
+        return Ok(user_id);
+    }
+
+    let user = crate::user_db::select_user_by_email(conn, &claims.email)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user: {:?}", e);
+            Status::internal("Failed to get user")
+        })?;
+    if let Some(user) = user {
+        tracing::error!(
+            "Email of a new identity matches an existing user: {:?}",
+            user.id
+        );
+        return Err(login_error(
+            tonic::Code::AlreadyExists,
+            "Account already exists, sign in and link this provider",
+            LoginError::AccountExists,
+        ));
+    }
+    let user = crate::user_db::insert_user(conn, &claims.email, &claims.sub, &claims.avatar)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create user: {:?}", e);
+            Status::internal("Failed to create user")
+        })?;
+    let user_id = uuid::Uuid::parse_str(&user.id).map_err(|e| {
+        tracing::error!("Failed to parse user id: {:?}", e);
+        Status::internal("Failed to parse user id")
+    })?;
+
+    crate::identity_db::insert_identity(
+        conn,
+        &user_id,
+        &claims.provider,
+        &claims.sub,
+        &claims.email,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to insert identity: {:?}", e);
+        Status::internal("Failed to insert identity")
+    })?;
+    Ok(user_id)
+}
+
+/**
+ * Validate the short-lived access token, without touching the refresh tokens.
+ */
 pub async fn auth(
     env: &service_users::Env,
     pool: &deadpool_postgres::Pool,
@@ -52,28 +214,222 @@ pub async fn auth(
 ) -> Result<Response<crate::proto::AuthResponse>, tonic::Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let token = service_users::decode_token(metadata, &env.jwt_secret)?.id;
+    let claims = service_users::decode_access_token(metadata, &env.jwt_keys)?;
+
+    let conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection: {:?}", e);
+        Status::internal("Failed to get connection")
+    })?;
+
+    // The access token is still valid, but the session might have been revoked since
+    let active = crate::token_db::touch_token_family(&conn, &claims.sid)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to auth session: {:?}", e);
+            Status::unauthenticated("Failed to auth session")
+        })?;
+    if !active {
+        tracing::error!("Session has been revoked");
+        crate::audit_service::record(
+            &conn,
+            metadata,
+            AuditEventType::SessionRejected,
+            uuid::Uuid::parse_str(&claims.id).ok(),
+            "",
+            &format!("session {}", claims.sid),
+        )
+        .await;
+        return Err(Status::unauthenticated("Unauthenticated"));
+    }
+
+    let user = select_active_user(env, &conn, StringOrUuid::String(claims.id)).await?;
+
+    tracing::info!("auth: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::AuthResponse {
+        // The role may have changed since the token was issued, the client forwards the current one
+        permissions: crate::authz::user_permissions(&user),
+        user: user.into(),
+        token: String::new(),
+        access_token: String::new(),
+        impersonator_id: claims.act.map(|act| act.sub).unwrap_or_default(),
+    }))
+}
+
+/**
+ * Exchange a refresh token for a new one from the same family, and a new access token.
+ */
+pub async fn refresh_token(
+    env: &service_users::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<crate::proto::Empty>,
+) -> Result<Response<crate::proto::AuthResponse>, tonic::Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    let token_id = service_users::decode_token(metadata, &env.jwt_keys)?.id;
+    let user_agent = metadata_value(metadata, "x-user-agent", 512);
+    let ip = metadata_value(metadata, "x-client-ip", 64);
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
         Status::internal("Failed to get connection")
     })?;
 
-    let token = crate::token_db::select_token_by_id(&conn, &token)
+    let rotated = crate::token_db::rotate_token(&conn, &token_id, &user_agent, &ip)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to auth token: {:?}", e);
+            tracing::error!("Failed to rotate token: {:?}", e);
             Status::unauthenticated("Failed to auth token")
         })?;
+    let token = match rotated {
+        Some(token) => token,
+        None => {
+            let token = crate::token_db::select_token_by_id(&conn, &token_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to auth token: {:?}", e);
+                    Status::unauthenticated("Failed to auth token")
+                })?;
+            handle_invalid_token(&conn, metadata, token, &user_agent, &ip).await?
+        }
+    };
+
+    let user = select_active_user(env, &conn, StringOrUuid::Uuid(token.user_id)).await?;
+
+    let permissions = crate::authz::user_permissions(&user);
+    let access_token = service_users::generate_access_token(
+        &env.jwt_keys,
+        &user.id,
+        &token.family_id.to_string(),
+        user.role,
+        permissions.clone(),
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to generate access token: {:?}", e);
+        Status::internal("Failed to generate access token")
+    })?;
+
+    // Delete old tokens. If this fails, it's not a big deal.
+    tokio::spawn(async move {
+        if let Err(err) = crate::token_db::delete_old_tokens(&conn).await {
+            tracing::error!("Failed to delete old tokens: {:?}", err);
+        }
+    });
+
+    tracing::info!("refresh_token: {:?}", start.elapsed());
+    Ok(Response::new(crate::proto::AuthResponse {
+        user: user.into(),
+        token: token.id.to_string(),
+        access_token,
+        impersonator_id: String::new(),
+        permissions,
+    }))
+}
+
+/**
+ * Session details forwarded by the client, missing or invalid values are empty.
+ */
+pub fn metadata_value(
+    metadata: &tonic::metadata::MetadataMap,
+    key: &str,
+    max_len: usize,
+) -> String {
+    metadata
+        .get(key)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(max_len).collect())
+        .unwrap_or_default()
+}
 
-    // check if token has expired, 7 days
-    if token.created + time::Duration::days(7) < time::OffsetDateTime::now_utc() {
+/**
+ * The token could not be rotated: it expired, was revoked, or was already rotated.
+ * Concurrent requests of the same browser can race on the same token, so for a few seconds
+ * after the rotation its replacement is returned again, to the client that rotated it only.
+ * Otherwise the token is treated as stolen and the whole family is revoked.
+ */
+async fn handle_invalid_token(
+    conn: &deadpool_postgres::Object,
+    metadata: &tonic::metadata::MetadataMap,
+    token: crate::token_db::Token,
+    user_agent: &str,
+    ip: &str,
+) -> Result<crate::token_db::Token, tonic::Status> {
+    if token.revoked.is_some() {
+        tracing::error!("Token has been revoked");
+        return Err(Status::unauthenticated("Unauthenticated"));
+    }
+    if token.rotated.is_none() {
         tracing::error!("Token has expired");
         return Err(Status::unauthenticated("Unauthenticated"));
     }
 
-    // get user
-    let mut user = crate::user_db::select_user_by_id(&conn, StringOrUuid::Uuid(token.user_id))
+    if let Some(replaced_by) = token.replaced_by {
+        let replacement = crate::token_db::select_token_by_id(conn, &replaced_by.to_string())
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to auth token: {:?}", e);
+                Status::unauthenticated("Failed to auth token")
+            })?;
+        if may_return_replacement(
+            &token,
+            &replacement,
+            user_agent,
+            ip,
+            time::OffsetDateTime::now_utc(),
+        ) {
+            return Ok(replacement);
+        }
+    }
+
+    tracing::warn!(
+        "Refresh token reuse detected, revoking family: {:?}",
+        token.family_id
+    );
+    crate::token_db::revoke_token_family(conn, &token.family_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke token family: {:?}", e);
+            Status::internal("Failed to revoke token family")
+        })?;
+    crate::audit_service::record(
+        conn,
+        metadata,
+        AuditEventType::TokenReused,
+        Some(token.user_id),
+        "",
+        &format!("session {}", token.family_id),
+    )
+    .await;
+    Err(Status::unauthenticated("Unauthenticated"))
+}
+
+/**
+ * The replacement of a rotated token can be returned again within 10 seconds of the rotation,
+ * while it is still valid, and only to the same user agent and ip that rotated it.
+ */
+fn may_return_replacement(
+    token: &crate::token_db::Token,
+    replacement: &crate::token_db::Token,
+    user_agent: &str,
+    ip: &str,
+    now: time::OffsetDateTime,
+) -> bool {
+    let Some(rotated) = token.rotated else {
+        return false;
+    };
+    rotated + time::Duration::seconds(10) > now
+        && replacement.rotated.is_none()
+        && replacement.revoked.is_none()
+        && !ip.is_empty()
+        && replacement.ip == ip
+        && replacement.user_agent == user_agent
+}
+
+pub async fn select_active_user(
+    env: &service_users::Env,
+    conn: &deadpool_postgres::Object,
+    user_id: StringOrUuid,
+) -> Result<crate::proto::User, tonic::Status> {
+    let mut user = crate::user_db::select_user_by_id(conn, user_id)
         .await
         .map_err(|e| {
             tracing::error!("Failed to auth user: {:?}", e);
@@ -84,16 +440,8 @@ pub async fn auth(
         return Err(Status::unauthenticated("Unauthenticated"));
     }
 
-    // create new token
-    let token_id = crate::token_db::insert_token(&conn, &user.id)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to insert token: {:?}", e);
-            Status::internal("Failed to insert token")
-        })?;
-
     // check if user is subscribed
-    let subscribed = crate::stripe_service::check_subscription(env, &conn, &user)
+    let subscribed = crate::stripe_service::check_subscription(env, conn, &user)
         .await
         .map_err(|e| {
             tracing::error!("Failed to update subscription: {:?}", e);
@@ -101,16 +449,124 @@ pub async fn auth(
         })?;
     user.subscription_active = subscribed;
 
-    // Delete old tokens. If this fails, it's not a big deal.
-    tokio::spawn(async move {
-        if let Err(err) = crate::token_db::delete_old_tokens(&conn).await {
-            tracing::error!("Failed to delete old tokens: {:?}", err);
-        }
-    });
+    let (enabled, required) =
+        crate::two_factor_db::select_two_factor_status(conn, &user.id, user.role)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get two-factor status: {:?}", e);
+                Status::internal("Failed to get two-factor status")
+            })?;
+    user.two_factor_enabled = enabled;
+    user.two_factor_required = required;
+    Ok(user)
+}
 
-    tracing::info!("auth: {:?}", start.elapsed());
-    Ok(Response::new(crate::proto::AuthResponse {
-        user: user.into(),
-        token: token_id.to_string(),
-    }))
+#[cfg(test)]
+mod tests {
+    use super::may_return_replacement;
+    use crate::token_db::Token;
+    use time::{Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    const USER_AGENT: &str = "Mozilla/5.0";
+    const IP: &str = "203.0.113.7";
+
+    fn rotation(now: OffsetDateTime) -> (Token, Token) {
+        let family_id = Uuid::now_v7();
+        let replacement = Token {
+            id: Uuid::now_v7(),
+            created: now,
+            user_id: Uuid::now_v7(),
+            family_id,
+            rotated: None,
+            replaced_by: None,
+            revoked: None,
+            user_agent: USER_AGENT.to_string(),
+            ip: IP.to_string(),
+            last_used: now,
+            impersonator_id: None,
+        };
+        let token = Token {
+            id: family_id,
+            rotated: Some(now),
+            replaced_by: Some(replacement.id),
+            ..replacement.clone()
+        };
+        (token, replacement)
+    }
+
+    #[test]
+    fn replacement_is_returned_to_the_same_client_during_the_grace_period() {
+        let now = OffsetDateTime::now_utc();
+        let (token, replacement) = rotation(now);
+        let later = now + Duration::seconds(9);
+        assert!(may_return_replacement(
+            &token,
+            &replacement,
+            USER_AGENT,
+            IP,
+            later
+        ));
+    }
+
+    #[test]
+    fn replacement_is_not_returned_to_another_client() {
+        let now = OffsetDateTime::now_utc();
+        let (token, replacement) = rotation(now);
+        assert!(!may_return_replacement(
+            &token,
+            &replacement,
+            USER_AGENT,
+            "198.51.100.1",
+            now
+        ));
+        assert!(!may_return_replacement(
+            &token,
+            &replacement,
+            "curl/8.0",
+            IP,
+            now
+        ));
+        assert!(!may_return_replacement(
+            &token,
+            &replacement,
+            USER_AGENT,
+            "",
+            now
+        ));
+    }
+
+    #[test]
+    fn replacement_is_not_returned_after_the_grace_period() {
+        let now = OffsetDateTime::now_utc();
+        let (token, replacement) = rotation(now);
+        let later = now + Duration::seconds(10);
+        assert!(!may_return_replacement(
+            &token,
+            &replacement,
+            USER_AGENT,
+            IP,
+            later
+        ));
+    }
+
+    #[test]
+    fn replacement_is_not_returned_once_rotated_or_revoked() {
+        let now = OffsetDateTime::now_utc();
+        let (token, replacement) = rotation(now);
+        let rotated = Token {
+            rotated: Some(now),
+            ..replacement.clone()
+        };
+        assert!(!may_return_replacement(
+            &token, &rotated, USER_AGENT, IP, now
+        ));
+        let revoked = Token {
+            revoked: Some(now),
+            ..replacement
+        };
+        assert!(!may_return_replacement(
+            &token, &revoked, USER_AGENT, IP, now
+        ));
+    }
 }
@@ -0,0 +1,71 @@
+use crate::proto::{Empty, UserDataDeletion};
+use tonic::{Request, Response, Status};
+
+/**
+ * The files and emails of a deleted account, see `DeleteAccount` in service-users.
+ * Called again until it succeeds, so every step has to be idempotent.
+ */
+pub async fn delete_user_data(
+    env: &service_utils::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<UserDataDeletion>,
+) -> Result<Response<Empty>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    service_utils::decode_service_token(metadata, &env.jwt_keys, &["users"])?;
+
+    let mut conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection from pool: {:?}", e);
+        Status::internal("Failed to get connection from pool")
+    })?;
+
+    let deletion = request.into_inner();
+    if deletion.purge {
+        // Objects first, a row is only deleted once its object is gone
+        let files = crate::file_db::select_all_files_by_target_id(&conn, &deletion.user_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get files: {:?}", e);
+                Status::internal("Failed to get files")
+            })?;
+        for file in files {
+            crate::file_utils::delete_file(env, &file.id, &file.file_name)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to delete file: {:?}", e);
+                    Status::internal("Failed to delete file")
+                })?;
+        }
+    }
+
+    let tr = conn.transaction().await.map_err(|e| {
+        tracing::error!("Failed to start transaction: {:?}", e);
+        Status::internal("Failed to start transaction")
+    })?;
+    let files = crate::file_db::delete_files_by_target_id(&tr, &deletion.user_id, deletion.purge)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete files: {:?}", e);
+            Status::internal("Failed to delete files")
+        })?;
+    let emails =
+        crate::email_db::delete_emails_by_target_id(&tr, &deletion.user_id, deletion.purge)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to delete emails: {:?}", e);
+                Status::internal("Failed to delete emails")
+            })?;
+    tr.commit().await.map_err(|e| {
+        tracing::error!("Failed to commit transaction: {:?}", e);
+        Status::internal("Failed to commit transaction")
+    })?;
+
+    tracing::info!(
+        "delete_user_data: {} files, {} emails, purge {}, {:?}",
+        files,
+        emails,
+        deletion.purge,
+        start.elapsed()
+    );
+    Ok(Response::new(Empty {}))
+}
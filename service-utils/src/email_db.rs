@@ -80,6 +80,20 @@ pub async fn get_emails_by_target_id(
     Ok(rows)
 }
 
+/**
+ * Every email sent to the target, oldest first, for the data export.
+ */
+pub async fn select_all_emails_by_target_id(conn: &Object, target_id: &str) -> Result<Vec<Email>> {
+    let target_id = Uuid::parse_str(target_id)?;
+    let rows = conn
+        .query(
+            "select * from emails where target_id = $1 and deleted = 'infinity' order by created",
+            &[&target_id],
+        )
+        .await?;
+    rows.into_iter().map(Email::try_from).collect()
+}
+
 pub async fn insert_email(
     conn: &deadpool_postgres::Transaction<'_>,
     target_id: &str,
@@ -96,3 +110,21 @@ pub async fn insert_email(
 
     email.try_into()
 }
+
+/**
+ * Every email of a deleted account, marked deleted first and deleted for good after the grace period.
+ */
+pub async fn delete_emails_by_target_id(
+    conn: &deadpool_postgres::Transaction<'_>,
+    target_id: &str,
+    purge: bool,
+) -> Result<u64> {
+    let target_id = Uuid::parse_str(target_id)?;
+    let query = if purge {
+        "delete from emails where target_id = $1"
+    } else {
+        "update emails set deleted = now() where target_id = $1 and deleted = 'infinity'"
+    };
+    let count = conn.execute(query, &[&target_id]).await?;
+    Ok(count)
+}
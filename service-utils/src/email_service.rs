@@ -12,7 +12,7 @@ pub async fn count_emails_by_target_id(
 ) -> Result<Response<Count>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let target_id = service_utils::auth(metadata, &env.jwt_secret)?.id;
+    let target_id = service_utils::auth(metadata, &env.jwt_keys)?.id;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
@@ -37,7 +37,7 @@ pub async fn get_emails_by_target_id(
 ) -> Result<Response<ReceiverStream<Result<crate::proto::Email, Status>>>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let target_id = service_utils::auth(metadata, &env.jwt_secret)?.id;
+    let target_id = service_utils::auth(metadata, &env.jwt_keys)?.id;
 
     let conn = pool.get().await.map_err(|e| {
         tracing::error!("Failed to get connection: {:?}", e);
@@ -85,7 +85,16 @@ pub async fn send_email(
 ) -> Result<Response<Email>, Status> {
     let start = std::time::Instant::now();
     let metadata = request.metadata();
-    let target_id = service_utils::auth(metadata, &env.jwt_secret)?.id;
+    // Sent by service-auth for a magic link, or by service-users for a data export, to the
+    // link or the user in `target_id`
+    let target_id = if service_utils::is_service_token(metadata) {
+        service_utils::decode_service_token(metadata, &env.jwt_keys, &["auth", "users"])?;
+        uuid::Uuid::parse_str(&request.get_ref().target_id)
+            .map_err(|_| Status::invalid_argument("Invalid target id"))?
+            .to_string()
+    } else {
+        service_utils::auth(metadata, &env.jwt_keys)?.id
+    };
 
     let email = request.into_inner();
     crate::email_validation::Validation::validate(&email)?;
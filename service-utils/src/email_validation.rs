@@ -5,11 +5,11 @@ pub struct Validator<'a> {
 }
 
 pub trait Validation {
-    fn validate(&self) -> Result<(), tonic::Status>;
+    fn validate(&self) -> Result<(), service_utils::RequestError>;
 }
 
 impl Validation for crate::proto::Email {
-    fn validate(&self) -> Result<(), tonic::Status> {
+    fn validate(&self) -> Result<(), service_utils::RequestError> {
         let mut validators = Vec::new();
         if self.email_to.is_empty() || self.email_to.len() < 3 || self.email_to.len() > 1000 {
             validators.push(Validator {
@@ -62,10 +62,12 @@ impl Validation for crate::proto::Email {
         } else {
             let json = serde_json::to_string(&validators);
             match json {
-                Ok(json) => Err(tonic::Status::invalid_argument(json)),
+                Ok(json) => Err(service_utils::RequestError::InvalidArgument(json)),
                 Err(e) => {
                     tracing::error!("Failed to serialize validators: {:?}", e);
-                    Err(tonic::Status::internal("Failed to serialize validators"))
+                    Err(service_utils::RequestError::Internal(
+                        "Failed to serialize validators",
+                    ))
                 }
             }
         }
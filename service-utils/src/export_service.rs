@@ -0,0 +1,173 @@
+use anyhow::Result;
+use std::io::Write;
+use tonic::{Request, Response, Status};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::proto::{DataExportPart, DataExportRequest, Email, File, FileTarget};
+
+/**
+ * Package the parts collected by service-users with the emails and files stored here, and upload
+ * the archive as a file of the user. It replaces the previous export. The email announcing it is
+ * sent by service-users, to the address it has for the user.
+ */
+pub async fn create_data_export(
+    env: &service_utils::Env,
+    pool: &deadpool_postgres::Pool,
+    request: Request<DataExportRequest>,
+) -> Result<Response<File>, Status> {
+    let start = std::time::Instant::now();
+    let metadata = request.metadata();
+    service_utils::decode_service_token(metadata, &env.jwt_keys, &["users"])?;
+
+    let export = request.into_inner();
+    let mut conn = pool.get().await.map_err(|e| {
+        tracing::error!("Failed to get connection from pool: {:?}", e);
+        Status::internal("Failed to get connection from pool")
+    })?;
+
+    let emails = crate::email_db::select_all_emails_by_target_id(&conn, &export.user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get emails: {:?}", e);
+            Status::internal("Failed to get emails")
+        })?;
+    let files = crate::file_db::select_all_files_by_target_id(&conn, &export.user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get files: {:?}", e);
+            Status::internal("Failed to get files")
+        })?
+        .into_iter()
+        // The previous exports are not part of the data
+        .filter(|file| {
+            file.deleted == "infinity" && file.file_target != FileTarget::DataExport as i32
+        })
+        .collect::<Vec<_>>();
+
+    let archive = build_archive(env, &export.parts, &emails, &files)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to build archive: {:?}", e);
+            Status::internal("Failed to build archive")
+        })?;
+
+    let tr = conn.transaction().await.map_err(|e| {
+        tracing::error!("Failed to start transaction: {:?}", e);
+        Status::internal("Failed to start transaction")
+    })?;
+    let previous = crate::file_db::delete_files_by_file_target(
+        &tr,
+        &export.user_id,
+        FileTarget::DataExport as i32,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to delete previous export: {:?}", e);
+        Status::internal("Failed to delete previous export")
+    })?;
+    let file = File {
+        file_name: "data-export.zip".to_string(),
+        file_size: archive.len().to_string(),
+        file_type: "application/zip".to_string(),
+        file_target: FileTarget::DataExport as i32,
+        ..Default::default()
+    };
+    let file = crate::file_db::insert_file(&tr, &file, &export.user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create file: {:?}", e);
+            Status::internal("Failed to create file")
+        })?;
+    crate::file_utils::upload_file(env, &file.id, &file.file_name, archive)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to upload file: {:?}", e);
+            Status::internal("Failed to upload file")
+        })?;
+    tr.commit().await.map_err(|e| {
+        tracing::error!("Failed to commit transaction: {:?}", e);
+        Status::internal("Failed to commit transaction")
+    })?;
+
+    // The new export is already available, a leftover object is not worth failing for
+    for previous in previous {
+        if let Err(e) = crate::file_utils::delete_file(env, &previous.id, &previous.file_name).await
+        {
+            tracing::error!("Failed to delete previous export: {:?}", e);
+        }
+    }
+
+    tracing::info!("create_data_export: {:?}", start.elapsed());
+    Ok(Response::new(file))
+}
+
+/**
+ * The parts, `emails.json` and `files.json` at the root, and the files as uploaded
+ * in `files/{id}/{name}`. Built in memory, like the uploads.
+ */
+async fn build_archive(
+    env: &service_utils::Env,
+    parts: &[DataExportPart],
+    emails: &[Email],
+    files: &[File],
+) -> Result<Vec<u8>> {
+    let mut archive = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for part in parts {
+        archive.start_file(part.name.as_str(), options)?;
+        archive.write_all(&part.content)?;
+    }
+
+    let emails: Vec<serde_json::Value> = emails
+        .iter()
+        .map(|email| {
+            serde_json::json!({
+                "id": email.id,
+                "created": email.created,
+                "email_to": email.email_to,
+                "email_from": email.email_from,
+                "email_from_name": email.email_from_name,
+                "email_subject": email.email_subject,
+                "email_body": email.email_body,
+            })
+        })
+        .collect();
+    archive.start_file("emails.json", options)?;
+    archive.write_all(&serde_json::to_vec_pretty(&emails)?)?;
+
+    let metadata: Vec<serde_json::Value> = files
+        .iter()
+        .map(|file| {
+            serde_json::json!({
+                "id": file.id,
+                "created": file.created,
+                "file_name": file.file_name,
+                "file_size": file.file_size,
+                "file_type": file.file_type,
+                "path": archive_path(file),
+            })
+        })
+        .collect();
+    archive.start_file("files.json", options)?;
+    archive.write_all(&serde_json::to_vec_pretty(&metadata)?)?;
+
+    for file in files {
+        let buffer = crate::file_utils::get_file_buffer(env, &file.id, &file.file_name).await?;
+        archive.start_file(archive_path(file), options)?;
+        archive.write_all(&buffer)?;
+    }
+
+    Ok(archive.finish()?.into_inner())
+}
+
+/**
+ * The name is chosen by the user, keep only its last component so it can't escape `files/`.
+ */
+fn archive_path(file: &File) -> String {
+    let name = std::path::Path::new(&file.file_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("file");
+    format!("files/{}/{}", file.id, name)
+}
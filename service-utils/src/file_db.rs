@@ -121,3 +121,50 @@ pub async fn delete_file_by_id(conn: &Transaction<'_>, id: &str, target_id: &str
 
     file.try_into()
 }
+
+/**
+ * Mark the files of a target deleted, returns them so their objects can be removed.
+ */
+pub async fn delete_files_by_file_target(
+    conn: &Transaction<'_>,
+    target_id: &str,
+    file_target: i32,
+) -> Result<Vec<File>> {
+    let target_id = Uuid::parse_str(target_id)?;
+    let rows = conn
+        .query(
+            "update files set deleted = now() where target_id = $1 and file_target = $2 and deleted = 'infinity' returning *",
+            &[&target_id, &file_target],
+        )
+        .await?;
+    rows.into_iter().map(File::try_from).collect()
+}
+
+/**
+ * Including the deleted files, their objects are only removed from the bucket on purge.
+ */
+pub async fn select_all_files_by_target_id(conn: &Object, target_id: &str) -> Result<Vec<File>> {
+    let target_id = Uuid::parse_str(target_id)?;
+    let rows = conn
+        .query("select * from files where target_id = $1", &[&target_id])
+        .await?;
+    rows.into_iter().map(File::try_from).collect()
+}
+
+/**
+ * Every file of a deleted account, marked deleted first and deleted for good after the grace period.
+ */
+pub async fn delete_files_by_target_id(
+    conn: &Transaction<'_>,
+    target_id: &str,
+    purge: bool,
+) -> Result<u64> {
+    let target_id = Uuid::parse_str(target_id)?;
+    let query = if purge {
+        "delete from files where target_id = $1"
+    } else {
+        "update files set deleted = now() where target_id = $1 and deleted = 'infinity'"
+    };
+    let count = conn.execute(query, &[&target_id]).await?;
+    Ok(count)
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use service_utils::Env;
 
-async fn connect_to_bucket(env: &Env) -> Result<s3::Bucket> {
+pub async fn connect_to_bucket(env: &Env) -> Result<s3::Bucket> {
     let s3_access_key = env.s3_access_key.clone();
     let s3_secret_key = env.s3_secret_key.clone();
     let s3_endpoint = env.s3_endpoint.clone();
@@ -1,5 +1,5 @@
 use crate::proto::utils_service_server::UtilsService;
-use crate::proto::{Count, Email, Empty, File, Id, Page};
+use crate::proto::{Count, DataExportRequest, Email, Empty, File, Id, Page, UserDataDeletion};
 use crate::MyService;
 use anyhow::Result;
 use tokio_stream::wrappers::ReceiverStream;
@@ -61,4 +61,18 @@ impl UtilsService for MyService {
     async fn delete_file_by_id(&self, request: Request<Id>) -> Result<Response<Empty>, Status> {
         crate::file_service::delete_file_by_id(&self.env, &self.pool, request).await
     }
+
+    async fn delete_user_data(
+        &self,
+        request: Request<UserDataDeletion>,
+    ) -> Result<Response<Empty>, Status> {
+        crate::deletion_service::delete_user_data(&self.env, &self.pool, request).await
+    }
+
+    async fn create_data_export(
+        &self,
+        request: Request<DataExportRequest>,
+    ) -> Result<Response<File>, Status> {
+        crate::export_service::create_data_export(&self.env, &self.pool, request).await
+    }
 }
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::time::Duration;
+use tonic_health::server::HealthReporter;
+
+use crate::proto::utils_service_server::UtilsServiceServer;
+use crate::MyService;
+
+/**
+ * Readiness of `proto.UtilsService` in `grpc.health.v1`, checked every 10 seconds.
+ * The overall status, the empty service name, is always serving and acts as the liveness check.
+ */
+pub fn spawn_readiness(
+    mut reporter: HealthReporter,
+    pool: deadpool_postgres::Pool,
+    env: service_utils::Env,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(10));
+        let mut ready = None;
+        loop {
+            interval.tick().await;
+            let result = check(&pool, &env).await;
+            if ready != Some(result.is_ok()) {
+                match &result {
+                    Ok(()) => tracing::info!("Service is ready"),
+                    Err(err) => tracing::error!("Service is not ready: {:?}", err),
+                }
+                ready = Some(result.is_ok());
+            }
+            match result {
+                Ok(()) => {
+                    reporter
+                        .set_serving::<UtilsServiceServer<MyService>>()
+                        .await
+                }
+                Err(_) => {
+                    reporter
+                        .set_not_serving::<UtilsServiceServer<MyService>>()
+                        .await
+                }
+            }
+        }
+    });
+}
+
+async fn check(pool: &deadpool_postgres::Pool, env: &service_utils::Env) -> Result<()> {
+    // A pool without free connections waits, don't let it hang the check
+    tokio::time::timeout(Duration::from_secs(5), async {
+        let conn = pool.get().await?;
+        conn.simple_query("select 1").await?;
+        // The bucket of the files, listing a single key is enough to check the credentials
+        let bucket = crate::file_utils::connect_to_bucket(env).await?;
+        let (_, status) = bucket
+            .list_page(String::new(), None, None, None, Some(1))
+            .await?;
+        if status != 200 {
+            anyhow::bail!("S3 bucket returned status {}", status);
+        }
+        Ok(())
+    })
+    .await?
+}
@@ -1,6 +1,10 @@
 use anyhow::{Context, Result};
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 mod proto;
 
 #[derive(Clone)]
@@ -13,7 +17,7 @@ pub struct Env {
     pub s3_access_key: String,
     pub s3_secret_key: String,
     pub s3_endpoint: String,
-    pub jwt_secret: String,
+    pub jwt_keys: JwtKeys,
 }
 
 pub fn init_envs() -> Result<Env> {
@@ -27,10 +31,240 @@ pub fn init_envs() -> Result<Env> {
         s3_access_key: std::env::var("S3_ACCESS_KEY").context("S3_ACCESS_KEY is not set")?,
         s3_secret_key: std::env::var("S3_SECRET_KEY").context("S3_SECRET_KEY is not set")?,
         s3_endpoint: std::env::var("S3_ENDPOINT").context("S3_ENDPOINT is not set")?,
-        jwt_secret: std::env::var("JWT_SECRET").context("JWT_SECRET is not set")?,
+        jwt_keys: init_jwt_keys()?,
     })
 }
 
+/**
+ * Keys used to verify the tokens signed by the client and the other services.
+ * Only public keys are accepted, except for the legacy HS256 mode with shared secrets.
+ * The key set is shared by every clone and swapped on reload, see `spawn_reload`.
+ */
+#[derive(Clone)]
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    key_set: Arc<RwLock<KeySet>>,
+}
+
+struct KeySet {
+    // kid -> key, every key that is still valid
+    decoding_keys: HashMap<String, DecodingKey>,
+}
+
+impl JwtKeys {
+    pub fn decode<T: serde::de::DeserializeOwned>(
+        &self,
+        token: &str,
+        kind: &TokenKind,
+    ) -> Result<T> {
+        let header = jsonwebtoken::decode_header(token)?;
+        // The algorithm comes from our config, never from the token itself.
+        if header.alg != self.algorithm {
+            return Err(anyhow::anyhow!(
+                "Unexpected token algorithm: {:?}",
+                header.alg
+            ));
+        }
+        if header.typ.as_deref() != Some(kind.typ) {
+            return Err(anyhow::anyhow!("Unexpected token type: {:?}", header.typ));
+        }
+        let kid = header.kid.as_deref().unwrap_or_default();
+        if !self.may_sign(kid, kind) {
+            return Err(anyhow::anyhow!(
+                "Token kid {:?} can't sign {} tokens",
+                kid,
+                kind.typ
+            ));
+        }
+        let key_set = self.key_set.read().expect("JWT key set lock poisoned");
+        let key = key_set
+            .decoding_keys
+            .get(kid)
+            .ok_or_else(|| anyhow::anyhow!("Unknown token kid: {:?}", header.kid))?;
+        let mut validation = Validation::new(self.algorithm);
+        if let Some(audience) = kind.audience {
+            validation.set_audience(&[audience]);
+            validation.set_required_spec_claims(&["exp", "aud"]);
+        }
+        let token = jsonwebtoken::decode::<T>(token, key, &validation)?;
+        Ok(token.claims)
+    }
+
+    /**
+     * The shared secrets of the legacy HS256 mode are known to every service and the client,
+     * so they can't tell the signers apart. Only the public keys are bound to their signer.
+     */
+    fn may_sign(&self, kid: &str, kind: &TokenKind) -> bool {
+        self.algorithm == Algorithm::HS256 || kind.signers.contains(&key_signer(kid))
+    }
+
+    /**
+     * Reload the key set every minute, so keys can be rotated without a restart.
+     * If the new keys are invalid, the current ones are kept.
+     */
+    pub fn spawn_reload(&self) {
+        let jwt_keys = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            // The first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match load_key_set(jwt_keys.algorithm) {
+                    Ok(key_set) => {
+                        *jwt_keys.key_set.write().expect("JWT key set lock poisoned") = key_set
+                    }
+                    Err(e) => tracing::error!("Failed to reload JWT keys: {:?}", e),
+                }
+            }
+        });
+    }
+}
+
+/**
+ * What a token is for: its `typ` header, the signers allowed to sign it and its `aud` claim,
+ * see `TokenKind` in service-users.
+ */
+pub struct TokenKind<'a> {
+    pub typ: &'a str,
+    pub signers: &'a [&'a str],
+    pub audience: Option<&'a str>,
+}
+
+// Signed by the client for the signed in user
+pub const CLIENT_TOKEN: TokenKind = TokenKind {
+    typ: "JWT",
+    signers: &["client"],
+    audience: None,
+};
+// A call between services, signed by the caller for the receiving service
+pub const SERVICE_TOKEN_TYP: &str = "service+jwt";
+
+/**
+ * The signer of a key is its kid, up to the first `-`, e.g. `auth` for `auth` or `auth-2024-06`.
+ */
+fn key_signer(kid: &str) -> &str {
+    kid.split('-').next().unwrap_or_default()
+}
+
+/**
+ * Every JWT_* variable can also be read from a file, with its path in JWT_*_FILE.
+ * Files are read again on each reload, this is how keys are rotated without a restart.
+ */
+fn jwt_var(name: &str) -> Result<Option<String>> {
+    let value = match std::env::var(format!("{}_FILE", name)) {
+        Ok(path) => std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}_FILE", name))?,
+        Err(_) => std::env::var(name).unwrap_or_default(),
+    };
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/**
+ * JWT_ALGORITHM is HS256 (legacy, default), RS256 or EdDSA.
+ */
+fn init_jwt_keys() -> Result<JwtKeys> {
+    let algorithm = match jwt_var("JWT_ALGORITHM")?.as_deref() {
+        None | Some("HS256") => Algorithm::HS256,
+        Some("RS256") => Algorithm::RS256,
+        Some("EdDSA") => Algorithm::EdDSA,
+        Some(other) => return Err(anyhow::anyhow!("Unsupported JWT_ALGORITHM: {}", other)),
+    };
+    Ok(JwtKeys {
+        algorithm,
+        key_set: Arc::new(RwLock::new(load_key_set(algorithm)?)),
+    })
+}
+
+fn load_key_set(algorithm: Algorithm) -> Result<KeySet> {
+    let secrets = load_secrets(algorithm)?;
+    Ok(KeySet {
+        decoding_keys: load_decoding_keys(algorithm, &secrets)?,
+    })
+}
+
+/**
+ * JWT_SECRETS is a JSON object of kid -> secret, the legacy JWT_SECRET is used for tokens without kid.
+ */
+fn load_secrets(algorithm: Algorithm) -> Result<HashMap<String, String>> {
+    // Shared secrets are only used by the legacy HS256 mode
+    if algorithm != Algorithm::HS256 {
+        return Ok(HashMap::new());
+    }
+    let mut secrets: HashMap<String, String> = match jwt_var("JWT_SECRETS")? {
+        Some(secrets) => {
+            serde_json::from_str(&secrets).context("JWT_SECRETS is not a valid JSON object")?
+        }
+        None => HashMap::new(),
+    };
+    if secrets.contains_key("") {
+        return Err(anyhow::anyhow!("JWT_SECRETS contains a secret without kid"));
+    }
+    if let Some(secret) = jwt_var("JWT_SECRET")? {
+        secrets.insert(String::new(), secret);
+    }
+    Ok(secrets)
+}
+
+/**
+ * JWT_PUBLIC_KEYS is a JWKS document, the same one served by service-auth.
+ * Every key needs a kid, and only public keys for the configured algorithm are accepted.
+ */
+fn load_public_keys(algorithm: Algorithm) -> Result<JwkSet> {
+    let jwks = jwt_var("JWT_PUBLIC_KEYS")?.context("JWT_PUBLIC_KEYS is not set")?;
+    // Private parameters (`d`, `p`, `q`...) are dropped while parsing.
+    let jwks: JwkSet =
+        serde_json::from_str(&jwks).context("JWT_PUBLIC_KEYS is not a valid JWKS")?;
+    for jwk in &jwks.keys {
+        let kid = jwk
+            .common
+            .key_id
+            .as_deref()
+            .filter(|kid| !kid.is_empty())
+            .context("JWT_PUBLIC_KEYS contains a key without kid")?;
+        match (&jwk.algorithm, algorithm) {
+            (AlgorithmParameters::RSA(_), Algorithm::RS256) => {}
+            (AlgorithmParameters::OctetKeyPair(_), Algorithm::EdDSA) => {}
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "JWT_PUBLIC_KEYS key {} is not a {:?} public key",
+                    kid,
+                    algorithm
+                ))
+            }
+        }
+    }
+    if jwks.keys.is_empty() {
+        return Err(anyhow::anyhow!("JWT_PUBLIC_KEYS has no keys"));
+    }
+    Ok(jwks)
+}
+
+/**
+ * Every key still valid for verification, by kid. The legacy JWT_SECRET is stored under an empty kid.
+ */
+fn load_decoding_keys(
+    algorithm: Algorithm,
+    secrets: &HashMap<String, String>,
+) -> Result<HashMap<String, DecodingKey>> {
+    let mut decoding_keys = HashMap::new();
+    if algorithm == Algorithm::HS256 {
+        for (kid, secret) in secrets {
+            decoding_keys.insert(kid.clone(), DecodingKey::from_secret(secret.as_bytes()));
+        }
+        if decoding_keys.is_empty() {
+            return Err(anyhow::anyhow!("JWT_SECRET is not set"));
+        }
+        return Ok(decoding_keys);
+    }
+    for jwk in load_public_keys(algorithm)?.keys {
+        let kid = jwk.common.key_id.clone().unwrap_or_default();
+        decoding_keys.insert(kid, DecodingKey::from_jwk(&jwk)?);
+    }
+    Ok(decoding_keys)
+}
+
 pub fn connect_to_db(env: &Env) -> Result<deadpool_postgres::Pool> {
     let tokio_config = tokio_postgres::Config::from_str(&env.database_url)?;
     let mgr_config = ManagerConfig {
@@ -52,44 +286,146 @@ pub fn connect_to_db(env: &Env) -> Result<deadpool_postgres::Pool> {
     Ok(pool)
 }
 
+/**
+ * Why a request was rejected. Handlers return it as a `tonic::Status` with `?`, which is too
+ * large to be the error of every check.
+ */
+#[derive(Debug)]
+pub enum RequestError {
+    Unauthenticated(&'static str),
+    PermissionDenied(&'static str),
+    InvalidArgument(String),
+    Internal(&'static str),
+}
+
+impl From<RequestError> for tonic::Status {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::Unauthenticated(message) => tonic::Status::unauthenticated(message),
+            RequestError::PermissionDenied(message) => tonic::Status::permission_denied(message),
+            RequestError::InvalidArgument(message) => tonic::Status::invalid_argument(message),
+            RequestError::Internal(message) => tonic::Status::internal(message),
+        }
+    }
+}
+
+/**
+ * The admin acting as the user, the `act` claim of RFC 8693, see `ImpersonateUser`.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Actor {
+    pub sub: String,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Claims {
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    // `UserRole` and its `Permission`s, forwarded by the client from `Auth`
+    #[serde(default)]
+    pub role: i32,
+    #[serde(default)]
+    pub permissions: Vec<i32>,
 }
-pub fn auth(
-    metadata: &tonic::metadata::MetadataMap,
-    jwt_secret: &str,
-) -> Result<Claims, tonic::Status> {
+
+impl Claims {
+    /**
+     * The shared authorization check, fails unless the role of the user grants the permission.
+     */
+    pub fn require(&self, permission: i32) -> Result<(), RequestError> {
+        if !self.permissions.contains(&permission) {
+            tracing::error!("Missing permission {:?}: {:?}", permission, self.id);
+            return Err(RequestError::PermissionDenied("Permission denied"));
+        }
+        Ok(())
+    }
+}
+fn extract_token(metadata: &tonic::metadata::MetadataMap) -> Result<&str, RequestError> {
     let token = match metadata.get("x-authorization") {
         Some(token) => token,
         None => {
             tracing::error!("Missing authorization token");
-            return Err(tonic::Status::unauthenticated(
-                "Missing authorization token",
-            ));
+            return Err(RequestError::Unauthenticated("Missing authorization token"));
         }
     };
     let token = token
         .to_str()
         .map_err(|e| {
             tracing::error!("Failed to parse authorization token: {:?}", e);
-            tonic::Status::unauthenticated("Invalid authorization token")
+            RequestError::Unauthenticated("Invalid authorization token")
         })?
         .strip_prefix("bearer ")
         .ok_or_else(|| {
             tracing::error!("Failed to parse authorization token");
-            tonic::Status::unauthenticated("Invalid authorization token")
+            RequestError::Unauthenticated("Invalid authorization token")
         })?;
+    Ok(token)
+}
 
-    let token_message = jsonwebtoken::decode::<Claims>(
-        token,
-        &jsonwebtoken::DecodingKey::from_secret(jwt_secret.as_ref()),
-        &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256),
-    )
-    .map_err(|e| {
-        tracing::error!("Failed to decode authorization token: {:?}", e);
-        tonic::Status::unauthenticated("Invalid authorization token")
-    })?;
-
-    Ok(token_message.claims)
+pub fn auth(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+) -> Result<Claims, RequestError> {
+    let token = extract_token(metadata)?;
+    let claims = jwt_keys
+        .decode::<Claims>(token, &CLIENT_TOKEN)
+        .map_err(|e| {
+            tracing::error!("Failed to decode authorization token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    if let Some(act) = &claims.act {
+        tracing::info!(act = %act.sub, "Impersonated request");
+    }
+
+    Ok(claims)
+}
+
+/**
+ * Call from another service, with no user behind it, see `DeleteUserData`.
+ */
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ServiceClaims {
+    pub service: String,
+    pub exp: i64,
+}
+/**
+ * Tells a call from another service from a call for a user, by the `typ` header. The token
+ * itself is checked by `decode_service_token` or `auth`.
+ */
+pub fn is_service_token(metadata: &tonic::metadata::MetadataMap) -> bool {
+    extract_token(metadata)
+        .ok()
+        .and_then(|token| jsonwebtoken::decode_header(token).ok())
+        .is_some_and(|header| header.typ.as_deref() == Some(SERVICE_TOKEN_TYP))
+}
+
+/**
+ * Only accepts a token signed by the key of one of `callers`, e.g. `users`, for this service.
+ */
+pub fn decode_service_token(
+    metadata: &tonic::metadata::MetadataMap,
+    jwt_keys: &JwtKeys,
+    callers: &[&str],
+) -> Result<ServiceClaims, RequestError> {
+    let token = extract_token(metadata)?;
+    let kind = TokenKind {
+        typ: SERVICE_TOKEN_TYP,
+        signers: callers,
+        audience: Some("service-utils"),
+    };
+    let claims = jwt_keys
+        .decode::<ServiceClaims>(token, &kind)
+        .map_err(|e| {
+            tracing::error!("Failed to decode service token: {:?}", e);
+            RequestError::Unauthenticated("Invalid authorization token")
+        })?;
+    if !callers
+        .iter()
+        .any(|caller| claims.service == format!("service-{}", caller))
+    {
+        tracing::error!("Unexpected service: {:?}", claims.service);
+        return Err(RequestError::PermissionDenied("Permission denied"));
+    }
+    Ok(claims)
 }
@@ -1,10 +1,13 @@
 mod grpc;
+mod health;
+mod deletion_service;
 mod file_service;
 mod file_utils;
 mod file_db;
 mod email_service;
 mod email_validation;
 mod email_db;
+mod export_service;
 mod migrations;
 mod proto;
 
@@ -25,6 +28,9 @@ async fn main() -> Result<()> {
     let filter = &env.rust_log;
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
+    // Reload the JWT keys periodically, so they can be rotated without a restart
+    env.jwt_keys.spawn_reload();
+
     // Connect to database
     let pool = service_utils::connect_to_db(&env).context("Failed to connect to database")?;
     tracing::info!("Connected to database");
@@ -38,9 +44,13 @@ async fn main() -> Result<()> {
     // Run gRPC server
     let addr = format!("[::]:{}", env.port).parse()?;
     tracing::info!("gRPC server started on port: {:?}", env.port);
+    // Standard gRPC health checks, the service is ready once its dependencies answer
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health::spawn_readiness(health_reporter, pool.clone(), env.clone());
     let server = MyService { env, pool };
     let svc = UtilsServiceServer::new(server);
     tonic::transport::Server::builder()
+        .add_service(health_service)
         .add_service(svc)
         .serve(addr)
         .await